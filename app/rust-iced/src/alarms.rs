@@ -0,0 +1,457 @@
+/*
+
+MIT License
+
+Copyright (c) 2024 Petr Zakopal, Deparment of Electric Drives and Traction, CTU FEE
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+/*----------------------------------------------------------------------------*/
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::time::Instant;
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+// Most events kept in the event log, the oldest events are dropped
+pub const ALARM_LOG_MAX_EVENTS: usize = 1000;
+
+// Condition of the channel raising the alarm
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum AlarmCondition {
+    #[default]
+    Above,          // Value above the high limit
+    Below,          // Value below the low limit
+    OutOfBand,      // Value outside of the low..high band
+    RateOfChange,   // Change between two samples larger than the high limit
+    Stale,          // No sample of the channel received for the stale time
+}
+
+impl AlarmCondition {
+    pub const ALL: [AlarmCondition; 5] = [
+        AlarmCondition::Above,
+        AlarmCondition::Below,
+        AlarmCondition::OutOfBand,
+        AlarmCondition::RateOfChange,
+        AlarmCondition::Stale,
+    ];
+
+    // Limits of the condition displayed in the alarm table
+    pub fn get_parameters(&self) -> &'static [AlarmField] {
+        match self {
+            AlarmCondition::Above | AlarmCondition::RateOfChange => &[AlarmField::High],
+            AlarmCondition::Below => &[AlarmField::Low],
+            AlarmCondition::OutOfBand => &[AlarmField::Low, AlarmField::High],
+            AlarmCondition::Stale => &[AlarmField::StaleTime],
+        }
+    }
+}
+
+// Displayed in the PickList
+impl fmt::Display for AlarmCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AlarmCondition::Above => write!(f, "Above"),
+            AlarmCondition::Below => write!(f, "Below"),
+            AlarmCondition::OutOfBand => write!(f, "Out of band"),
+            AlarmCondition::RateOfChange => write!(f, "Rate of change"),
+            AlarmCondition::Stale => write!(f, "No update"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Serialize, Deserialize)]
+pub enum AlarmSeverity {
+    Info,
+    #[default]
+    Warning,
+    Critical,
+}
+
+impl AlarmSeverity {
+    pub const ALL: [AlarmSeverity; 3] =
+        [AlarmSeverity::Info, AlarmSeverity::Warning, AlarmSeverity::Critical];
+}
+
+impl fmt::Display for AlarmSeverity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AlarmSeverity::Info => write!(f, "Info"),
+            AlarmSeverity::Warning => write!(f, "Warning"),
+            AlarmSeverity::Critical => write!(f, "Critical"),
+        }
+    }
+}
+
+// What happens when the alarm is raised
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlarmAction {
+    Highlight,  // Marker in the chart and the banner of the active alarms
+    Log,        // Entry in the event log
+    AutoStop,   // Stop fetching the data from the serial port
+    AutoSave,   // Save the displayed buffer to a CSV file
+}
+
+impl AlarmAction {
+    pub const ALL: [AlarmAction; 4] = [
+        AlarmAction::Highlight,
+        AlarmAction::Log,
+        AlarmAction::AutoStop,
+        AlarmAction::AutoSave,
+    ];
+}
+
+// Displayed as the label of the checkbox
+impl fmt::Display for AlarmAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AlarmAction::Highlight => write!(f, "Highlight"),
+            AlarmAction::Log => write!(f, "Log"),
+            AlarmAction::AutoStop => write!(f, "Auto-stop"),
+            AlarmAction::AutoSave => write!(f, "Auto-save"),
+        }
+    }
+}
+
+// Editable fields of the alarm table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AlarmField {
+    Channel,
+    Low,
+    High,
+    StaleTime,
+}
+
+// Displayed as the placeholder of the input
+impl fmt::Display for AlarmField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AlarmField::Channel => write!(f, "Channel ID"),
+            AlarmField::Low => write!(f, "Low limit"),
+            AlarmField::High => write!(f, "High limit"),
+            AlarmField::StaleTime => write!(f, "No update [ms]"),
+        }
+    }
+}
+
+// Alarm rule of one channel, the limits are compared with the values scaled by the device profile
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlarmRule {
+    pub channel: String,            // Variable ID of the watched channel
+    pub condition: AlarmCondition,
+    pub low: f64,                   // Low limit of the below and out of band conditions
+    pub high: f64,                  // High limit, or the largest change between two samples
+    pub stale_time: f64,            // Longest time without a sample in ms
+    pub severity: AlarmSeverity,
+    pub highlight: bool,
+    pub log: bool,
+    pub auto_stop: bool,
+    pub auto_save: bool,
+    #[serde(skip)]
+    pub active: bool,               // Condition is met, events are generated only on the changes
+    #[serde(skip)]
+    previous: Option<f64>,          // Last value for the rate of change
+    #[serde(skip)]
+    last_update: Option<Instant>,   // Receive time of the last sample for the stale condition
+    #[serde(skip)]
+    inputs: HashMap<AlarmField, String>, // Strings inserted by user which are not parsed yet
+}
+
+impl Default for AlarmRule {
+    fn default() -> Self {
+        Self {
+            channel: String::new(),
+            condition: AlarmCondition::default(),
+            low: 0.0,
+            high: 0.0,
+            stale_time: 1000.0,
+            severity: AlarmSeverity::default(),
+            highlight: true,
+            log: true,
+            auto_stop: false,
+            auto_save: false,
+            active: false,
+            previous: None,
+            last_update: None,
+            inputs: HashMap::new(),
+        }
+    }
+}
+
+impl AlarmRule {
+    // Checking the received sample of the channel, returns the new state when it was changed
+    pub fn check(&mut self, value: f64, now: Instant) -> Option<bool> {
+        self.last_update = Some(now);
+        if value.is_nan() {
+            return None;
+        }
+        let previous = self.previous.replace(value);
+        let active = match self.condition {
+            AlarmCondition::Above => value > self.high,
+            AlarmCondition::Below => value < self.low,
+            AlarmCondition::OutOfBand => value < self.low || value > self.high,
+            AlarmCondition::RateOfChange => {
+                previous.is_some_and(|previous| (value - previous).abs() > self.high)
+            }
+            AlarmCondition::Stale => false,
+        };
+        self.set_active(active)
+    }
+
+    // Checking the time since the last sample, called also when no frames are received, the time
+    // is counted from the first check when the channel was not received yet
+    pub fn check_stale(&mut self, now: Instant) -> Option<bool> {
+        if self.condition != AlarmCondition::Stale {
+            return None;
+        }
+        let last_update = *self.last_update.get_or_insert(now);
+        let stale = now.duration_since(last_update).as_secs_f64() * 1000.0 > self.stale_time;
+        self.set_active(stale)
+    }
+
+    fn set_active(&mut self, active: bool) -> Option<bool> {
+        if active == self.active {
+            return None;
+        }
+        self.active = active;
+        Some(active)
+    }
+
+    // Rule starts again without the history, e.g. after the acquisition is restarted
+    pub fn reset(&mut self) {
+        self.active = false;
+        self.previous = None;
+        self.last_update = None;
+    }
+
+    // Description of the condition used in the event log and in the banner of the active alarms
+    pub fn describe(&self) -> String {
+        match self.condition {
+            AlarmCondition::Above => format!("{} above {}", self.channel, self.high),
+            AlarmCondition::Below => format!("{} below {}", self.channel, self.low),
+            AlarmCondition::OutOfBand => {
+                format!("{} out of {}..{}", self.channel, self.low, self.high)
+            }
+            AlarmCondition::RateOfChange => {
+                format!("{} changed by more than {} per sample", self.channel, self.high)
+            }
+            AlarmCondition::Stale => {
+                format!("{} not updated for {} ms", self.channel, self.stale_time)
+            }
+        }
+    }
+
+    // Event of the changed state of the alarm, the value is added when it is known
+    pub fn get_event(&self, rule: usize, sample: u128, raised: bool, value: f64) -> AlarmEvent {
+        let description = match value.is_nan() {
+            true => self.describe(),
+            false => format!("{} (value {})", self.describe(), value),
+        };
+        AlarmEvent {
+            time: Local::now(),
+            sample,
+            rule,
+            channel: self.channel.clone(),
+            severity: self.severity,
+            raised,
+            log: self.log,
+            highlight: self.highlight,
+            message: if raised {
+                description
+            } else {
+                format!("Cleared: {}", description)
+            },
+        }
+    }
+
+    pub fn get_action(&self, action: AlarmAction) -> bool {
+        match action {
+            AlarmAction::Highlight => self.highlight,
+            AlarmAction::Log => self.log,
+            AlarmAction::AutoStop => self.auto_stop,
+            AlarmAction::AutoSave => self.auto_save,
+        }
+    }
+
+    pub fn set_action(&mut self, action: AlarmAction, enabled: bool) {
+        match action {
+            AlarmAction::Highlight => self.highlight = enabled,
+            AlarmAction::Log => self.log = enabled,
+            AlarmAction::AutoStop => self.auto_stop = enabled,
+            AlarmAction::AutoSave => self.auto_save = enabled,
+        }
+    }
+
+    // Text displayed in the input of the alarm table
+    pub fn get_input(&self, field: AlarmField) -> String {
+        if let Some(input) = self.inputs.get(&field) {
+            return input.clone();
+        }
+        match field {
+            AlarmField::Channel => self.channel.clone(),
+            AlarmField::Low => self.low.to_string(),
+            AlarmField::High => self.high.to_string(),
+            AlarmField::StaleTime => self.stale_time.to_string(),
+        }
+    }
+
+    // Setting the field from the alarm table, the value is applied when it can be parsed
+    pub fn set_field(&mut self, field: AlarmField, value: String) {
+        let parsed = match field {
+            AlarmField::Channel => {
+                self.channel = value.trim().to_string();
+                true
+            }
+            AlarmField::Low => value.parse::<f64>().map(|low| self.low = low).is_ok(),
+            AlarmField::High => value.parse::<f64>().map(|high| self.high = high).is_ok(),
+            AlarmField::StaleTime => match value.parse::<f64>() {
+                Ok(stale_time) if stale_time > 0.0 => {
+                    self.stale_time = stale_time;
+                    true
+                }
+                _ => false,
+            },
+        };
+
+        if parsed {
+            self.inputs.remove(&field);
+            self.reset();
+        } else {
+            println!("Error parsing the {:?} of the alarm: {}", field, value);
+            self.inputs.insert(field, value);
+        }
+    }
+}
+
+// Raised or cleared alarm
+#[derive(Debug, Clone)]
+pub struct AlarmEvent {
+    pub time: DateTime<Local>,
+    pub sample: u128,               // X axis value of the sample which changed the alarm
+    pub rule: usize,                // Index of the rule in the device profile
+    pub channel: String,
+    pub severity: AlarmSeverity,
+    pub raised: bool,               // False when the condition is no longer met
+    pub log: bool,                  // Displayed in the event log
+    pub highlight: bool,            // Marked in the chart
+    pub message: String,
+}
+
+// Timestamped events of the alarms, the newest event is the last, events without the log action
+// are kept only for the markers in the chart
+#[derive(Debug, Clone, Default)]
+pub struct AlarmLog {
+    pub events: VecDeque<AlarmEvent>,
+}
+
+impl AlarmLog {
+    pub fn push(&mut self, event: AlarmEvent) {
+        self.events.push_back(event);
+        if self.events.len() > ALARM_LOG_MAX_EVENTS {
+            self.events.pop_front();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.events.clear();
+    }
+
+    // Samples of the cleared store no longer exist, so the events are not marked in the chart
+    pub fn clear_highlights(&mut self) {
+        for event in self.events.iter_mut() {
+            event.highlight = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn get_rule(condition: AlarmCondition, low: f64, high: f64) -> AlarmRule {
+        AlarmRule {
+            channel: "@000".to_string(),
+            condition,
+            low,
+            high,
+            ..Default::default()
+        }
+    }
+
+    // New states returned for the values checked one after another
+    fn check_values(rule: &mut AlarmRule, values: &[f64]) -> Vec<Option<bool>> {
+        let now = Instant::now();
+        values.iter().map(|value| rule.check(*value, now)).collect()
+    }
+
+    #[test]
+    fn above_transitions() {
+        let mut rule = get_rule(AlarmCondition::Above, 0.0, 10.0);
+        assert_eq!(
+            check_values(&mut rule, &[5.0, 11.0, 12.0, f64::NAN, 10.0, 9.0]),
+            [None, Some(true), None, None, Some(false), None]
+        );
+        assert!(!rule.active);
+    }
+
+    #[test]
+    fn out_of_band_transitions() {
+        let mut rule = get_rule(AlarmCondition::OutOfBand, -1.0, 1.0);
+        assert_eq!(
+            check_values(&mut rule, &[0.0, -2.0, 2.0, 0.5, 1.5]),
+            [None, Some(true), None, Some(false), Some(true)]
+        );
+    }
+
+    #[test]
+    fn rate_of_change_transitions() {
+        let mut rule = get_rule(AlarmCondition::RateOfChange, 0.0, 1.0);
+        // First sample has no previous value, NaN samples keep the previous value
+        assert_eq!(
+            check_values(&mut rule, &[100.0, 100.5, 102.0, f64::NAN, 102.5, 101.0]),
+            [None, None, Some(true), None, Some(false), Some(true)]
+        );
+        rule.reset();
+        assert_eq!(check_values(&mut rule, &[0.0]), [None]);
+    }
+
+    #[test]
+    fn stale_transitions() {
+        let mut rule = get_rule(AlarmCondition::Stale, 0.0, 0.0);
+        rule.stale_time = 100.0;
+        let start = Instant::now();
+        // Time is counted from the first check when no sample was received
+        assert_eq!(rule.check_stale(start), None);
+        assert_eq!(rule.check_stale(start + Duration::from_millis(50)), None);
+        assert_eq!(rule.check_stale(start + Duration::from_millis(150)), Some(true));
+        assert_eq!(rule.check_stale(start + Duration::from_millis(200)), None);
+        // Received sample clears the alarm
+        assert_eq!(rule.check(1.0, start + Duration::from_millis(210)), Some(false));
+        assert_eq!(rule.check_stale(start + Duration::from_millis(300)), None);
+        assert_eq!(rule.check_stale(start + Duration::from_millis(320)), Some(true));
+
+        // Other conditions are not stale
+        let mut rule = get_rule(AlarmCondition::Above, 0.0, 1.0);
+        assert_eq!(rule.check_stale(start + Duration::from_secs(10)), None);
+    }
+}
@@ -0,0 +1,310 @@
+/*
+
+MIT License
+
+Copyright (c) 2024 Petr Zakopal, Deparment of Electric Drives and Traction, CTU FEE
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+/*----------------------------------------------------------------------------*/
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::signal_store::SignalStore;
+
+// Largest number of samples read for the logic lanes, longer ranges take every n-th sample, so
+// very short pulses may be missed until the chart is zoomed in
+const LOGIC_MAX_SAMPLES: usize = 100_000;
+
+// Named bits of a packed status/flag register, the bits are taken from the fixed point word as it
+// was sent by the FPGA
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BitField {
+    pub channel: String,            // Variable ID of the status word
+    pub name: String,               // Name of the bit or of the multi-bit field
+    pub offset: u32,                // Position of the least significant bit
+    pub width: u32,                 // Number of bits, 1 is drawn as a digital trace
+    pub labels: Vec<String>,        // Labels of the values of a multi-bit field, e.g. states
+    #[serde(skip)]
+    inputs: HashMap<BitFieldField, String>, // Strings inserted by user which are not parsed yet
+}
+
+// Editable fields of the bit field table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BitFieldField {
+    Channel,
+    Name,
+    Offset,
+    Width,
+    Labels,
+}
+
+impl BitFieldField {
+    pub const ALL: [BitFieldField; 5] = [
+        BitFieldField::Channel,
+        BitFieldField::Name,
+        BitFieldField::Offset,
+        BitFieldField::Width,
+        BitFieldField::Labels,
+    ];
+}
+
+// Displayed as the placeholder of the input
+impl std::fmt::Display for BitFieldField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BitFieldField::Channel => write!(f, "Channel ID"),
+            BitFieldField::Name => write!(f, "Name"),
+            BitFieldField::Offset => write!(f, "LSB"),
+            BitFieldField::Width => write!(f, "Width"),
+            BitFieldField::Labels => write!(f, "Labels, e.g. IDLE, RUN or S0..S12"),
+        }
+    }
+}
+
+impl Default for BitField {
+    fn default() -> Self {
+        Self {
+            channel: String::new(),
+            name: String::new(),
+            offset: 0,
+            width: 1,
+            labels: Vec::new(),
+            inputs: HashMap::new(),
+        }
+    }
+}
+
+impl BitField {
+    // Value of the field from the fixed point word of the channel as received from the FPGA
+    pub fn decode(&self, word: u32) -> u32 {
+        let mask = if self.width >= 32 {
+            u32::MAX
+        } else {
+            (1 << self.width) - 1
+        };
+        word.checked_shr(self.offset).unwrap_or(0) & mask
+    }
+
+    // Name displayed next to the lane
+    pub fn get_lane_name(&self) -> String {
+        let name = if self.name.is_empty() {
+            format!("bit {}", self.offset)
+        } else {
+            self.name.clone()
+        };
+        if self.width > 1 {
+            format!("{}.{}[{}:{}]", self.channel, name, self.offset + self.width - 1, self.offset)
+        } else {
+            format!("{}.{}", self.channel, name)
+        }
+    }
+
+    // Text displayed in the input of the bit field table
+    pub fn get_input(&self, field: BitFieldField) -> String {
+        if let Some(input) = self.inputs.get(&field) {
+            return input.clone();
+        }
+        match field {
+            BitFieldField::Channel => self.channel.clone(),
+            BitFieldField::Name => self.name.clone(),
+            BitFieldField::Offset => self.offset.to_string(),
+            BitFieldField::Width => self.width.to_string(),
+            BitFieldField::Labels => self.labels.join(", "),
+        }
+    }
+
+    // Setting the field from the bit field table, the value is applied when it can be parsed
+    pub fn set_field(&mut self, field: BitFieldField, value: String) {
+        let parsed = match field {
+            BitFieldField::Channel => {
+                self.channel = value.trim().to_string();
+                true
+            }
+            BitFieldField::Name => {
+                self.name = value.trim().to_string();
+                true
+            }
+            BitFieldField::Offset => match value.parse::<u32>() {
+                Ok(offset) if offset < 32 => {
+                    self.offset = offset;
+                    true
+                }
+                _ => false,
+            },
+            BitFieldField::Width => match value.parse::<u32>() {
+                Ok(width) if (1..=32).contains(&width) => {
+                    self.width = width;
+                    true
+                }
+                _ => false,
+            },
+            BitFieldField::Labels => match parse_labels(&value) {
+                Some(labels) => {
+                    self.labels = labels;
+                    // The list is kept as written by user, e.g. "S0..S12"
+                    self.inputs.insert(field, value);
+                    return;
+                }
+                None => false,
+            },
+        };
+
+        if parsed {
+            self.inputs.remove(&field);
+        } else {
+            println!("Error parsing the {:?} of the bit field: {}", field, value);
+            self.inputs.insert(field, value);
+        }
+    }
+}
+
+// Comma separated labels, "S0..S12" expands to S0, S1, ..., S12
+fn parse_labels(value: &str) -> Option<Vec<String>> {
+    let mut labels = Vec::new();
+    for item in value.split(',').map(|item| item.trim()).filter(|item| !item.is_empty()) {
+        match item.split_once("..") {
+            Some((first, last)) => {
+                let prefix = first.trim_end_matches(|character: char| character.is_ascii_digit());
+                let first_number: usize = first[prefix.len()..].parse().ok()?;
+                let last_number: usize = last.strip_prefix(prefix).unwrap_or(last).parse().ok()?;
+                if last_number < first_number {
+                    return None;
+                }
+                labels.extend((first_number..=last_number).map(|number| format!("{}{}", prefix, number)));
+            }
+            None => labels.push(item.to_string()),
+        }
+    }
+    Some(labels)
+}
+
+// One lane of the logic analyzer, consecutive samples with the same value are merged into one
+// segment (first sample, end sample, value)
+#[derive(Debug, Clone)]
+pub struct LogicLane {
+    pub name: String,
+    pub width: u32,
+    pub labels: Vec<String>,
+    pub segments: Vec<(f64, f64, u32)>,
+}
+
+impl LogicLane {
+    // Lane of the bit field from the samples start..end of the store, None when the channel is
+    // not recorded or it is computed by the application, so it has no received words, missing
+    // samples end the segment
+    pub fn new(bit_field: &BitField, signal_store: &SignalStore, start: usize, end: usize) -> Option<Self> {
+        let position = *signal_store.channel_indexes.get(&bit_field.channel)?;
+        let channel = &signal_store.channels[position];
+        if !channel.has_words() {
+            return None;
+        }
+        let step = (end.saturating_sub(start)).div_ceil(LOGIC_MAX_SAMPLES).max(1);
+
+        let mut segments: Vec<(f64, f64, u32)> = Vec::new();
+        let mut current: Option<(usize, u32)> = None;
+        for index in (start..end).step_by(step) {
+            let value = channel.get_word(index).map(|word| bit_field.decode(word));
+            match (current, value) {
+                (Some((_, current_value)), Some(value)) if current_value == value => {}
+                _ => {
+                    if let Some((first, current_value)) = current {
+                        segments.push((first as f64, index as f64, current_value));
+                    }
+                    current = value.map(|value| (index, value));
+                }
+            }
+        }
+        if let Some((first, current_value)) = current {
+            segments.push((first as f64, end as f64, current_value));
+        }
+
+        Some(Self {
+            name: bit_field.get_lane_name(),
+            width: bit_field.width,
+            labels: bit_field.labels.clone(),
+            segments,
+        })
+    }
+
+    // Label of the value of the field, the number when there is no label
+    pub fn get_label(&self, value: u32) -> String {
+        match self.labels.get(value as usize) {
+            Some(label) => label.clone(),
+            None => value.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_bit_field(offset: u32, width: u32) -> BitField {
+        BitField {
+            offset,
+            width,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn decode_bits() {
+        let word = 0b1011_0110;
+        assert_eq!(get_bit_field(0, 1).decode(word), 0);
+        assert_eq!(get_bit_field(1, 1).decode(word), 1);
+        assert_eq!(get_bit_field(4, 4).decode(word), 0b1011);
+        assert_eq!(get_bit_field(0, 32).decode(0xDEAD_BEEF), 0xDEAD_BEEF);
+        assert_eq!(get_bit_field(31, 1).decode(0x8000_0000), 1);
+        assert_eq!(get_bit_field(31, 1).decode(0x7FFF_FFFF), 0);
+        // Field over the end of the word takes only the remaining bits
+        assert_eq!(get_bit_field(31, 32).decode(u32::MAX), 1);
+        assert_eq!(get_bit_field(28, 8).decode(0xA000_0000), 0xA);
+        // Sign extended negative word
+        assert_eq!(get_bit_field(0, 4).decode(-3i32 as u32), 0b1101);
+    }
+
+    #[test]
+    fn labels_with_ranges() {
+        let labels = |labels: &[&str]| Some(labels.iter().map(|label| label.to_string()).collect());
+        assert_eq!(parse_labels("IDLE, RUN ,FAULT"), labels(&["IDLE", "RUN", "FAULT"]));
+        let expanded = parse_labels("S0..S12").unwrap();
+        assert_eq!(expanded.len(), 13);
+        assert_eq!((expanded[0].as_str(), expanded[12].as_str()), ("S0", "S12"));
+        assert_eq!(parse_labels("OFF, S2..4"), labels(&["OFF", "S2", "S3", "S4"]));
+        assert_eq!(parse_labels(""), Some(Vec::new()));
+        assert_eq!(parse_labels("S5..S2"), None);
+        assert_eq!(parse_labels("S..T"), None);
+    }
+
+    #[test]
+    fn invalid_fields_are_rejected() {
+        let mut bit_field = BitField::default();
+        bit_field.set_field(BitFieldField::Offset, "32".to_string());
+        bit_field.set_field(BitFieldField::Width, "0".to_string());
+        assert_eq!((bit_field.offset, bit_field.width), (0, 1));
+        assert_eq!(bit_field.get_input(BitFieldField::Offset), "32");
+        bit_field.set_field(BitFieldField::Labels, "S0..S3".to_string());
+        assert_eq!(bit_field.labels.len(), 4);
+        assert_eq!(bit_field.get_input(BitFieldField::Labels), "S0..S3");
+    }
+}
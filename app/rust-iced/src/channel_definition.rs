@@ -0,0 +1,266 @@
+/*
+
+MIT License
+
+Copyright (c) 2024 Petr Zakopal, Deparment of Electric Drives and Traction, CTU FEE
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+/*----------------------------------------------------------------------------*/
+
+use std::fmt::Write;
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::channels::DeviceProfile;
+use crate::com_core::message::FIXED_POINT_FRACTIONAL_BITS_Q_32_15;
+use crate::gui::NavigationLayout;
+
+// Length of the variable ID in bytes, the ID is sent as ASCII in one VARIABLE_LENGTH wide word
+const VARIABLE_ID_LENGTH: usize = 4;
+// Most variables of one definition, every variable takes two words of the monitConnector and the
+// word index of the comUnit and the NUMBER_OF_VARIABLES parameter are 8 bits wide
+const MAX_CHANNELS: usize = 127;
+
+// One monitored variable as defined in the channel definition file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelDefinition {
+    pub id: String,             // Variable ID sent by the FPGA, 4 ASCII characters e.g. "@000"
+    pub name: String,           // Display name
+    pub q_format: String,       // Fixed point format of the variable in the FPGA e.g. "Q17.15"
+    #[serde(default)]
+    pub unit: String,           // Unit of the scaled value
+    #[serde(default = "default_scale")]
+    pub scale: f64,             // Physical value = fixed point value * scale
+    #[serde(default = "default_pane")]
+    pub pane: usize,            // Chart pane in which the channel is plotted, starting from 1
+    #[serde(default)]
+    pub source: Option<String>, // Verilog expression connected to the buffer of the variable
+}
+
+fn default_scale() -> f64 {
+    1.0
+}
+
+fn default_pane() -> usize {
+    1
+}
+
+impl ChannelDefinition {
+    // Number of fractional bits from the Q format, "Q17.15" and "Q15" both have 15 bits
+    pub fn get_fractional_bits(&self) -> Option<i32> {
+        let format = self.q_format.trim().strip_prefix(['Q', 'q'])?;
+        let fractional_bits = match format.split_once('.') {
+            Some((_, fractional_bits)) => fractional_bits,
+            None => format,
+        };
+        fractional_bits.parse().ok()
+    }
+
+    // Gain of the device profile, the received data are always decoded as Q32.15, so the other
+    // formats are rescaled by the difference of the fractional bits
+    pub fn get_gain(&self) -> f64 {
+        let fractional_bits = self
+            .get_fractional_bits()
+            .unwrap_or(FIXED_POINT_FRACTIONAL_BITS_Q_32_15);
+        self.scale * 2f64.powi(FIXED_POINT_FRACTIONAL_BITS_Q_32_15 - fractional_bits)
+    }
+
+    // Variable ID as the Verilog constant, "@000" = 32'h40303030
+    pub fn get_verilog_id(&self) -> String {
+        format!("32'h{}", hex::encode_upper(self.id.as_bytes()))
+    }
+}
+
+// Channel map shared by the application and the HDL
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ChannelDefinitionFile {
+    pub channels: Vec<ChannelDefinition>,
+}
+
+impl ChannelDefinitionFile {
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        let file = std::fs::File::open(path)?;
+        let definition: Self = serde_json::from_reader(file)?;
+        definition.validate()?;
+        Ok(definition)
+    }
+
+    // Checking that the definition can be sent by the monit module
+    fn validate(&self) -> std::io::Result<()> {
+        if self.channels.len() > MAX_CHANNELS {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "The definition has {} variables, the monit module can send at most {}",
+                    self.channels.len(),
+                    MAX_CHANNELS
+                ),
+            ));
+        }
+        for channel in self.channels.iter() {
+            if channel.id.len() != VARIABLE_ID_LENGTH || !channel.id.is_ascii() {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!(
+                        "Variable ID {:?} must be {} ASCII characters",
+                        channel.id, VARIABLE_ID_LENGTH
+                    ),
+                ));
+            }
+            if channel.get_fractional_bits().is_none() {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!(
+                        "Invalid Q format {:?} of the variable {}",
+                        channel.q_format, channel.id
+                    ),
+                ));
+            }
+            if channel.pane == 0 {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Pane of the variable {} starts from 1", channel.id),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    // Applying the names, units, scaling and panes to the device profile and to all desktops
+    pub fn apply(&self, device_profile: &mut DeviceProfile, navigation_layout: &mut NavigationLayout) {
+        for channel in self.channels.iter() {
+            device_profile.set_channel(&channel.id, &channel.name, &channel.unit, channel.get_gain());
+        }
+
+        let pane_count = self
+            .channels
+            .iter()
+            .map(|channel| channel.pane)
+            .max()
+            .unwrap_or(1);
+        for desktop in navigation_layout.available_desktops.iter() {
+            let pane_layout = navigation_layout
+                .pane_layouts
+                .entry(desktop.clone())
+                .or_default();
+            pane_layout.pane_count = pane_count;
+            pane_layout.pane_count_string = pane_count.to_string();
+            for channel in self.channels.iter() {
+                pane_layout
+                    .channel_panes
+                    .insert(channel.id.clone(), channel.pane - 1);
+            }
+        }
+    }
+
+    // Generating the part of monit.v which connects the variables to the monitConnector
+    // every variable takes two words of the connector, the ID and the buffer output with the data,
+    // the buffer wires and the dataBuffer instances are generated as well
+    pub fn generate_verilog(&self) -> String {
+        let number_of_variables = self.channels.len() * 2;
+        let mut verilog = String::new();
+
+        let _ = writeln!(verilog, "// Generated from the channel definition file, do not edit by hand");
+        let _ = writeln!(verilog, "// How many variables will be send in a message");
+        let _ = writeln!(
+            verilog,
+            "parameter [7:0] NUMBER_OF_VARIABLES = {};",
+            number_of_variables
+        );
+        let _ = writeln!(verilog);
+        let _ = writeln!(verilog, "initial begin");
+        let _ = writeln!(
+            verilog,
+            "    numberOfVariables=32'h{:08X};",
+            number_of_variables
+        );
+        let _ = writeln!(verilog, "end");
+        let _ = writeln!(verilog);
+
+        let _ = writeln!(verilog, "       // by variables for bytes and variables by senderConnector");
+        for (index, channel) in self.channels.iter().enumerate() {
+            let _ = writeln!(
+                verilog,
+                "       assign monitConnector[{}] = {}; // @varId = {} {} ({})",
+                2 * index,
+                channel.get_verilog_id(),
+                channel.id,
+                channel.name,
+                channel.q_format,
+            );
+            let _ = writeln!(
+                verilog,
+                "       assign monitConnector[{}] = buf_out_{};",
+                2 * index + 1,
+                2 * index + 1
+            );
+        }
+
+        let _ = writeln!(verilog);
+        let _ = writeln!(verilog, "       // Connecting the analysed variables to the buffer inputs");
+        for (index, channel) in self.channels.iter().enumerate() {
+            if let Some(source) = &channel.source {
+                let _ = writeln!(
+                    verilog,
+                    "       assign buf_in_{} = {};   // {}",
+                    2 * index + 1,
+                    source,
+                    channel.name
+                );
+            }
+        }
+
+        // Buffer of every variable in the same place as in monit.v, after the assignments
+        let buffer_indexes: Vec<usize> =
+            (0..self.channels.len()).map(|index| 2 * index + 1).collect();
+        let get_wires = |prefix: &str| {
+            buffer_indexes
+                .iter()
+                .map(|index| format!("{}{}", prefix, index))
+                .collect::<Vec<String>>()
+                .join(", ")
+        };
+        let _ = writeln!(verilog);
+        if !buffer_indexes.is_empty() {
+            let _ = writeln!(
+                verilog,
+                "wire [31:0] {}; // Buffer input - analysed values",
+                get_wires("buf_in_")
+            );
+            let _ = writeln!(
+                verilog,
+                "wire [31:0] {}; // Buffer output - values retrieved when reading from the buffer",
+                get_wires("buf_out_")
+            );
+        }
+        let _ = writeln!(verilog);
+        for index in buffer_indexes.iter() {
+            let _ = writeln!(
+                verilog,
+                "dataBuffer #() dataBuffer{index} (.clk(clk), .rst(rstBuffer), .wr_enable(wr_enable), .rd_enable(rd_enable), .buf_in(buf_in_{index}), .buf_out(buf_out_{index}));",
+            );
+        }
+
+        verilog
+    }
+}
@@ -0,0 +1,428 @@
+/*
+
+MIT License
+
+Copyright (c) 2024 Petr Zakopal, Deparment of Electric Drives and Traction, CTU FEE
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+/*----------------------------------------------------------------------------*/
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::alarms::{AlarmEvent, AlarmRule};
+use crate::bit_fields::BitField;
+use crate::expression::VirtualChannel;
+use crate::filters::ChannelFilter;
+use crate::gui::PaneLayout;
+use crate::transforms::DriveTransform;
+
+// Directory with the saved device profiles, next to the output files
+const PROFILE_DIRECTORY: &str = "./../rtm-profiles";
+// Default line width of a trace in pixels
+const DEFAULT_LINE_WIDTH: u32 = 2;
+
+// Display settings and scaling of one variable received from the FPGA
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChannelConfig {
+    pub name: String,               // Display name used in the legend instead of the variable ID
+    pub unit: String,               // Unit of the scaled value
+    pub color: Option<[u8; 3]>,     // RGB color of the trace, None picks the color from the palette
+    pub line_width: u32,            // Width of the trace in pixels
+    pub visible: bool,              // Hidden channels are still recorded, only not drawn
+    pub gain: f64,                  // Scaled value = raw value * gain + offset
+    pub offset: f64,
+}
+
+impl ChannelConfig {
+    fn new(id: &str) -> Self {
+        Self {
+            name: id.to_string(),
+            unit: String::new(),
+            color: None,
+            line_width: DEFAULT_LINE_WIDTH,
+            visible: true,
+            gain: 1.0,
+            offset: 0.0,
+        }
+    }
+
+    pub fn scale(&self, raw_value: f64) -> f64 {
+        raw_value * self.gain + self.offset
+    }
+}
+
+// Editable fields of the channel table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChannelField {
+    Name,
+    Unit,
+    Color,
+    LineWidth,
+    Gain,
+    Offset,
+}
+
+// Channel table of one device, saved as a JSON file to the profile directory
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DeviceProfile {
+    pub name: String,                               // Name of the profile and of the saved file
+    pub channels: BTreeMap<String, ChannelConfig>,  // Variable ID to the channel settings
+    #[serde(default)]
+    pub transforms: Vec<DriveTransform>,            // Derived channels computed in this order
+    #[serde(default)]
+    pub expressions: Vec<String>,                   // Virtual channels "name = expression"
+                                                    // computed after the transforms
+    #[serde(default)]
+    pub filters: Vec<ChannelFilter>,                // Filtered copies of the channels computed
+                                                    // after the virtual channels
+    #[serde(default)]
+    pub bit_fields: Vec<BitField>,                  // Named bits of the status words drawn as the
+                                                    // logic analyzer lanes
+    #[serde(default)]
+    pub alarms: Vec<AlarmRule>,                     // Limit alarms of the channels
+    #[serde(default)]
+    pub pane_layouts: HashMap<String, PaneLayout>,  // Chart panes of every desktop, copied from
+                                                    // the navigation layout when saved
+    #[serde(skip)]
+    virtual_channels: Vec<Result<VirtualChannel, String>>, // Parsed expressions with the state of
+                                                           // their functions, or the parse error
+    #[serde(skip)]
+    inputs: HashMap<(String, ChannelField), String>, // Strings inserted by user which are not
+                                                     // parsed yet, e.g. "1." or "#ff"
+}
+
+impl DeviceProfile {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    // Scaled value of the channel, channels without settings are not scaled
+    pub fn scale(&self, id: &str, raw_value: f64) -> f64 {
+        match self.channels.get(id) {
+            Some(channel) => channel.scale(raw_value),
+            None => raw_value,
+        }
+    }
+
+    // Scaled min/max, negative gain swaps the min and max
+    pub fn scale_min_max(&self, id: &str, raw_min_max: (f64, f64)) -> (f64, f64) {
+        let (a, b) = (self.scale(id, raw_min_max.0), self.scale(id, raw_min_max.1));
+        (a.min(b), a.max(b))
+    }
+
+    pub fn is_visible(&self, id: &str) -> bool {
+        self.channels.get(id).is_none_or(|channel| channel.visible)
+    }
+
+    // Label of the channel with the unit, e.g. "Speed [rpm]"
+    pub fn get_label(&self, id: &str) -> String {
+        match self.channels.get(id) {
+            Some(channel) if channel.unit.is_empty() => channel.name.clone(),
+            Some(channel) => format!("{} [{}]", channel.name, channel.unit),
+            None => id.to_string(),
+        }
+    }
+
+    pub fn get_channel(&self, id: &str) -> ChannelConfig {
+        self.channels
+            .get(id)
+            .cloned()
+            .unwrap_or_else(|| ChannelConfig::new(id))
+    }
+
+    fn get_channel_mut(&mut self, id: &str) -> &mut ChannelConfig {
+        self.channels
+            .entry(id.to_string())
+            .or_insert_with(|| ChannelConfig::new(id))
+    }
+
+    // Text displayed in the input of the channel table
+    pub fn get_input(&self, id: &str, field: ChannelField) -> String {
+        if let Some(input) = self.inputs.get(&(id.to_string(), field)) {
+            return input.clone();
+        }
+        let channel = self.get_channel(id);
+        match field {
+            ChannelField::Name => channel.name,
+            ChannelField::Unit => channel.unit,
+            ChannelField::Color => channel
+                .color
+                .map(|color| format!("#{}", hex::encode(color)))
+                .unwrap_or_default(),
+            ChannelField::LineWidth => channel.line_width.to_string(),
+            ChannelField::Gain => channel.gain.to_string(),
+            ChannelField::Offset => channel.offset.to_string(),
+        }
+    }
+
+    // Setting the field from the channel table, the value is applied when it can be parsed
+    pub fn set_field(&mut self, id: &str, field: ChannelField, value: String) {
+        let channel = self.get_channel_mut(id);
+        let parsed = match field {
+            ChannelField::Name => {
+                channel.name = value.clone();
+                true
+            }
+            ChannelField::Unit => {
+                channel.unit = value.clone();
+                true
+            }
+            ChannelField::Color => {
+                let color_hex = value.trim().trim_start_matches('#');
+                if color_hex.is_empty() {
+                    channel.color = None;
+                    true
+                } else {
+                    let mut color = [0u8; 3];
+                    match hex::decode_to_slice(color_hex, &mut color) {
+                        Ok(()) => {
+                            channel.color = Some(color);
+                            true
+                        }
+                        Err(_) => false,
+                    }
+                }
+            }
+            ChannelField::LineWidth => match value.parse::<u32>() {
+                Ok(width) if width > 0 => {
+                    channel.line_width = width;
+                    true
+                }
+                _ => false,
+            },
+            ChannelField::Gain => match value.parse::<f64>() {
+                Ok(gain) => {
+                    channel.gain = gain;
+                    true
+                }
+                Err(_) => false,
+            },
+            ChannelField::Offset => match value.parse::<f64>() {
+                Ok(offset) => {
+                    channel.offset = offset;
+                    true
+                }
+                Err(_) => false,
+            },
+        };
+
+        let key = (id.to_string(), field);
+        if parsed {
+            self.inputs.remove(&key);
+        } else {
+            println!("Error parsing the {:?} of the channel {}: {}", field, id, value);
+            self.inputs.insert(key, value);
+        }
+    }
+
+    // Setting the channel from the channel definition file, the display settings are kept
+    pub fn set_channel(&mut self, id: &str, name: &str, unit: &str, gain: f64) {
+        let channel = self.get_channel_mut(id);
+        channel.name = name.to_string();
+        channel.unit = unit.to_string();
+        channel.gain = gain;
+        channel.offset = 0.0;
+    }
+
+    // Adding the derived channels of the transforms to the received frame, so they are stored,
+    // displayed and exported as the received channels
+    pub fn apply_transforms(&self, frame: &mut Map<String, Value>) {
+        for transform in self.transforms.iter() {
+            transform.apply(frame, self);
+        }
+    }
+
+    // Adding the virtual channels evaluated from the expressions to the received frame, the
+    // expressions get the values scaled by the profile
+    pub fn apply_expressions(&mut self, frame: &mut Map<String, Value>) {
+        let channels = &self.channels;
+        let input = |frame: &Map<String, Value>, id: &str| -> f64 {
+            match frame.get(id).and_then(|value| value.as_f64()) {
+                Some(value) => channels.get(id).map_or(value, |channel| channel.scale(value)),
+                None => f64::NAN,
+            }
+        };
+        for virtual_channel in self.virtual_channels.iter_mut().flatten() {
+            virtual_channel.apply(frame, &input);
+        }
+    }
+
+    // Adding the filtered copies of the channels to the received frame, the raw channels stay in
+    // the frame, the filters get the values scaled by the profile
+    // missing inputs and non-finite outputs are added as null, so every frame has the same channels
+    pub fn apply_filters(&mut self, frame: &mut Map<String, Value>) {
+        for filter in self.filters.iter_mut() {
+            if filter.input.is_empty() || filter.output.is_empty() {
+                continue;
+            }
+            let scaled_value = match frame.get(&filter.input).and_then(|value| value.as_f64()) {
+                Some(value) => self
+                    .channels
+                    .get(&filter.input)
+                    .map_or(value, |channel| channel.scale(value)),
+                None => f64::NAN,
+            };
+            let output = serde_json::Number::from_f64(filter.process(scaled_value))
+                .map_or(Value::Null, Value::Number);
+            frame.insert(filter.output.clone(), output);
+        }
+    }
+
+    // Filters start again from the next received sample
+    pub fn reset_filters(&mut self) {
+        for filter in self.filters.iter_mut() {
+            filter.reset();
+        }
+    }
+
+    // Checking the alarm rules of the channels in the received frame, the values are scaled by
+    // the profile, events are returned only when the state of an alarm is changed
+    pub fn check_alarms(
+        &mut self,
+        frame: &Map<String, Value>,
+        sample: u128,
+        now: Instant,
+    ) -> Vec<AlarmEvent> {
+        let mut events = Vec::new();
+        for (index, rule) in self.alarms.iter_mut().enumerate() {
+            let Some(value) = frame.get(&rule.channel).and_then(|value| value.as_f64()) else {
+                continue;
+            };
+            let scaled_value = self
+                .channels
+                .get(&rule.channel)
+                .map_or(value, |channel| channel.scale(value));
+            if let Some(raised) = rule.check(scaled_value, now) {
+                events.push(rule.get_event(index, sample, raised, scaled_value));
+            }
+        }
+        events
+    }
+
+    // Checking the channels which were not received for the stale time of their rules
+    pub fn check_stale_alarms(&mut self, sample: u128, now: Instant) -> Vec<AlarmEvent> {
+        let mut events = Vec::new();
+        for (index, rule) in self.alarms.iter_mut().enumerate() {
+            if let Some(raised) = rule.check_stale(now) {
+                events.push(rule.get_event(index, sample, raised, f64::NAN));
+            }
+        }
+        events
+    }
+
+    // Alarms start again without the history, e.g. after the acquisition is restarted
+    pub fn reset_alarms(&mut self) {
+        for rule in self.alarms.iter_mut() {
+            rule.reset();
+        }
+    }
+
+    // Parsing all expressions again, which also resets the averages, derivatives and integrals
+    pub fn compile_expressions(&mut self) {
+        self.virtual_channels = self
+            .expressions
+            .iter()
+            .map(|expression| VirtualChannel::parse(expression))
+            .collect();
+    }
+
+    // The other virtual channels keep the state of their functions when an expression is added,
+    // removed or edited
+    pub fn add_expression(&mut self) {
+        self.expressions.push(String::new());
+        self.virtual_channels.push(VirtualChannel::parse(""));
+    }
+
+    pub fn remove_expression(&mut self, index: usize) {
+        if index < self.expressions.len() {
+            self.expressions.remove(index);
+            if index < self.virtual_channels.len() {
+                let _ = self.virtual_channels.remove(index);
+            }
+        }
+    }
+
+    // Only the edited expression is parsed again
+    pub fn set_expression(&mut self, index: usize, expression: String) {
+        if let Some(stored) = self.expressions.get_mut(index) {
+            let virtual_channel = VirtualChannel::parse(&expression);
+            *stored = expression;
+            match self.virtual_channels.get_mut(index) {
+                Some(compiled) => *compiled = virtual_channel,
+                None => self.compile_expressions(),
+            }
+        }
+    }
+
+    // Parse error of the expression displayed next to it, empty expressions have no error
+    pub fn get_expression_error(&self, index: usize) -> Option<&str> {
+        match self.virtual_channels.get(index) {
+            Some(Err(error)) if !self.expressions[index].trim().is_empty() => Some(error),
+            _ => None,
+        }
+    }
+
+    pub fn set_visible(&mut self, id: &str, visible: bool) {
+        self.get_channel_mut(id).visible = visible;
+    }
+
+    fn get_path(name: &str) -> PathBuf {
+        PathBuf::from(PROFILE_DIRECTORY).join(format!("{}.json", name))
+    }
+
+    // Names of the profiles saved in the profile directory
+    pub fn list_saved() -> Vec<String> {
+        let mut names: Vec<String> = match std::fs::read_dir(PROFILE_DIRECTORY) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().is_some_and(|extension| extension == "json"))
+                .filter_map(|path| path.file_stem().map(|stem| stem.to_string_lossy().to_string()))
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        names.sort();
+        names
+    }
+
+    pub fn load(name: &str) -> std::io::Result<Self> {
+        let file = std::fs::File::open(Self::get_path(name))?;
+        let mut profile: Self = serde_json::from_reader(file)?;
+        profile.name = name.to_string();
+        profile.compile_expressions();
+        Ok(profile)
+    }
+
+    pub fn save(&self) -> std::io::Result<()> {
+        std::fs::create_dir_all(PROFILE_DIRECTORY)?;
+        let file = std::fs::File::create(Self::get_path(&self.name))?;
+        serde_json::to_writer_pretty(file, self)?;
+        Ok(())
+    }
+}
@@ -28,6 +28,7 @@ SOFTWARE.
 use fixed::{traits::Fixed, types::extra::U15, FixedI32};
 use serde_json::{Map, Value};
 use core::panic;
+use std::collections::HashMap;
 use std::fmt;
 
 // Custom format for displaying ASCII or Hexadecimal representation
@@ -48,6 +49,9 @@ impl<'a> fmt::Display for Utf8Lossy<'a> {
     }
 }
 
+// Number of fractional bits of the fixed point representation in which the data are decoded
+// other Q formats of the channel definition file are rescaled by the device profile gain
+pub const FIXED_POINT_FRACTIONAL_BITS_Q_32_15: i32 = 15;
 // Scaling factor for using the fixed point representation
 const FIXED_POINT_SCALING_FACTOR_Q_32_15: f32 = 32768.0; // 2^15
 const FIXED_POINT_INTEGER_PART_MASK_Q_32_15: u32 = 0xFFFF_8000;
@@ -55,6 +59,9 @@ const FIXED_POINT_FRACTIONAL_PART_MASK_Q_32_15: u32 = 0x0000_7FFF;
 const VARIABLE_LENGTH: usize = 4;
 const NUMBER_OF_VARIABLES: usize = 8;
 const ENDING_PART_LENGTH_BYTES: usize = 2;
+// Key of the object with the received fixed point words in the frame, the variable IDs have
+// VARIABLE_LENGTH characters, so the key cannot collide with a variable
+pub const FRAME_WORDS_KEY: &str = "words";
 
 pub fn get_length_of_message_data(data: &Vec<u8>) -> u32 {
     let data_message_length: Vec<u8> = data[0..VARIABLE_LENGTH].iter().copied().rev().collect();
@@ -118,6 +125,7 @@ pub fn get_structured_message_data(data: &Vec<u8>) -> Value {
     let variable_length = 4;
     let mut variable_ids: Vec<String> = Vec::new();
     let mut variable_data: Vec<f32> = Vec::new();
+    let mut variable_words: Vec<u32> = Vec::new();
     let mut message_json: Value = Value::Object(Default::default());
     let message_data: Vec<u8> = get_message_inner_part(&data);
 
@@ -220,6 +228,8 @@ pub fn get_structured_message_data(data: &Vec<u8>) -> Value {
                 //            );
                 // variable_data.push(get_hex_from_chunk(&var_data));
                 variable_data.push(var_data_from_fixed);
+                // The word is kept as received, the f32 value does not hold all 32 bits
+                variable_words.push(received_hex_from_chunk);
             } else {
                 // Handle chunks with less than 8 elements (if needed)
                 //            println!("Chunk has less than 8 elements");
@@ -254,6 +264,15 @@ pub fn get_structured_message_data(data: &Vec<u8>) -> Value {
                 );
             }
 
+            // Received fixed point words of the variables under FRAME_WORDS_KEY, they are removed
+            // from the frame by take_frame_words before the frame is stored
+            let words_map: Map<String, Value> = variable_ids
+                .iter()
+                .zip(variable_words.iter())
+                .map(|(key, word)| (key.to_string(), Value::from(*word)))
+                .collect();
+            message_map.insert(FRAME_WORDS_KEY.to_string(), Value::Object(words_map));
+
             // Final serde_json object with key value pairs of
             // var_id:var_data
             // where var_id is String
@@ -270,6 +289,17 @@ pub fn get_structured_message_data(data: &Vec<u8>) -> Value {
     return message_json;
 }
 
+// Removing the received fixed point words from the frame, so only the var_id:var_data pairs stay
+pub fn take_frame_words(frame: &mut Map<String, Value>) -> HashMap<String, u32> {
+    match frame.remove(FRAME_WORDS_KEY) {
+        Some(Value::Object(words)) => words
+            .into_iter()
+            .filter_map(|(key, word)| Some((key, u32::try_from(word.as_u64()?).ok()?)))
+            .collect(),
+        _ => HashMap::new(),
+    }
+}
+
 pub fn get_hex_from_chunk(data: &[u8]) -> u32 {
     // Join bytes to one string and convert to hex in a string represebration
     let data_string: String = data.iter().map(|&b| format!("{:02X}", b)).collect();
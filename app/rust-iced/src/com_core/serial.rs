@@ -28,7 +28,6 @@ SOFTWARE.
 use std::fs::File;
 use std::io::Write;
 
-use plotters_iced::sample::lttb::LttbSource;
 use serde_json::Value;
 use serialport::SerialPort;
 use tokio::sync::broadcast;
@@ -82,6 +81,9 @@ pub async fn serial_port_task(
     ]);
 
     let mut message_hex_temp_window: Vec<u8> = Vec::new();
+    // Frames decoded from one read of the serial port buffer, they are sent to the frontend
+    // together as one JSON array, so the GUI is not updated for every single frame
+    let mut message_batch: Vec<Value> = Vec::new();
     // Waiting for the appropriate value to be received to start executing the serial port
     // receiving mechansm
     while rx.wait_for(|val| *val == true).await.is_ok() {
@@ -227,10 +229,7 @@ pub async fn serial_port_task(
                                 // test_file.write_all(text.as_ref()).unwrap();
                                 //println!("The serialized data: {:#?}", message_structured.to_string());
 
-                                tokio::time::sleep(tokio::time::Duration::from_nanos(1)).await;
-                                tx_data_bf_broadcast_channel
-                                    .send(message_structured.into())
-                                    .unwrap();
+                                message_batch.push(message_structured);
                             }
                         }
 
@@ -245,6 +244,14 @@ pub async fn serial_port_task(
                 }
             }
 
+            // Sending all frames decoded from the read buffer as one batch
+            if !message_batch.is_empty() {
+                tokio::time::sleep(tokio::time::Duration::from_nanos(1)).await;
+                tx_data_bf_broadcast_channel
+                    .send(Value::Array(std::mem::take(&mut message_batch)))
+                    .unwrap();
+            }
+
             //           println!("data_read = false");
             data_read = false;
             //is_start_byte = false;
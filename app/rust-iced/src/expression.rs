@@ -0,0 +1,480 @@
+/*
+
+MIT License
+
+Copyright (c) 2024 Petr Zakopal, Deparment of Electric Drives and Traction, CTU FEE
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+/*----------------------------------------------------------------------------*/
+
+use std::collections::VecDeque;
+
+use serde_json::{Map, Value};
+
+// Virtual channel defined by user as "name = expression", e.g. "p = @000 * @001"
+//
+// Expressions support numbers, references of the received channels @ID (the @ is part of the
+// variable ID, e.g. @000), references of any channel @{ID} (e.g. @{i_alpha}),
+// + - * / ^, parentheses and the functions:
+//   sqrt(x), abs(x), clamp(x, min, max), min(x, y), max(x, y)
+//   avg(x, n)      moving average of the last n samples
+//   ddt(x)         difference to the previous sample (d/dt(x) is accepted as well)
+//   integral(x)    running sum of the samples
+// Derivative and integral are per sample, multiply or divide by the sample rate for seconds.
+// The stateful functions keep their state between frames, so the channel is evaluated
+// incrementally, once per received frame.
+#[derive(Debug, Clone)]
+pub struct VirtualChannel {
+    pub id: String,     // Variable ID of the virtual channel used in the charts and exports
+    expression: Node,
+}
+
+// Node of the parsed expression, stateful functions keep their state in the node
+#[derive(Debug, Clone)]
+enum Node {
+    Number(f64),
+    Channel(String),
+    Negate(Box<Node>),
+    Binary(char, Box<Node>, Box<Node>),
+    Sqrt(Box<Node>),
+    Abs(Box<Node>),
+    Clamp(Box<Node>, Box<Node>, Box<Node>),
+    Min(Box<Node>, Box<Node>),
+    Max(Box<Node>, Box<Node>),
+    Average {
+        input: Box<Node>,
+        window: usize,
+        values: VecDeque<f64>,
+        sum: f64,
+    },
+    Derivative {
+        input: Box<Node>,
+        previous: Option<f64>,
+    },
+    Integral {
+        input: Box<Node>,
+        sum: f64,
+    },
+}
+
+impl Node {
+    // Value of the node for one frame, missing channels are NaN, NaN samples are skipped by the
+    // stateful functions, so one missing sample does not spoil the average or the integral
+    fn evaluate(&mut self, input: &dyn Fn(&str) -> f64) -> f64 {
+        match self {
+            Node::Number(value) => *value,
+            Node::Channel(id) => input(id),
+            Node::Negate(node) => -node.evaluate(input),
+            Node::Binary(operator, left, right) => {
+                let (left, right) = (left.evaluate(input), right.evaluate(input));
+                match operator {
+                    '+' => left + right,
+                    '-' => left - right,
+                    '*' => left * right,
+                    '/' => left / right,
+                    _ => left.powf(right),
+                }
+            }
+            Node::Sqrt(node) => node.evaluate(input).sqrt(),
+            Node::Abs(node) => node.evaluate(input).abs(),
+            Node::Clamp(node, min, max) => {
+                let (value, min, max) = (node.evaluate(input), min.evaluate(input), max.evaluate(input));
+                value.max(min).min(max)
+            }
+            Node::Min(left, right) => left.evaluate(input).min(right.evaluate(input)),
+            Node::Max(left, right) => left.evaluate(input).max(right.evaluate(input)),
+            Node::Average {
+                input: node,
+                window,
+                values,
+                sum,
+            } => {
+                let value = node.evaluate(input);
+                if !value.is_nan() {
+                    values.push_back(value);
+                    *sum += value;
+                    if values.len() > *window {
+                        *sum -= values.pop_front().unwrap_or(0.0);
+                    }
+                }
+                if values.is_empty() {
+                    f64::NAN
+                } else {
+                    *sum / values.len() as f64
+                }
+            }
+            Node::Derivative {
+                input: node,
+                previous,
+            } => {
+                let value = node.evaluate(input);
+                if value.is_nan() {
+                    return f64::NAN;
+                }
+                let derivative = previous.map_or(0.0, |previous| value - previous);
+                *previous = Some(value);
+                derivative
+            }
+            Node::Integral { input: node, sum } => {
+                let value = node.evaluate(input);
+                if !value.is_nan() {
+                    *sum += value;
+                }
+                *sum
+            }
+        }
+    }
+}
+
+impl VirtualChannel {
+    // Parsing the definition "name = expression", returns the error message for the user
+    pub fn parse(definition: &str) -> Result<Self, String> {
+        let (id, expression) = definition
+            .split_once('=')
+            .ok_or_else(|| "missing '=' between the name and the expression".to_string())?;
+        let id = id.trim();
+        if id.is_empty() {
+            return Err("missing name of the virtual channel".to_string());
+        }
+        let mut parser = Parser {
+            characters: expression.chars().collect(),
+            position: 0,
+        };
+        let expression = parser.parse_sum()?;
+        parser.skip_whitespace();
+        if parser.position < parser.characters.len() {
+            return Err(format!(
+                "unexpected '{}' at position {}",
+                parser.characters[parser.position],
+                parser.position + 1
+            ));
+        }
+        Ok(Self {
+            id: id.to_string(),
+            expression,
+        })
+    }
+
+    // Evaluating the channel for one frame and adding the value to the frame, input returns the
+    // scaled value of the channel, NaN when missing
+    // non-finite values (e.g. sqrt of a negative value) are added as null, so every frame has the
+    // same channels and the columns of the output file stay aligned
+    pub fn apply(&mut self, frame: &mut Map<String, Value>, input: &dyn Fn(&Map<String, Value>, &str) -> f64) {
+        let value = self.expression.evaluate(&|id| input(frame, id));
+        let value = serde_json::Number::from_f64(value).map_or(Value::Null, Value::Number);
+        frame.insert(self.id.clone(), value);
+    }
+}
+
+// Recursive descent parser of the expressions
+struct Parser {
+    characters: Vec<char>,
+    position: usize,
+}
+
+impl Parser {
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|character| character.is_whitespace()) {
+            self.position += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.characters.get(self.position).copied()
+    }
+
+    // Next character after the whitespace is consumed when it is the expected one
+    fn consume(&mut self, expected: char) -> bool {
+        self.skip_whitespace();
+        if self.peek() == Some(expected) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        if self.consume(expected) {
+            Ok(())
+        } else {
+            Err(format!("expected '{}' at position {}", expected, self.position + 1))
+        }
+    }
+
+    fn take_while(&mut self, predicate: impl Fn(char) -> bool) -> String {
+        let start = self.position;
+        while self.peek().is_some_and(&predicate) {
+            self.position += 1;
+        }
+        self.characters[start..self.position].iter().collect()
+    }
+
+    // sum := product (('+' | '-') product)*
+    fn parse_sum(&mut self) -> Result<Node, String> {
+        let mut node = self.parse_product()?;
+        loop {
+            let operator = if self.consume('+') {
+                '+'
+            } else if self.consume('-') {
+                '-'
+            } else {
+                return Ok(node);
+            };
+            node = Node::Binary(operator, Box::new(node), Box::new(self.parse_product()?));
+        }
+    }
+
+    // product := unary (('*' | '/') unary)*
+    fn parse_product(&mut self) -> Result<Node, String> {
+        let mut node = self.parse_unary()?;
+        loop {
+            let operator = if self.consume('*') {
+                '*'
+            } else if self.consume('/') {
+                '/'
+            } else {
+                return Ok(node);
+            };
+            node = Node::Binary(operator, Box::new(node), Box::new(self.parse_unary()?));
+        }
+    }
+
+    // unary := '-' unary | power
+    fn parse_unary(&mut self) -> Result<Node, String> {
+        if self.consume('-') {
+            return Ok(Node::Negate(Box::new(self.parse_unary()?)));
+        }
+        self.parse_power()
+    }
+
+    // power := primary ('^' unary)?, right associative
+    fn parse_power(&mut self) -> Result<Node, String> {
+        let base = self.parse_primary()?;
+        if self.consume('^') {
+            return Ok(Node::Binary('^', Box::new(base), Box::new(self.parse_unary()?)));
+        }
+        Ok(base)
+    }
+
+    // primary := number | '@' ID | function '(' arguments ')' | '(' sum ')'
+    fn parse_primary(&mut self) -> Result<Node, String> {
+        self.skip_whitespace();
+        let start = self.position;
+        match self.peek() {
+            Some('(') => {
+                self.position += 1;
+                let node = self.parse_sum()?;
+                self.expect(')')?;
+                Ok(node)
+            }
+            Some('@') => {
+                self.position += 1;
+                let id = if self.consume('{') {
+                    let id = self.take_while(|character| character != '}');
+                    self.expect('}')?;
+                    id
+                } else {
+                    let id = self.take_while(|character| character.is_alphanumeric() || character == '_');
+                    if id.is_empty() {
+                        id
+                    } else {
+                        format!("@{}", id)
+                    }
+                };
+                if id.is_empty() {
+                    return Err(format!("missing channel ID at position {}", start + 1));
+                }
+                Ok(Node::Channel(id))
+            }
+            Some(character) if character.is_ascii_digit() || character == '.' => {
+                let mut number =
+                    self.take_while(|character| character.is_ascii_digit() || character == '.');
+                // Exponent with an optional sign, e.g. 1e-3 or 2E+5
+                if let Some(exponent @ ('e' | 'E')) = self.peek() {
+                    self.position += 1;
+                    number.push(exponent);
+                    if let Some(sign @ ('+' | '-')) = self.peek() {
+                        self.position += 1;
+                        number.push(sign);
+                    }
+                    number += &self.take_while(|character| character.is_ascii_digit());
+                }
+                number
+                    .parse::<f64>()
+                    .map(Node::Number)
+                    .map_err(|_| format!("invalid number '{}' at position {}", number, start + 1))
+            }
+            Some(character) if character.is_alphabetic() => {
+                let mut name = self.take_while(|character| character.is_alphanumeric() || character == '_');
+                // d/dt(x) is the same as ddt(x)
+                if name == "d" && self.characters[self.position..].starts_with(&['/', 'd', 't']) {
+                    self.position += 3;
+                    name = "ddt".to_string();
+                }
+                self.parse_function(&name, start)
+            }
+            Some(character) => Err(format!("unexpected '{}' at position {}", character, start + 1)),
+            None => Err("unexpected end of the expression".to_string()),
+        }
+    }
+
+    fn parse_function(&mut self, name: &str, start: usize) -> Result<Node, String> {
+        self.expect('(')?;
+        let mut arguments = vec![self.parse_sum()?];
+        while self.consume(',') {
+            arguments.push(self.parse_sum()?);
+        }
+        self.expect(')')?;
+
+        let expected_arguments = match name {
+            "sqrt" | "abs" | "ddt" | "integral" => 1,
+            "min" | "max" | "avg" => 2,
+            "clamp" => 3,
+            _ => return Err(format!("unknown function '{}' at position {}", name, start + 1)),
+        };
+        if arguments.len() != expected_arguments {
+            return Err(format!(
+                "function '{}' takes {} arguments, {} given",
+                name,
+                expected_arguments,
+                arguments.len()
+            ));
+        }
+
+        let mut arguments = arguments.into_iter().map(Box::new);
+        let mut argument = || arguments.next().expect("number of arguments is checked");
+        Ok(match name {
+            "sqrt" => Node::Sqrt(argument()),
+            "abs" => Node::Abs(argument()),
+            "ddt" => Node::Derivative {
+                input: argument(),
+                previous: None,
+            },
+            "integral" => Node::Integral {
+                input: argument(),
+                sum: 0.0,
+            },
+            "min" => Node::Min(argument(), argument()),
+            "max" => Node::Max(argument(), argument()),
+            "avg" => {
+                let input = argument();
+                let window = match *argument() {
+                    Node::Number(window) if window >= 1.0 => window as usize,
+                    _ => return Err("window of 'avg' must be a number of samples".to_string()),
+                };
+                Node::Average {
+                    input,
+                    window,
+                    values: VecDeque::with_capacity(window),
+                    sum: 0.0,
+                }
+            }
+            _ => Node::Clamp(argument(), argument(), argument()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Value of the virtual channel for one frame with the channel values
+    fn evaluate(virtual_channel: &mut VirtualChannel, values: &[(&str, f64)]) -> f64 {
+        let mut frame = Map::new();
+        for (id, value) in values {
+            frame.insert(id.to_string(), Value::from(*value));
+        }
+        let input = |frame: &Map<String, Value>, id: &str| {
+            frame.get(id).and_then(|value| value.as_f64()).unwrap_or(f64::NAN)
+        };
+        virtual_channel.apply(&mut frame, &input);
+        frame[&virtual_channel.id].as_f64().unwrap_or(f64::NAN)
+    }
+
+    fn evaluate_constant(definition: &str) -> f64 {
+        let mut virtual_channel = VirtualChannel::parse(definition).unwrap();
+        evaluate(&mut virtual_channel, &[])
+    }
+
+    #[test]
+    fn operator_precedence() {
+        assert_eq!(evaluate_constant("y = 1 + 2 * 3 ^ 2"), 19.0);
+        assert_eq!(evaluate_constant("y = (1 + 2) * 3"), 9.0);
+        assert_eq!(evaluate_constant("y = 10 - 4 - 3"), 3.0);
+        assert_eq!(evaluate_constant("y = 8 / 4 / 2"), 1.0);
+        assert_eq!(evaluate_constant("y = -(2 + 3) * 2"), -10.0);
+    }
+
+    #[test]
+    fn numbers_with_exponent() {
+        assert_eq!(evaluate_constant("y = 1e-3 * 2E+5"), 200.0);
+        assert_eq!(evaluate_constant("y = 1.5e2"), 150.0);
+        assert_eq!(evaluate_constant("y = .5 + 1e3"), 1000.5);
+    }
+
+    #[test]
+    fn channel_references() {
+        let mut power = VirtualChannel::parse("p = @000 * @{A-1}").unwrap();
+        assert_eq!(evaluate(&mut power, &[("@000", 3.0), ("A-1", 4.0)]), 12.0);
+        // Missing channel gives a missing value
+        assert!(evaluate(&mut power, &[("@000", 3.0)]).is_nan());
+    }
+
+    #[test]
+    fn functions() {
+        assert_eq!(evaluate_constant("y = sqrt(16) + abs(-2)"), 6.0);
+        assert_eq!(evaluate_constant("y = clamp(5, 0, 3) + min(1, 2) + max(1, 2)"), 6.0);
+
+        let mut average = VirtualChannel::parse("y = avg(@{x}, 2)").unwrap();
+        let averages: Vec<f64> = [1.0, 3.0, 5.0]
+            .iter()
+            .map(|x| evaluate(&mut average, &[("x", *x)]))
+            .collect();
+        assert_eq!(averages, vec![1.0, 2.0, 4.0]);
+
+        let mut derivative = VirtualChannel::parse("y = d/dt(@{x})").unwrap();
+        let derivatives: Vec<f64> = [1.0, 3.0, 6.0]
+            .iter()
+            .map(|x| evaluate(&mut derivative, &[("x", *x)]))
+            .collect();
+        assert_eq!(derivatives, vec![0.0, 2.0, 3.0]);
+
+        // Missing sample is skipped by the integral
+        let mut integral = VirtualChannel::parse("y = integral(@{x})").unwrap();
+        assert_eq!(evaluate(&mut integral, &[("x", 1.0)]), 1.0);
+        assert_eq!(evaluate(&mut integral, &[]), 1.0);
+        assert_eq!(evaluate(&mut integral, &[("x", 2.5)]), 3.5);
+    }
+
+    #[test]
+    fn parse_errors() {
+        let error = |definition: &str| VirtualChannel::parse(definition).unwrap_err();
+        assert!(error("y 1").contains("missing '='"));
+        assert!(error(" = 1").contains("missing name"));
+        assert!(error("y = foo(1)").contains("unknown function 'foo'"));
+        assert!(error("y = min(1)").contains("takes 2 arguments"));
+        assert!(error("y = (1 + 2").contains("expected ')'"));
+        assert!(error("y = 1 +").contains("unexpected end"));
+        assert!(error("y = 1.2.3").contains("invalid number"));
+        assert!(error("y = 1 2").contains("position"));
+    }
+}
@@ -0,0 +1,413 @@
+/*
+
+MIT License
+
+Copyright (c) 2024 Petr Zakopal, Deparment of Electric Drives and Traction, CTU FEE
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+/*----------------------------------------------------------------------------*/
+
+use std::collections::{HashMap, VecDeque};
+use std::f64::consts::PI;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+// Longest window of the moving average and median, the median sorts the whole window for every
+// sample
+const MAX_FILTER_LENGTH: usize = 1000;
+
+// Real-time filter applied to the samples of one channel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum FilterKind {
+    #[default]
+    MovingAverage,  // Average of the last length samples
+    IirLowPass,     // Single-pole low-pass with the cutoff frequency
+    BiquadLowPass,  // Second order sections designed with the cutoff and quality
+    BiquadHighPass,
+    BiquadBandPass,
+    Median,         // Median of the last length samples, removes spikes
+}
+
+impl FilterKind {
+    pub const ALL: [FilterKind; 6] = [
+        FilterKind::MovingAverage,
+        FilterKind::IirLowPass,
+        FilterKind::BiquadLowPass,
+        FilterKind::BiquadHighPass,
+        FilterKind::BiquadBandPass,
+        FilterKind::Median,
+    ];
+
+    // Parameters of the filter kind displayed in the filter table
+    pub fn get_parameters(&self) -> &'static [FilterField] {
+        match self {
+            FilterKind::MovingAverage | FilterKind::Median => &[FilterField::Length],
+            FilterKind::IirLowPass => &[FilterField::Cutoff, FilterField::SampleRate],
+            FilterKind::BiquadLowPass | FilterKind::BiquadHighPass | FilterKind::BiquadBandPass => {
+                &[FilterField::Cutoff, FilterField::SampleRate, FilterField::Quality]
+            }
+        }
+    }
+}
+
+// Displayed in the PickList
+impl fmt::Display for FilterKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FilterKind::MovingAverage => write!(f, "Moving average"),
+            FilterKind::IirLowPass => write!(f, "IIR low-pass"),
+            FilterKind::BiquadLowPass => write!(f, "Biquad low-pass"),
+            FilterKind::BiquadHighPass => write!(f, "Biquad high-pass"),
+            FilterKind::BiquadBandPass => write!(f, "Biquad band-pass"),
+            FilterKind::Median => write!(f, "Median"),
+        }
+    }
+}
+
+// Editable fields of the filter table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FilterField {
+    Input,
+    Output,
+    Length,
+    Cutoff,
+    SampleRate,
+    Quality,
+}
+
+// Displayed as the placeholder of the input
+impl fmt::Display for FilterField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FilterField::Input => write!(f, "Input ID"),
+            FilterField::Output => write!(f, "Output ID"),
+            FilterField::Length => write!(f, "Length [samples]"),
+            FilterField::Cutoff => write!(f, "Cutoff [Hz]"),
+            FilterField::SampleRate => write!(f, "Sample rate [Hz]"),
+            FilterField::Quality => write!(f, "Q"),
+        }
+    }
+}
+
+// State of the running filter, not saved with the profile
+#[derive(Debug, Clone, Default)]
+enum FilterState {
+    #[default]
+    Empty,
+    Window(VecDeque<f64>, f64),         // Last samples and their sum
+    Iir(f64),                           // Last output
+    Biquad([f64; 5], [f64; 4]),         // Coefficients b0, b1, b2, a1, a2 and x1, x2, y1, y2
+}
+
+// Filter of one channel, the filtered samples are stored as a separate channel, so the raw
+// channel stays available
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelFilter {
+    pub kind: FilterKind,
+    pub input: String,              // Variable ID of the filtered channel
+    pub output: String,             // Variable ID of the filtered samples
+    pub length: usize,              // Window of the moving average and median in samples
+    pub cutoff: f64,                // Cutoff or centre frequency in Hz
+    pub sample_rate: f64,           // Sample rate of the input channel in Hz
+    pub quality: f64,               // Quality factor of the biquads
+    #[serde(skip)]
+    state: FilterState,
+    #[serde(skip)]
+    inputs: HashMap<FilterField, String>, // Strings inserted by user which are not parsed yet
+}
+
+impl ChannelFilter {
+    pub fn new(kind: FilterKind) -> Self {
+        Self {
+            kind,
+            input: String::new(),
+            output: String::new(),
+            length: 10,
+            cutoff: 100.0,
+            sample_rate: 1000.0,
+            quality: std::f64::consts::FRAC_1_SQRT_2,
+            state: FilterState::Empty,
+            inputs: HashMap::new(),
+        }
+    }
+
+    // Filtered value of the next sample, NaN samples are passed through without changing the state
+    pub fn process(&mut self, sample: f64) -> f64 {
+        if sample.is_nan() {
+            return f64::NAN;
+        }
+        if matches!(self.state, FilterState::Empty) {
+            self.state = self.get_initial_state(sample);
+        }
+
+        match &mut self.state {
+            FilterState::Window(values, sum) => {
+                values.push_back(sample);
+                *sum += sample;
+                if values.len() > self.length {
+                    *sum -= values.pop_front().unwrap_or(0.0);
+                }
+                if self.kind == FilterKind::Median {
+                    let mut sorted: Vec<f64> = values.iter().copied().collect();
+                    sorted.sort_by(f64::total_cmp);
+                    let middle = sorted.len() / 2;
+                    if sorted.len().is_multiple_of(2) {
+                        (sorted[middle - 1] + sorted[middle]) / 2.0
+                    } else {
+                        sorted[middle]
+                    }
+                } else {
+                    *sum / values.len() as f64
+                }
+            }
+            FilterState::Iir(output) => {
+                let alpha = 1.0 - (-2.0 * PI * self.cutoff / self.sample_rate).exp();
+                *output += alpha * (sample - *output);
+                *output
+            }
+            FilterState::Biquad([b0, b1, b2, a1, a2], [x1, x2, y1, y2]) => {
+                let output = *b0 * sample + *b1 * *x1 + *b2 * *x2 - *a1 * *y1 - *a2 * *y2;
+                (*x2, *x1, *y2, *y1) = (*x1, sample, *y1, output);
+                output
+            }
+            FilterState::Empty => sample,
+        }
+    }
+
+    // State starting from the first sample, so the low-pass filters do not start from zero
+    fn get_initial_state(&self, sample: f64) -> FilterState {
+        match self.kind {
+            FilterKind::MovingAverage | FilterKind::Median => {
+                FilterState::Window(VecDeque::with_capacity(self.length + 1), 0.0)
+            }
+            FilterKind::IirLowPass => FilterState::Iir(sample),
+            FilterKind::BiquadLowPass | FilterKind::BiquadHighPass | FilterKind::BiquadBandPass => {
+                let coefficients = self.get_biquad_coefficients();
+                // Steady state of the filter for the constant input
+                let gain = (coefficients[0] + coefficients[1] + coefficients[2])
+                    / (1.0 + coefficients[3] + coefficients[4]);
+                let output = if gain.is_finite() { gain * sample } else { 0.0 };
+                FilterState::Biquad(coefficients, [sample, sample, output, output])
+            }
+        }
+    }
+
+    // Normalised coefficients b0, b1, b2, a1, a2 of the biquad from the Audio EQ Cookbook
+    fn get_biquad_coefficients(&self) -> [f64; 5] {
+        let omega = 2.0 * PI * self.cutoff / self.sample_rate;
+        let (sin, cos) = omega.sin_cos();
+        let alpha = sin / (2.0 * self.quality);
+        let a0 = 1.0 + alpha;
+        let (b0, b1, b2) = match self.kind {
+            FilterKind::BiquadHighPass => ((1.0 + cos) / 2.0, -(1.0 + cos), (1.0 + cos) / 2.0),
+            FilterKind::BiquadBandPass => (alpha, 0.0, -alpha),
+            _ => ((1.0 - cos) / 2.0, 1.0 - cos, (1.0 - cos) / 2.0),
+        };
+        [b0 / a0, b1 / a0, b2 / a0, -2.0 * cos / a0, (1.0 - alpha) / a0]
+    }
+
+    // Filter starts again from the next sample, e.g. after the parameters are changed
+    pub fn reset(&mut self) {
+        self.state = FilterState::Empty;
+    }
+
+    pub fn set_kind(&mut self, kind: FilterKind) {
+        self.kind = kind;
+        self.reset();
+    }
+
+    // Text displayed in the input of the filter table
+    pub fn get_input(&self, field: FilterField) -> String {
+        if let Some(input) = self.inputs.get(&field) {
+            return input.clone();
+        }
+        match field {
+            FilterField::Input => self.input.clone(),
+            FilterField::Output => self.output.clone(),
+            FilterField::Length => self.length.to_string(),
+            FilterField::Cutoff => self.cutoff.to_string(),
+            FilterField::SampleRate => self.sample_rate.to_string(),
+            FilterField::Quality => self.quality.to_string(),
+        }
+    }
+
+    // Cutoff must be positive and below the Nyquist frequency of the sample rate
+    fn is_cutoff_valid(cutoff: f64, sample_rate: f64) -> bool {
+        cutoff > 0.0 && cutoff < sample_rate / 2.0
+    }
+
+    // Setting the field from the filter table, the value is applied when it can be parsed, the
+    // cutoff must be below the Nyquist frequency and the length at most MAX_FILTER_LENGTH
+    pub fn set_field(&mut self, field: FilterField, value: String) {
+        let parsed = match field {
+            FilterField::Input => {
+                // Output follows the input until it is changed by user
+                if self.output.is_empty() || self.output == format!("{}_f", self.input) {
+                    self.output = format!("{}_f", value.trim());
+                }
+                self.input = value.trim().to_string();
+                true
+            }
+            FilterField::Output => {
+                self.output = value.trim().to_string();
+                true
+            }
+            FilterField::Length => match value.parse::<usize>() {
+                Ok(length) if length > 0 && length <= MAX_FILTER_LENGTH => {
+                    self.length = length;
+                    true
+                }
+                _ => false,
+            },
+            FilterField::Cutoff => match value.parse::<f64>() {
+                Ok(cutoff) if Self::is_cutoff_valid(cutoff, self.sample_rate) => {
+                    self.cutoff = cutoff;
+                    true
+                }
+                _ => false,
+            },
+            FilterField::SampleRate => match value.parse::<f64>() {
+                Ok(sample_rate) if Self::is_cutoff_valid(self.cutoff, sample_rate) => {
+                    self.sample_rate = sample_rate;
+                    true
+                }
+                _ => false,
+            },
+            FilterField::Quality => match value.parse::<f64>() {
+                Ok(quality) if quality > 0.0 => {
+                    self.quality = quality;
+                    true
+                }
+                _ => false,
+            },
+        };
+
+        if parsed {
+            self.inputs.remove(&field);
+            self.reset();
+        } else {
+            println!("Error parsing the {:?} of the filter: {}", field, value);
+            self.inputs.insert(field, value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_filter(kind: FilterKind, cutoff: f64, sample_rate: f64) -> ChannelFilter {
+        let mut filter = ChannelFilter::new(kind);
+        filter.sample_rate = sample_rate;
+        filter.cutoff = cutoff;
+        filter
+    }
+
+    // Amplitude of the filtered sine from the RMS of whole periods after the transient
+    fn get_amplitude(filter: &mut ChannelFilter, frequency: f64) -> f64 {
+        let sample_rate = filter.sample_rate;
+        let samples = (0..4000)
+            .map(|n| (2.0 * PI * frequency * n as f64 / sample_rate).sin())
+            .map(|sample| filter.process(sample))
+            .collect::<Vec<f64>>();
+        let rms = samples[2000..].iter().map(|sample| sample * sample).sum::<f64>() / 2000.0;
+        (2.0 * rms).sqrt()
+    }
+
+    #[test]
+    fn iir_low_pass_step() {
+        let mut filter = get_filter(FilterKind::IirLowPass, 10.0, 1000.0);
+        assert_eq!(filter.process(0.0), 0.0);
+        let alpha = 1.0 - (-2.0 * PI * 10.0 / 1000.0).exp();
+        assert!((filter.process(1.0) - alpha).abs() < 1e-12);
+        // Output after one time constant 1 / (2 pi fc) is 1 - 1/e
+        let time_constant = (1000.0 / (2.0 * PI * 10.0)).round() as usize;
+        let output = (1..time_constant).map(|_| filter.process(1.0)).last().unwrap();
+        assert!((output - (1.0 - (-1.0f64).exp())).abs() < 0.02);
+    }
+
+    #[test]
+    fn biquad_gain() {
+        let mut low_pass = get_filter(FilterKind::BiquadLowPass, 50.0, 1000.0);
+        assert!((get_amplitude(&mut low_pass, 5.0) - 1.0).abs() < 0.01);
+        // -3 dB at the cutoff with Q = 1/sqrt(2)
+        low_pass.reset();
+        assert!((get_amplitude(&mut low_pass, 50.0) - std::f64::consts::FRAC_1_SQRT_2).abs() < 0.01);
+        low_pass.reset();
+        assert!(get_amplitude(&mut low_pass, 400.0) < 0.02);
+
+        let mut high_pass = get_filter(FilterKind::BiquadHighPass, 50.0, 1000.0);
+        assert!(get_amplitude(&mut high_pass, 5.0) < 0.02);
+        high_pass.reset();
+        assert!((get_amplitude(&mut high_pass, 400.0) - 1.0).abs() < 0.02);
+
+        let mut band_pass = get_filter(FilterKind::BiquadBandPass, 50.0, 1000.0);
+        assert!((get_amplitude(&mut band_pass, 50.0) - 1.0).abs() < 0.01);
+        band_pass.reset();
+        assert!(get_amplitude(&mut band_pass, 400.0) < 0.1);
+    }
+
+    #[test]
+    fn biquad_starts_in_steady_state() {
+        let mut filter = get_filter(FilterKind::BiquadLowPass, 50.0, 1000.0);
+        for _ in 0..10 {
+            assert!((filter.process(3.0) - 3.0).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn median_removes_spikes() {
+        let mut filter = ChannelFilter::new(FilterKind::Median);
+        filter.length = 3;
+        let output: Vec<f64> = [1.0, 100.0, 2.0, 3.0, f64::NAN, -50.0, 4.0]
+            .into_iter()
+            .map(|sample| filter.process(sample))
+            .collect();
+        assert_eq!(output[..4], [1.0, 50.5, 2.0, 3.0]);
+        assert!(output[4].is_nan());
+        assert_eq!(output[5..], [2.0, 3.0]);
+    }
+
+    #[test]
+    fn moving_average() {
+        let mut filter = ChannelFilter::new(FilterKind::MovingAverage);
+        filter.length = 2;
+        let output: Vec<f64> = [2.0, 4.0, 8.0].into_iter().map(|sample| filter.process(sample)).collect();
+        assert_eq!(output, [2.0, 3.0, 6.0]);
+    }
+
+    #[test]
+    fn invalid_fields_are_rejected() {
+        let mut filter = get_filter(FilterKind::BiquadLowPass, 100.0, 1000.0);
+        filter.set_field(FilterField::Cutoff, "500".to_string());
+        assert_eq!(filter.cutoff, 100.0);
+        filter.set_field(FilterField::SampleRate, "200".to_string());
+        assert_eq!(filter.sample_rate, 1000.0);
+        filter.set_field(FilterField::SampleRate, "250".to_string());
+        assert_eq!(filter.sample_rate, 250.0);
+        filter.set_field(FilterField::Length, (MAX_FILTER_LENGTH + 1).to_string());
+        assert_eq!(filter.length, 10);
+        assert_eq!(filter.get_input(FilterField::Length), (MAX_FILTER_LENGTH + 1).to_string());
+        filter.set_field(FilterField::Length, MAX_FILTER_LENGTH.to_string());
+        assert_eq!(filter.length, MAX_FILTER_LENGTH);
+    }
+}
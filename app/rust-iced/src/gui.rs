@@ -25,13 +25,23 @@ SOFTWARE.
 
 /*----------------------------------------------------------------------------*/
 
-use std::{collections::{HashMap, VecDeque}, fs::File};
+use std::{collections::{HashMap, VecDeque}, fs::File, time::{Duration, Instant}};
 
-use iced::{theme, Element, Theme};
-use plotters::{chart::SeriesLabelPosition, element::Rectangle, series::LineSeries, style::{Color, FontTransform, IntoFont, Palette, Palette99, RGBAColor, ShapeStyle, BLACK, WHITE}};
-use plotters_iced::{Chart, ChartBuilder, ChartWidget, DrawingBackend};
+use iced::{event, keyboard, mouse, theme, widget::canvas::{Cache, Event, Frame, Geometry}, Element, Size, Theme};
+use plotters::{chart::SeriesLabelPosition, element::{PathElement, Rectangle, Text}, series::LineSeries, style::{Color, FontTransform, IntoFont, Palette, Palette99, RGBAColor, RGBColor, ShapeStyle, BLACK, WHITE}};
+use plotters_iced::{Chart, ChartBuilder, ChartWidget, DrawingBackend, Renderer};
+use serde::{Deserialize, Serialize};
 
-use crate::{com_core::SerialPortConnectInfo, Message};
+use crate::{
+    alarms::AlarmSeverity, channels::DeviceProfile, com_core::SerialPortConnectInfo, signal_store::SignalStore, Message,
+};
+
+pub mod decimation;
+use decimation::{decimate, DecimationMode, TracePoint};
+pub mod spectrum_chart;
+pub mod xy_chart;
+pub mod logic_chart;
+pub mod histogram_chart;
 
 // Gives back result of ShapeStyle which is then used in a LineSeries chart
 // The color is automatically generated from a Palette based on a chart index
@@ -46,6 +56,40 @@ pub fn get_line_series_style(index: u32) -> ShapeStyle {
     new_style
 }
 
+// Style of the plotted line of a channel from the device profile, channels without a color keep
+// the color from the Palette based on a chart index
+pub fn get_channel_line_style(device_profile: &DeviceProfile, id: &str, index: u32) -> ShapeStyle {
+    let channel = device_profile.get_channel(id);
+    ShapeStyle {
+        color: match channel.color {
+            Some([r, g, b]) => RGBColor(r, g, b).to_rgba(),
+            None => Palette99::pick(index as usize).to_rgba(),
+        },
+        filled: false,
+        stroke_width: channel.line_width,
+    }
+}
+
+// Colors of the mesh, axes and labels of a chart based on the theme
+// (bold lines, light lines, axis, labels)
+pub fn get_chart_style_colors(theme: &Theme) -> (RGBAColor, RGBAColor, RGBAColor, RGBAColor) {
+    if *theme == theme::Theme::Light {
+        (
+            plotters::style::colors::BLACK.mix(0.8),
+            plotters::style::colors::BLACK.mix(0.4),
+            plotters::style::colors::BLACK.mix(0.8),
+            plotters::style::colors::BLACK.mix(1.0),
+        )
+    } else {
+        (
+            plotters::style::colors::WHITE.mix(0.1),
+            plotters::style::colors::WHITE.mix(0.05),
+            plotters::style::colors::WHITE.mix(0.45),
+            plotters::style::colors::WHITE.mix(1.0),
+        )
+    }
+}
+
 // Running buffer struct for iced widget input
 #[derive(Default, Clone)]
 pub struct RunningBuffer {
@@ -58,6 +102,76 @@ pub struct RunningBuffer {
 pub struct NavigationLayout {
     pub available_desktops: Vec<String>,    // Vector of strings of available desktops
     pub selected_desktop: Option<String>,   // Currently selected desktop from the PickList
+    pub pane_layouts: HashMap<String, PaneLayout>, // Chart panes of every desktop
+}
+
+impl NavigationLayout {
+    // Chart panes of the selected desktop, desktop without a definition has one pane
+    pub fn get_pane_layout(&self) -> PaneLayout {
+        self.selected_desktop
+            .as_ref()
+            .and_then(|desktop| self.pane_layouts.get(desktop))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub fn get_pane_layout_mut(&mut self) -> &mut PaneLayout {
+        let desktop = self.selected_desktop.clone().unwrap_or_default();
+        self.pane_layouts.entry(desktop).or_default()
+    }
+}
+
+// Stacked chart panes of one desktop and the assignment of the channels to them, saved with the
+// device profile
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaneLayout {
+    pub pane_count: usize,              // Parsed number of the chart panes
+    pub pane_count_string: String,      // String format of the number of panes inserted by user
+    pub channel_panes: HashMap<String, usize>, // Variable ID to the index of the pane, channels
+                                               // without assignment are in the first pane
+    #[serde(default)]
+    pub panes: Vec<PaneSettings>,       // Y axis and decimation of every pane, panes without
+                                        // settings use the defaults
+}
+
+impl Default for PaneLayout {
+    fn default() -> Self {
+        Self {
+            pane_count: 1,
+            pane_count_string: "1".to_string(),
+            channel_panes: HashMap::new(),
+            panes: Vec::new(),
+        }
+    }
+}
+
+impl PaneLayout {
+    // Index of the pane in which the channel is plotted
+    pub fn get_pane(&self, channel_id: &str) -> usize {
+        self.channel_panes
+            .get(channel_id)
+            .copied()
+            .unwrap_or(0)
+            .min(self.pane_count.max(1) - 1)
+    }
+
+    pub fn get_pane_settings(&self, pane: usize) -> PaneSettings {
+        self.panes.get(pane).cloned().unwrap_or_default()
+    }
+
+    pub fn get_pane_settings_mut(&mut self, pane: usize) -> &mut PaneSettings {
+        if self.panes.len() <= pane {
+            self.panes.resize_with(pane + 1, Default::default);
+        }
+        &mut self.panes[pane]
+    }
+
+    // Names of the panes for the PickList
+    pub fn get_pane_names(&self) -> Vec<String> {
+        (1..=self.pane_count.max(1))
+            .map(|pane| format!("Pane {}", pane))
+            .collect()
+    }
 }
 
 // Struct for handling writing data to file output
@@ -66,6 +180,9 @@ pub struct OutputFile {
     pub handler: Option<File>,  // For opened file hadler to be able to access write operation
                                 // between cycles of data process
     pub enable_output: bool,    // For enabling and disabling the output to file
+    pub columns: Option<Vec<String>>, // Variable IDs of the header written before the first sample,
+                                      // every row is written in this order
+    pub path: Option<String>,   // Path of the created file, the markers are saved next to it
 }
 
 // Struct for Serial Port settings
@@ -84,98 +201,684 @@ pub struct SerialPortData {
 
 
 
+// Struct for redrawing the chart at a fixed FPS independent of the acquisition rate
+pub struct RenderSettings {
+    pub fps: u32,               // Parsed FPS at which the chart is redrawn
+    pub fps_string: String,     // String format of the FPS inserted by user
+    pub dirty: bool,            // Something changed since the last redraw of the chart
+    pub effective_fps: f32,     // Measured number of chart redraws per second
+    rendered_frames: u32,       // Number of redraws since measured_since
+    measured_since: Instant,    // Start of the current measurement of the effective FPS
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        Self {
+            fps: 30,
+            fps_string: "30".to_string(),
+            dirty: true,
+            effective_fps: 0.0,
+            rendered_frames: 0,
+            measured_since: Instant::now(),
+        }
+    }
+}
+
+impl RenderSettings {
+    // Counting the redraws on every render tick, the effective FPS is updated once per second
+    pub fn measure(&mut self, now: Instant, rendered: bool) {
+        if rendered {
+            self.rendered_frames += 1;
+        }
+        let elapsed = now.saturating_duration_since(self.measured_since);
+        if elapsed >= Duration::from_secs(1) {
+            self.effective_fps = self.rendered_frames as f32 / elapsed.as_secs_f32();
+            self.rendered_frames = 0;
+            self.measured_since = now;
+        }
+    }
+}
+
+// Samples from which the channel statistics are computed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StatisticsScope {
+    #[default]
+    VisibleWindow,  // Samples displayed in the chart
+    WholeBuffer,    // All recorded samples
+}
+
+impl StatisticsScope {
+    pub const ALL: [StatisticsScope; 2] =
+        [StatisticsScope::VisibleWindow, StatisticsScope::WholeBuffer];
+}
+
+// Displayed in the PickList
+impl std::fmt::Display for StatisticsScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StatisticsScope::VisibleWindow => write!(f, "Visible window"),
+            StatisticsScope::WholeBuffer => write!(f, "Whole buffer"),
+        }
+    }
+}
+
+// Method for obtaining the range of the Y axis of a chart
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum YScaleMode {
+    FitAll,         // Min and max of the whole recorded history
+    #[default]
+    FitVisible,     // Min and max of the displayed samples
+    StickyMax,      // Range only grows with the displayed samples until the zoom is reset
+    Manual,         // Min and max inserted by user
+}
+
+impl YScaleMode {
+    pub const ALL: [YScaleMode; 4] = [
+        YScaleMode::FitAll,
+        YScaleMode::FitVisible,
+        YScaleMode::StickyMax,
+        YScaleMode::Manual,
+    ];
+}
+
+// Displayed in the PickList
+impl std::fmt::Display for YScaleMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            YScaleMode::FitAll => write!(f, "Fit all"),
+            YScaleMode::FitVisible => write!(f, "Fit visible"),
+            YScaleMode::StickyMax => write!(f, "Sticky max"),
+            YScaleMode::Manual => write!(f, "Manual"),
+        }
+    }
+}
+
+// Portion of the data range added above and below the data, so the traces do not touch the border
+const Y_RANGE_MARGIN: f64 = 0.05;
+
+// Distance in pixels from a measurement cursor at which it can be grabbed by the mouse
+const CURSOR_GRAB_DISTANCE: f32 = 5.0;
+// Color of the measurement cursors
+const MEASUREMENT_CURSOR_COLOR: RGBColor = RGBColor(255, 165, 0);
+// Color of the trigger position and level in the trigger mode
+const TRIGGER_MARKER_COLOR: RGBColor = RGBColor(220, 20, 60);
+// Color of the step response markers
+const STEP_MARKER_COLOR: RGBColor = RGBColor(255, 165, 0);
+// Color of the markers with the notes on the timeline
+const TIMELINE_MARKER_COLOR: RGBColor = RGBColor(0, 160, 160);
+// Opacity of the reference traces drawn behind the live traces
+const REFERENCE_TRACE_OPACITY: f64 = 0.35;
+// Color of the difference between the live channel and the reference
+const REFERENCE_DIFFERENCE_COLOR: RGBColor = RGBColor(199, 21, 133);
+
+// Color of the alarm markers and of the banner of the active alarms by the severity
+pub fn get_alarm_color(severity: AlarmSeverity) -> RGBColor {
+    match severity {
+        AlarmSeverity::Info => RGBColor(30, 144, 255),
+        AlarmSeverity::Warning => RGBColor(255, 140, 0),
+        AlarmSeverity::Critical => RGBColor(220, 20, 60),
+    }
+}
+
+// One of the measurement cursors, vertical cursors X1 X2 and horizontal cursors Y1 Y2
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CursorHandle {
+    Vertical(usize),
+    Horizontal(usize),
+}
+
+// Trace of the reference capture, or the difference of the live channel to it, drawn in a pane
+#[derive(Debug, Clone, Default)]
+pub struct OverlayTrace {
+    pub id: String,                 // Variable ID, the reference takes the color of the channel
+    pub points: Vec<(f64, f64)>,    // Points shifted to the displayed X axis
+    pub difference: bool,           // Live channel minus the reference
+}
+
+// Raised alarm marked in the pane with the alarm channel
+#[derive(Debug, Clone)]
+pub struct AlarmMarker {
+    pub pane: usize,
+    pub sample: f64,
+    pub severity: AlarmSeverity,
+}
+
+// Step response results annotated in the pane with the analysed channel
+#[derive(Debug, Clone, Default)]
+pub struct StepAnnotation {
+    pub pane: usize,
+    pub markers: Vec<(f64, String)>,    // Vertical markers (sample, label)
+    pub levels: Vec<f64>,               // Horizontal lines of the final value and settling band
+}
+
+// Two vertical and two horizontal measurement cursors in data coordinates
+#[derive(Debug, Default, Clone)]
+pub struct MeasurementCursors {
+    pub enabled: bool,
+    pub x: [f64; 2],    // Samples of the vertical cursors X1 X2
+    pub y: [f64; 2],    // Values of the horizontal cursors Y1 Y2
+}
+
+impl MeasurementCursors {
+    // Enabling the cursors places them to the thirds of the displayed viewport
+    pub fn toggle(&mut self, viewport: ChartViewport) {
+        self.enabled = !self.enabled;
+        if self.enabled {
+            let x_third = (viewport.x.1 - viewport.x.0) / 3.0;
+            let y_third = (viewport.y.1 - viewport.y.0) / 3.0;
+            self.x = [viewport.x.0 + x_third, viewport.x.1 - x_third];
+            self.y = [viewport.y.0 + y_third, viewport.y.1 - y_third];
+        }
+    }
+
+    pub fn move_cursor(&mut self, handle: CursorHandle, value: f64) {
+        match handle {
+            CursorHandle::Vertical(index) => self.x[index] = value,
+            CursorHandle::Horizontal(index) => self.y[index] = value,
+        }
+    }
+
+    // Lines of the readout, values of every channel at the vertical cursors and the differences
+    pub fn get_readout(
+        &self,
+        signal_store: &SignalStore,
+        device_profile: &DeviceProfile,
+    ) -> Vec<String> {
+        let mut readout: Vec<String> = Vec::new();
+        let samples = self.x.map(|x| x.round().max(0.0) as usize);
+
+        for channel in signal_store.channels.iter() {
+            if !device_profile.is_visible(&channel.id) {
+                continue;
+            }
+            let values =
+                samples.map(|sample| device_profile.scale(&channel.id, channel.get(sample)));
+            readout.push(format!(
+                "{}: X1 {} | X2 {} | Δ {}",
+                device_profile.get_label(&channel.id),
+                format_axis_value(values[0]),
+                format_axis_value(values[1]),
+                format_axis_value(values[1] - values[0]),
+            ));
+        }
+
+        let delta_x = self.x[1] - self.x[0];
+        let inverse_delta_x = if delta_x != 0.0 { 1.0 / delta_x } else { f64::NAN };
+        // Sample rate estimated from the receive times of the samples between the cursors, or of
+        // the whole history when the cursors are too close, Δt stays in samples without the rate
+        let sample_rate = signal_store
+            .get_sample_rate(samples[0].min(samples[1]), samples[0].max(samples[1]))
+            .or_else(|| signal_store.get_sample_rate(0, signal_store.len()));
+        readout.push(match sample_rate {
+            Some(sample_rate) => format!(
+                "Δt: {} s ({} samples) | 1/Δt: {} Hz",
+                format_axis_value(delta_x / sample_rate),
+                format_axis_value(delta_x),
+                format_axis_value(inverse_delta_x * sample_rate),
+            ),
+            None => format!(
+                "Δt: {} samples | 1/Δt: {} 1/sample",
+                format_axis_value(delta_x),
+                format_axis_value(inverse_delta_x),
+            ),
+        });
+        readout.push(format!(
+            "Y1: {} | Y2: {} | ΔY: {}",
+            format_axis_value(self.y[0]),
+            format_axis_value(self.y[1]),
+            format_axis_value(self.y[1] - self.y[0]),
+        ));
+
+        readout
+    }
+}
+
+// Y axis and decimation of one chart pane selected by user
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct PaneSettings {
+    pub decimation: DecimationMode, // Method for reducing the traces to the pixel width of chart
+    pub y_scale: YScaleMode,        // Method for obtaining the range of the Y axis
+    pub y_manual_min: f64,          // Parsed manual min of the Y axis
+    pub y_manual_max: f64,          // Parsed manual max of the Y axis
+    pub y_manual_min_string: String, // String format of the manual min inserted by user
+    pub y_manual_max_string: String, // String format of the manual max inserted by user
+}
+
+// Settings of one chart view selected by user, shared by all panes
+#[derive(Debug, Default, Clone)]
+pub struct ChartSettings {
+    pub trigger_marker: Option<(f64, f64)>, // Trigger sample and level drawn in the trigger mode
+    pub step_annotation: Option<StepAnnotation>, // Results of the step response analysis
+    pub alarm_markers: Vec<AlarmMarker>,    // Raised alarms with the highlight action in the range
+    pub timeline_markers: Vec<(f64, String)>, // Markers (sample, note) in the displayed range
+    pub show_markers: bool,                 // Side panel with the list of the markers
+    pub show_statistics: bool,              // Side panel with the statistics of the channels
+    pub statistics_scope: StatisticsScope,  // Samples from which the statistics are computed
+}
+
+impl PaneSettings {
+    // Range of the Y axis based on the scale mode, visible is the min/max of the displayed samples
+    // and whole_run is the min/max of the whole history, sticky_range is the accumulated range of
+    // the pane in the sticky max mode
+    pub fn get_y_range(
+        &self,
+        visible: Option<(f64, f64)>,
+        whole_run: Option<(f64, f64)>,
+        sticky_range: &mut Option<(f64, f64)>,
+    ) -> (f64, f64) {
+        let data_range = match self.y_scale {
+            YScaleMode::Manual if self.y_manual_max > self.y_manual_min => {
+                return (self.y_manual_min, self.y_manual_max)
+            }
+            // Until both manual values are valid the displayed samples are fitted
+            YScaleMode::Manual => visible,
+            YScaleMode::FitAll => whole_run,
+            YScaleMode::FitVisible => visible,
+            YScaleMode::StickyMax => {
+                *sticky_range = match (*sticky_range, visible) {
+                    (Some(sticky), Some(visible)) => {
+                        Some((sticky.0.min(visible.0), sticky.1.max(visible.1)))
+                    }
+                    (sticky, visible) => sticky.or(visible),
+                };
+                *sticky_range
+            }
+        };
+
+        match data_range {
+            Some((min, max)) if max > min => {
+                let margin = (max - min) * Y_RANGE_MARGIN;
+                (min - margin, max + margin)
+            }
+            // Constant signal is displayed in the middle of the chart
+            Some((min, _)) => (min - 1.0, min + 1.0),
+            None => (-1.0, 1.0),
+        }
+    }
+
+    // Returning the Y axis to the automatic scaling, the sticky ranges are cleared by the panes
+    pub fn reset_zoom(&mut self) {
+        if self.y_scale == YScaleMode::Manual {
+            self.y_scale = YScaleMode::default();
+        }
+    }
+}
+
+// Layout of the chart in pixels, used for building the chart and for mapping the cursor position
+// to the data coordinates
+const CHART_MARGIN: u32 = 25;
+const CHART_X_LABEL_AREA_SIZE: u32 = 55;
+const CHART_Y_LABEL_AREA_SIZE: u32 = 40;
+
+// Zoom factor of one step of the scroll wheel
+const ZOOM_STEP: f64 = 1.2;
+// Number of pixels of a touchpad scroll taken as one step of the scroll wheel
+const SCROLL_PIXELS_PER_LINE: f32 = 50.0;
+// Narrower box zoom is taken as a click and cancelled
+const MIN_BOX_ZOOM_SAMPLES: f64 = 2.0;
+
+// Displayed ranges of the axes of a chart in data coordinates
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChartViewport {
+    pub x: (f64, f64),  // Range of samples on the X axis
+    pub y: (f64, f64),  // Range of values on the Y axis
+}
+
+impl Default for ChartViewport {
+    fn default() -> Self {
+        Self {
+            x: (0.0, 1.0),
+            y: (-1.0, 1.0),
+        }
+    }
+}
+
+impl ChartViewport {
+    // Converting the position in the chart widget to the data coordinates
+    pub fn to_data(&self, plotting_area: iced::Rectangle, position: iced::Point) -> (f64, f64) {
+        let x_ratio = ((position.x - plotting_area.x) / plotting_area.width) as f64;
+        let y_ratio = ((position.y - plotting_area.y) / plotting_area.height) as f64;
+        (
+            self.x.0 + x_ratio * (self.x.1 - self.x.0),
+            self.y.1 - y_ratio * (self.y.1 - self.y.0),
+        )
+    }
+}
+
+// Zoom of the chart panes, the X axis is shared by all panes and every pane has its own Y axis
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ChartZoom {
+    pub x: Option<(f64, f64)>,              // Range of samples selected by zooming and panning,
+                                            // None follows the data
+    pub y: HashMap<usize, (f64, f64)>,      // Range of values of the zoomed panes
+}
+
+impl ChartZoom {
+    pub fn is_zoomed(&self) -> bool {
+        self.x.is_some() || !self.y.is_empty()
+    }
+
+    // Saving the viewport of the zoomed or panned pane
+    pub fn set(&mut self, pane: usize, viewport: ChartViewport) {
+        self.x = Some(viewport.x);
+        self.y.insert(pane, viewport.y);
+    }
+}
+
+// Data and state of one displayed chart pane, refreshed only on the render tick
+#[derive(Default)]
+pub struct ChartPane {
+    pub data: (VecDeque<u128>, HashMap<u128, VecDeque<f64>>), // Data of the channels assigned
+                                                               // to the pane
+    pub channels: Vec<(usize, String)>,     // Position in the signal store and variable ID of the
+                                            // plotted channels, the data are indexed by position + 1
+    pub cache: Cache,                       // Cache of the drawn pane
+    pub viewport: ChartViewport,            // Displayed ranges of the axes
+    pub y_sticky_range: Option<(f64, f64)>, // Accumulated range in the sticky max mode
+    pub box_selection: Option<((f64, f64), (f64, f64))>, // Corners of the box zoom being selected
+    pub overlays: Vec<OverlayTrace>,        // Reference traces of the channels assigned to the pane
+    pub cursors: MeasurementCursors,        // Measurement cursors of the pane
+}
+
+impl ChartPane {
+    // Data of the channels at the positions in the signal store in the range start..end scaled by
+    // the device profile, the received, derived and filtered channels are taken the same way
+    pub fn set_data(
+        &mut self,
+        signal_store: &SignalStore,
+        device_profile: &DeviceProfile,
+        positions: &[usize],
+        (start, end): (usize, usize),
+        max_buckets: usize,
+    ) {
+        let (x_values, mut y_values) = signal_store.window(positions, start, end, max_buckets);
+        let mut pane_y_values = HashMap::new();
+        self.channels.clear();
+        for &position in positions.iter() {
+            let chart_index = position as u128 + 1;
+            let id = &signal_store.channels[position].id;
+            if let Some(mut values) = y_values.remove(&chart_index) {
+                for value in values.iter_mut() {
+                    *value = device_profile.scale(id, *value);
+                }
+                pane_y_values.insert(chart_index, values);
+            }
+            self.channels.push((position, id.clone()));
+        }
+        self.data = (x_values, pane_y_values);
+    }
+}
+
+// Interaction state of the chart widget kept by iced between the events
+#[derive(Debug, Default)]
+pub struct ChartInteraction {
+    pan_start: Option<(iced::Point, ChartViewport)>, // Cursor position and viewport when the
+                                                      // panning started
+    pan_moved: bool,                                  // The viewport was already moved by the
+                                                      // current drag
+    box_start: Option<(f64, f64)>,                    // First corner of the box zoom
+    dragged_cursor: Option<CursorHandle>,             // Measurement cursor being dragged
+    modifiers: keyboard::Modifiers,                   // Pressed Shift/Ctrl for the scroll zoom
+}
+
+// Area of the chart widget of given size where the data are plotted
+fn get_plotting_area(size: Size) -> iced::Rectangle {
+    let left = (CHART_MARGIN + CHART_Y_LABEL_AREA_SIZE) as f32;
+    let top = CHART_MARGIN as f32;
+    let right = size.width - CHART_MARGIN as f32;
+    let bottom = size.height - (CHART_MARGIN + CHART_X_LABEL_AREA_SIZE) as f32;
+    iced::Rectangle {
+        x: left,
+        y: top,
+        width: (right - left).max(1.0),
+        height: (bottom - top).max(1.0),
+    }
+}
+
+// Moving the position outside of the area to its nearest border
+fn clamp_to_area(area: iced::Rectangle, position: iced::Point) -> iced::Point {
+    iced::Point::new(
+        position.x.clamp(area.x, area.x + area.width),
+        position.y.clamp(area.y, area.y + area.height),
+    )
+}
+
+// Zooming the range by factor around the anchor, which stays at the same position
+fn zoom_range(range: (f64, f64), anchor: f64, factor: f64) -> (f64, f64) {
+    (
+        anchor - (anchor - range.0) * factor,
+        anchor + (range.1 - anchor) * factor,
+    )
+}
+
+// Formatting the value of the Y axis label, so small and large values are readable
+pub fn format_axis_value(value: f64) -> String {
+    let magnitude = value.abs();
+    if value.is_nan() {
+        "-".to_string()
+    } else if magnitude == 0.0 {
+        "0".to_string()
+    } else if !(1e-3..1e6).contains(&magnitude) {
+        format!("{:.2e}", value)
+    } else if magnitude >= 100.0 {
+        format!("{:.0}", value)
+    } else if magnitude >= 1.0 {
+        format!("{:.2}", value)
+    } else {
+        format!("{:.4}", value)
+    }
+}
+
 // Default chart struct used for inputs
-pub struct DefaultChart {
+pub struct DefaultChart<'a> {
     pub data: (VecDeque<u128>, HashMap<u128, VecDeque<f64>>),
-    pub json_data: serde_json::Value,
+    pub channels: &'a [(usize, String)], // Position in the signal store and variable ID of the
+                                         // plotted channels
     pub theme: Theme,
+    pub cache: &'a Cache,       // Cache cleared on the render tick, otherwise the chart is not
+                                // redrawn when only the widgets around are updated
+    pub settings: ChartSettings,
+    pub pane_settings: PaneSettings, // Y axis and decimation of the pane
+    pub cursors: MeasurementCursors, // Measurement cursors of the pane
+    pub viewport: ChartViewport, // Displayed ranges of the X and Y axes
+    pub box_selection: Option<((f64, f64), (f64, f64))>, // Corners of the box zoom being selected
+    pub pane: usize,             // Index of the pane, sent with the zoom messages
+    pub device_profile: &'a DeviceProfile, // Names, units, colors and line widths of the channels
+    pub overlays: &'a [OverlayTrace],       // Reference traces drawn behind the live traces
 }
 
 
 // Implementing the chart with builder for used struct - defining displayed chart
-impl Chart<Message> for DefaultChart {
-    type State = ();
-    fn build_chart<DB: DrawingBackend>(&self, _state: &Self::State, mut builder: ChartBuilder<DB>) {
-        let mut newest_time: u128;
-        let mut oldest_time: u128 = 0;
+impl Chart<Message> for DefaultChart<'_> {
+    type State = ChartInteraction;
 
-        if let Some(newest_time_data) = self.data.0.back().map(|&x| x) {
-            newest_time = newest_time_data;
-        } else {
-            //newest_time = self.new_data_points.0[self.new_data_points.0.len()];
-            newest_time = 0;
-        }
+    // Drawing the chart through the cache, so it is redrawn only at the selected FPS
+    fn draw<R: Renderer, F: Fn(&mut Frame)>(&self, renderer: &R, size: Size, f: F) -> Geometry {
+        renderer.draw_cache(self.cache, size, f)
+    }
 
-        if let Some(oldest_time_iter) = self
-            .data
-            .0
-            .iter()
-            .map(|&x| x)
-            .min_by(|a, b| a.partial_cmp(b).unwrap())
-        {
-            oldest_time = oldest_time_iter;
+    // Zooming with the scroll wheel (Shift = only Y axis, Ctrl = only X axis), panning by dragging
+    // with the left button and box zoom by dragging with the right button
+    // the new viewport is sent to the application, which keeps the zoom history
+    fn update(
+        &self,
+        state: &mut Self::State,
+        event: Event,
+        bounds: iced::Rectangle,
+        cursor: mouse::Cursor,
+    ) -> (event::Status, Option<Message>) {
+        let plotting_area = get_plotting_area(bounds.size());
+        let position = cursor.position_in(bounds);
+        let position_in_plot = position.filter(|position| plotting_area.contains(*position));
+
+        match event {
+            Event::Keyboard(keyboard::Event::ModifiersChanged(modifiers)) => {
+                state.modifiers = modifiers;
+                (event::Status::Ignored, None)
+            }
+            Event::Mouse(mouse::Event::WheelScrolled { delta }) => {
+                let Some(position) = position_in_plot else {
+                    return (event::Status::Ignored, None);
+                };
+                let lines = match delta {
+                    mouse::ScrollDelta::Lines { y, .. } => y,
+                    mouse::ScrollDelta::Pixels { y, .. } => y / SCROLL_PIXELS_PER_LINE,
+                };
+                // Scrolling up zooms in
+                let factor = ZOOM_STEP.powf(-lines as f64);
+                let (x, y) = self.viewport.to_data(plotting_area, position);
+                let mut zoomed = self.viewport;
+                if !state.modifiers.shift() {
+                    zoomed.x = zoom_range(self.viewport.x, x, factor);
+                }
+                if !state.modifiers.control() {
+                    zoomed.y = zoom_range(self.viewport.y, y, factor);
+                }
+                (event::Status::Captured, Some(Message::ChartViewportChanged(self.pane, zoomed)))
+            }
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                let Some(position) = position_in_plot else {
+                    return (event::Status::Ignored, None);
+                };
+                // Grabbing a measurement cursor has priority over panning
+                if let Some(handle) = self.get_cursor_handle_at(plotting_area, position) {
+                    state.dragged_cursor = Some(handle);
+                    return (event::Status::Captured, None);
+                }
+                state.pan_start = Some((position, self.viewport));
+                state.pan_moved = false;
+                (event::Status::Captured, None)
+            }
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Right)) => {
+                let Some(position) = position_in_plot else {
+                    return (event::Status::Ignored, None);
+                };
+                let corner = self.viewport.to_data(plotting_area, position);
+                state.box_start = Some(corner);
+                (
+                    event::Status::Captured,
+                    Some(Message::ChartBoxSelecting(self.pane, Some((corner, corner)))),
+                )
+            }
+            Event::Mouse(mouse::Event::CursorMoved { .. }) => {
+                let Some(position) = position else {
+                    return (event::Status::Ignored, None);
+                };
+                if let Some(handle) = state.dragged_cursor {
+                    let (x, y) = self
+                        .viewport
+                        .to_data(plotting_area, clamp_to_area(plotting_area, position));
+                    let value = match handle {
+                        CursorHandle::Vertical(_) => x,
+                        CursorHandle::Horizontal(_) => y,
+                    };
+                    return (
+                        event::Status::Captured,
+                        Some(Message::MoveMeasurementCursor(self.pane, handle, value)),
+                    );
+                }
+                if let Some((pan_start_position, pan_start_viewport)) = state.pan_start {
+                    // Moving the viewport with the cursor, the first move is saved to the zoom
+                    // history, the following moves of the same drag are not
+                    let dx = (position.x - pan_start_position.x) as f64 / plotting_area.width as f64
+                        * (pan_start_viewport.x.1 - pan_start_viewport.x.0);
+                    let dy = (position.y - pan_start_position.y) as f64 / plotting_area.height as f64
+                        * (pan_start_viewport.y.1 - pan_start_viewport.y.0);
+                    let panned = ChartViewport {
+                        x: (pan_start_viewport.x.0 - dx, pan_start_viewport.x.1 - dx),
+                        y: (pan_start_viewport.y.0 + dy, pan_start_viewport.y.1 + dy),
+                    };
+                    let message = if state.pan_moved {
+                        Message::ChartPanMoved(self.pane, panned)
+                    } else {
+                        Message::ChartViewportChanged(self.pane, panned)
+                    };
+                    state.pan_moved = true;
+                    return (event::Status::Captured, Some(message));
+                }
+                if let Some(box_start) = state.box_start {
+                    let corner = self
+                        .viewport
+                        .to_data(plotting_area, clamp_to_area(plotting_area, position));
+                    return (
+                        event::Status::Captured,
+                        Some(Message::ChartBoxSelecting(self.pane, Some((box_start, corner)))),
+                    );
+                }
+                (event::Status::Ignored, None)
+            }
+            Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => {
+                if state.pan_start.take().is_some() || state.dragged_cursor.take().is_some() {
+                    return (event::Status::Captured, None);
+                }
+                (event::Status::Ignored, None)
+            }
+            Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Right)) => {
+                let Some(box_start) = state.box_start.take() else {
+                    return (event::Status::Ignored, None);
+                };
+                let box_end = match position {
+                    Some(position) => self
+                        .viewport
+                        .to_data(plotting_area, clamp_to_area(plotting_area, position)),
+                    None => box_start,
+                };
+                let zoomed = ChartViewport {
+                    x: (box_start.0.min(box_end.0), box_start.0.max(box_end.0)),
+                    y: (box_start.1.min(box_end.1), box_start.1.max(box_end.1)),
+                };
+                // Too small box is taken as a click and the selection is cancelled
+                if zoomed.x.1 - zoomed.x.0 < MIN_BOX_ZOOM_SAMPLES || zoomed.y.1 <= zoomed.y.0 {
+                    return (event::Status::Captured, Some(Message::ChartBoxSelecting(self.pane, None)));
+                }
+                (event::Status::Captured, Some(Message::ChartViewportChanged(self.pane, zoomed)))
+            }
+            _ => (event::Status::Ignored, None),
         }
+    }
 
-        if let Some(newest_time_iter) = self
-            .data
-            .0
-            .iter()
-            .map(|&x| x)
-            .max_by(|a, b| a.partial_cmp(b).unwrap())
-        {
-            newest_time = newest_time_iter;
-        }
-
-        // Used for dynamic axis - would need to change the variable names to correspond with the
-        // newest version
-        // otherwise when using Q32.15
-        // the min max would be
-        // -65536.0 131072.0
-        // Finding max y value from tuples
-
-        //        let max_y = match self
-        //            .new_data_points.0
-        //            .iter()
-        //            .map(|& y| y)
-        //            .max_by(|a, b| a.partial_cmp(b).unwrap())
-        //        {
-        //            Some(v) => v,
-        //            None => 0.0,
-        //        };
-        //
-        //        let min_y = match self
-        //            .new_data_points.0
-        //            .iter()
-        //            .map(|& y| y)
-        //            .min_by(|a, b| a.partial_cmp(b).unwrap())
-        //        {
-        //            Some(v) => v,
-        //            None => 0.0,
-        //        };
-        //
-
-        let mut bold_line_style_color: RGBAColor;
-        let mut light_line_style_color: RGBAColor;
-        let mut axis_style_color: RGBAColor;
-        let mut label_style_color: RGBAColor;
-
-        if self.theme == theme::Theme::Light {
-            bold_line_style_color = plotters::style::colors::BLACK.mix(0.8);
-            light_line_style_color = plotters::style::colors::BLACK.mix(0.4);
-            axis_style_color = plotters::style::colors::BLACK.mix(0.8);
-            label_style_color = plotters::style::colors::BLACK.mix(1.0);
-        } else {
-            bold_line_style_color = plotters::style::colors::WHITE.mix(0.1);
-            light_line_style_color = plotters::style::colors::WHITE.mix(0.05);
-            axis_style_color = plotters::style::colors::WHITE.mix(0.45);
-            label_style_color = plotters::style::colors::WHITE.mix(1.0);
+    fn mouse_interaction(
+        &self,
+        state: &Self::State,
+        bounds: iced::Rectangle,
+        cursor: mouse::Cursor,
+    ) -> mouse::Interaction {
+        if state.pan_start.is_some() {
+            return mouse::Interaction::Grabbing;
+        }
+        let plotting_area = get_plotting_area(bounds.size());
+        let hovered_cursor = match state.dragged_cursor {
+            Some(handle) => Some(handle),
+            None => cursor
+                .position_in(bounds)
+                .and_then(|position| self.get_cursor_handle_at(plotting_area, position)),
+        };
+        match hovered_cursor {
+            Some(CursorHandle::Vertical(_)) => return mouse::Interaction::ResizingHorizontally,
+            Some(CursorHandle::Horizontal(_)) => return mouse::Interaction::ResizingVertically,
+            None => {}
         }
+        match cursor.position_in(bounds) {
+            Some(position) if get_plotting_area(bounds.size()).contains(position) => {
+                mouse::Interaction::Crosshair
+            }
+            _ => mouse::Interaction::Idle,
+        }
+    }
+
+    fn build_chart<DB: DrawingBackend>(&self, _state: &Self::State, mut builder: ChartBuilder<DB>) {
+        // Displayed ranges of the axes, either following the data or selected by zooming
+        let (oldest_time, newest_time) = self.viewport.x;
+        let (min_y, max_y) = self.viewport.y;
+
+        let (bold_line_style_color, light_line_style_color, axis_style_color, label_style_color) =
+            get_chart_style_colors(&self.theme);
 
         let mut chart = builder
-            .x_label_area_size(55)
-            .y_label_area_size(40)
-            .margin(25)
-            .build_cartesian_2d(oldest_time..newest_time, -100000.0..100000.0)
+            .x_label_area_size(CHART_X_LABEL_AREA_SIZE)
+            .y_label_area_size(CHART_Y_LABEL_AREA_SIZE)
+            .margin(CHART_MARGIN)
+            .build_cartesian_2d(oldest_time..newest_time, min_y..max_y)
             .expect("failed to build chart");
 
         chart
@@ -191,52 +894,91 @@ impl Chart<Message> for DefaultChart {
                     .color(&label_style_color)
                     .transform(FontTransform::Rotate90),
             )
-            .y_label_formatter(&|y: &f64| format!("{}", y))
+            .y_label_formatter(&|y: &f64| format_axis_value(*y))
             .x_label_style(("sans-serif", 15).into_font().color(&label_style_color))
-            .x_label_formatter(&|x| format!("{}", x))
+            .x_label_formatter(&|x: &f64| format!("{:.0}", x))
             .x_desc("Sample")
             //.y_desc("Here's the label for Y")
             .draw()
             .expect("failed to draw chart mesh");
 
-        if let Some(obj) = self.json_data.as_object() {
-            let mut index_chart = 1;
-            let mut index_chart_color = 1;
-            for (key, _value) in obj {
-                chart
-                    .draw_series(
-                        LineSeries::new(
-                            self.data.0.iter().enumerate().filter_map(|(index, &x)| {
-                                // Get the value from the hashmap at the selected index
-                                if let Some(y_values) = self.data.1.get(&index_chart) {
-                                    // Ensure that the index is within bounds of the y_values vector
-                                    if index < y_values.len() {
-                                        Some((x as u128, y_values[index]))
-                                    } else {
-                                        None
-                                    }
-                                } else {
-                                    None
-                                }
-                            }),
-                            // 0.,
-                            // &Palette99::pick(index_chart_color).mix(0.),
-                            get_line_series_style(index_chart_color),
-                        ), // .border_style(ShapeStyle::from(Palette99::pick(index_chart_color)).stroke_width(2)),
-                           //.border_style(&Palette99::pick(index_chart_color)),
-                    )
-                    .expect("failed to draw chart data")
-                    .label(key.to_string())
-                    .legend(move |(x, y)| {
-                        Rectangle::new(
-                            [(x - 1, y - 1), (x + 15, y)],
-                            &Palette99::pick(index_chart_color as usize),
-                        )
-                    });
-
-                index_chart = index_chart + 1;
-                index_chart_color = index_chart_color + 1;
-            }
+        // Width of the plotting area in pixels, every trace is decimated to this number of points
+        let (plot_width, _) = chart.plotting_area().dim_in_pixel();
+
+        // Reference traces are faded and drawn first, so the live traces stay on top
+        for overlay in self.overlays.iter() {
+            let trace_points: Vec<TracePoint> = overlay
+                .points
+                .iter()
+                .map(|&(x, y)| TracePoint { x, y })
+                .collect();
+            let decimated_points =
+                decimate(&trace_points, plot_width as usize, self.pane_settings.decimation);
+            let label = self.device_profile.get_label(&overlay.id);
+            let (line_style, label) = if overlay.difference {
+                (
+                    ShapeStyle::from(REFERENCE_DIFFERENCE_COLOR).stroke_width(1),
+                    format!("Δ {}", label),
+                )
+            } else {
+                // Color index of the channel is its position in the signal store
+                let index_chart_color = self
+                    .channels
+                    .iter()
+                    .find(|(_, id)| *id == overlay.id)
+                    .map_or(0, |(position, _)| *position as u32 + 1);
+                let line_style =
+                    get_channel_line_style(self.device_profile, &overlay.id, index_chart_color);
+                (
+                    ShapeStyle {
+                        color: line_style.color.mix(REFERENCE_TRACE_OPACITY),
+                        ..line_style
+                    },
+                    format!("{} (ref)", label),
+                )
+            };
+            let legend_color = line_style.color.filled();
+
+            chart
+                .draw_series(LineSeries::new(
+                    decimated_points.iter().map(|point| (point.x, point.y)),
+                    line_style,
+                ))
+                .expect("failed to draw reference trace")
+                .label(label)
+                .legend(move |(x, y)| Rectangle::new([(x - 1, y - 1), (x + 15, y)], legend_color));
+        }
+
+        // Traces of the channels of the pane, the data and the color are indexed by the position
+        // of the channel in the signal store, so they stay with the channel
+        for (position, id) in self.channels.iter() {
+            let index_chart = *position as u128 + 1;
+            // Get the values from the hashmap at the selected index and skip samples where the
+            // variable was not received
+            let trace_points: Vec<TracePoint> = match self.data.1.get(&index_chart) {
+                Some(y_values) => self
+                    .data
+                    .0
+                    .iter()
+                    .zip(y_values.iter())
+                    .filter(|(_, y)| !y.is_nan())
+                    .map(|(&x, &y)| TracePoint { x: x as f64, y })
+                    .collect(),
+                None => continue,
+            };
+            let decimated_points =
+                decimate(&trace_points, plot_width as usize, self.pane_settings.decimation);
+            let line_style = get_channel_line_style(self.device_profile, id, index_chart as u32);
+            let legend_color = line_style.color.filled();
+
+            chart
+                .draw_series(LineSeries::new(
+                    decimated_points.iter().map(|point| (point.x, point.y)),
+                    line_style,
+                ))
+                .expect("failed to draw chart data")
+                .label(self.device_profile.get_label(id))
+                .legend(move |(x, y)| Rectangle::new([(x - 1, y - 1), (x + 15, y)], legend_color));
         }
 
         //        chart
@@ -272,6 +1014,148 @@ impl Chart<Message> for DefaultChart {
         //                Rectangle::new([(x - 1, y + 1), (x + 15, y)], PLOT_LINE_COLOR_2)
         //            });
 
+        // Measurement cursors, vertical X1 X2 and horizontal Y1 Y2
+        if self.cursors.enabled {
+            for (index, x) in self.cursors.x.iter().enumerate() {
+                chart
+                    .draw_series(std::iter::once(PathElement::new(
+                        vec![(*x, min_y), (*x, max_y)],
+                        ShapeStyle::from(MEASUREMENT_CURSOR_COLOR).stroke_width(1),
+                    )))
+                    .expect("failed to draw measurement cursor");
+                chart
+                    .draw_series(std::iter::once(Text::new(
+                        format!("X{}", index + 1),
+                        (*x, max_y),
+                        ("sans-serif", 15).into_font().color(&MEASUREMENT_CURSOR_COLOR),
+                    )))
+                    .expect("failed to draw measurement cursor label");
+            }
+            for (index, y) in self.cursors.y.iter().enumerate() {
+                chart
+                    .draw_series(std::iter::once(PathElement::new(
+                        vec![(oldest_time, *y), (newest_time, *y)],
+                        ShapeStyle::from(MEASUREMENT_CURSOR_COLOR).stroke_width(1),
+                    )))
+                    .expect("failed to draw measurement cursor");
+                chart
+                    .draw_series(std::iter::once(Text::new(
+                        format!("Y{}", index + 1),
+                        (oldest_time, *y),
+                        ("sans-serif", 15).into_font().color(&MEASUREMENT_CURSOR_COLOR),
+                    )))
+                    .expect("failed to draw measurement cursor label");
+            }
+        }
+
+        // Trigger position and trigger level in the trigger mode
+        if let Some((trigger_x, trigger_level)) = self.settings.trigger_marker {
+            chart
+                .draw_series(std::iter::once(PathElement::new(
+                    vec![(trigger_x, min_y), (trigger_x, max_y)],
+                    ShapeStyle::from(TRIGGER_MARKER_COLOR.mix(0.6)).stroke_width(1),
+                )))
+                .expect("failed to draw trigger marker");
+            chart
+                .draw_series(std::iter::once(PathElement::new(
+                    vec![(oldest_time, trigger_level), (newest_time, trigger_level)],
+                    ShapeStyle::from(TRIGGER_MARKER_COLOR.mix(0.6)).stroke_width(1),
+                )))
+                .expect("failed to draw trigger marker");
+            chart
+                .draw_series(std::iter::once(Text::new(
+                    "T",
+                    (trigger_x, max_y),
+                    ("sans-serif", 15).into_font().color(&TRIGGER_MARKER_COLOR),
+                )))
+                .expect("failed to draw trigger marker label");
+        }
+
+        // Step response markers in the pane with the analysed channel
+        if let Some(annotation) = self
+            .settings
+            .step_annotation
+            .as_ref()
+            .filter(|annotation| annotation.pane == self.pane)
+        {
+            for level in annotation.levels.iter() {
+                chart
+                    .draw_series(std::iter::once(PathElement::new(
+                        vec![(oldest_time, *level), (newest_time, *level)],
+                        ShapeStyle::from(STEP_MARKER_COLOR.mix(0.5)).stroke_width(1),
+                    )))
+                    .expect("failed to draw step response level");
+            }
+            for (index, (x, label)) in annotation.markers.iter().enumerate() {
+                chart
+                    .draw_series(std::iter::once(PathElement::new(
+                        vec![(*x, min_y), (*x, max_y)],
+                        ShapeStyle::from(STEP_MARKER_COLOR.mix(0.7)).stroke_width(1),
+                    )))
+                    .expect("failed to draw step response marker");
+                // Labels are staggered, so the close markers stay readable
+                let label_y = max_y - (max_y - min_y) * 0.05 * (index % 4) as f64;
+                chart
+                    .draw_series(std::iter::once(Text::new(
+                        label.clone(),
+                        (*x, label_y),
+                        ("sans-serif", 13).into_font().color(&STEP_MARKER_COLOR),
+                    )))
+                    .expect("failed to draw step response label");
+            }
+        }
+
+        // Samples at which the alarms were raised
+        for marker in self.settings.alarm_markers.iter().filter(|marker| marker.pane == self.pane) {
+            let color = get_alarm_color(marker.severity);
+            chart
+                .draw_series(std::iter::once(PathElement::new(
+                    vec![(marker.sample, min_y), (marker.sample, max_y)],
+                    ShapeStyle::from(color.mix(0.7)).stroke_width(2),
+                )))
+                .expect("failed to draw alarm marker");
+            chart
+                .draw_series(std::iter::once(Text::new(
+                    "!",
+                    (marker.sample, max_y),
+                    ("sans-serif", 15).into_font().color(&color),
+                )))
+                .expect("failed to draw alarm marker label");
+        }
+
+        // Markers with the notes, drawn in every pane and labelled at the bottom
+        for (x, note) in self.settings.timeline_markers.iter() {
+            chart
+                .draw_series(std::iter::once(PathElement::new(
+                    vec![(*x, min_y), (*x, max_y)],
+                    ShapeStyle::from(TIMELINE_MARKER_COLOR.mix(0.7)).stroke_width(1),
+                )))
+                .expect("failed to draw timeline marker");
+            chart
+                .draw_series(std::iter::once(Text::new(
+                    note.clone(),
+                    (*x, min_y + (max_y - min_y) * 0.05),
+                    ("sans-serif", 13).into_font().color(&TIMELINE_MARKER_COLOR),
+                )))
+                .expect("failed to draw timeline marker label");
+        }
+
+        // Rubber band of the box zoom which is currently being selected
+        if let Some((corner_a, corner_b)) = self.box_selection {
+            chart
+                .draw_series(std::iter::once(Rectangle::new(
+                    [corner_a, corner_b],
+                    ShapeStyle::from(label_style_color.mix(0.15)).filled(),
+                )))
+                .expect("failed to draw box selection");
+            chart
+                .draw_series(std::iter::once(Rectangle::new(
+                    [corner_a, corner_b],
+                    ShapeStyle::from(label_style_color).stroke_width(1),
+                )))
+                .expect("failed to draw box selection");
+        }
+
         chart
             .configure_series_labels()
             .background_style(&WHITE.mix(0.8))
@@ -284,19 +1168,184 @@ impl Chart<Message> for DefaultChart {
     }
 }
 
-impl DefaultChart {
+impl<'a> DefaultChart<'a> {
+    // Measurement cursor close enough to the position to be grabbed
+    fn get_cursor_handle_at(
+        &self,
+        plotting_area: iced::Rectangle,
+        position: iced::Point,
+    ) -> Option<CursorHandle> {
+        if !self.cursors.enabled {
+            return None;
+        }
+        let (pixel_x, pixel_y) = (position.x - plotting_area.x, position.y - plotting_area.y);
+        for (index, x) in self.cursors.x.iter().enumerate() {
+            let cursor_pixel = ((x - self.viewport.x.0) / (self.viewport.x.1 - self.viewport.x.0)
+                * plotting_area.width as f64) as f32;
+            if (cursor_pixel - pixel_x).abs() <= CURSOR_GRAB_DISTANCE {
+                return Some(CursorHandle::Vertical(index));
+            }
+        }
+        for (index, y) in self.cursors.y.iter().enumerate() {
+            let cursor_pixel = ((self.viewport.y.1 - y) / (self.viewport.y.1 - self.viewport.y.0)
+                * plotting_area.height as f64) as f32;
+            if (cursor_pixel - pixel_y).abs() <= CURSOR_GRAB_DISTANCE {
+                return Some(CursorHandle::Horizontal(index));
+            }
+        }
+        None
+    }
+
     pub fn new(
-        data: (VecDeque<u128>, HashMap<u128, VecDeque<f64>>),
-        json_data: serde_json::Value,
+        pane: usize,
+        chart_pane: &'a ChartPane,
         theme: Theme,
+        settings: ChartSettings,
+        pane_settings: PaneSettings,
+        device_profile: &'a DeviceProfile,
     ) -> Self {
         Self {
-            data,
-            json_data,
+            data: chart_pane.data.clone(),
+            channels: &chart_pane.channels,
             theme,
+            cache: &chart_pane.cache,
+            settings,
+            pane_settings,
+            cursors: chart_pane.cursors.clone(),
+            viewport: chart_pane.viewport,
+            box_selection: chart_pane.box_selection,
+            pane,
+            device_profile,
+            overlays: &chart_pane.overlays,
         }
     }
-    pub fn view(self) -> Element<'static, Message> {
+    pub fn view(self) -> Element<'a, Message> {
         ChartWidget::new(self).into()
     }
 }
+
+// Struct for scrolling back through the history kept in the signal store
+#[derive(Default, Clone)]
+pub struct HistoryView {
+    pub span: u64,              // Parsed number of displayed samples, 0 means the whole run
+    pub span_string: String,    // String format of the number of displayed samples
+    pub end: Option<u64>,       // Last displayed sample, None means following the newest data
+}
+
+impl HistoryView {
+    // Range of samples start..end which should be displayed from the history of given length
+    pub fn get_range(&self, length: usize) -> (usize, usize) {
+        let end = match self.end {
+            Some(end) => (end as usize).min(length),
+            None => length,
+        };
+        if self.span == 0 {
+            (0, end)
+        } else {
+            (end.saturating_sub(self.span as usize), end)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filters::{ChannelFilter, FilterField, FilterKind};
+    use crate::transforms::{DriveTransform, TransformKind};
+    use serde_json::{Map, Value};
+
+    #[test]
+    fn derived_channels_in_pane_data() {
+        let mut device_profile = DeviceProfile::new("test");
+        let mut transform = DriveTransform::new(TransformKind::Clarke);
+        transform.inputs = ["@000".to_string(), "@001".to_string(), String::new()];
+        device_profile.transforms.push(transform);
+
+        let mut signal_store = SignalStore::new();
+        signal_store.directory =
+            std::env::temp_dir().join(format!("rtm-pane-{}", std::process::id()));
+        for sample in 0..10 {
+            let mut frame = Map::new();
+            frame.insert("@000".to_string(), Value::from(sample as f64));
+            frame.insert("@001".to_string(), Value::from(100.0 + sample as f64));
+            device_profile.apply_transforms(&mut frame);
+            signal_store.push_frame(&frame, &HashMap::new()).unwrap();
+        }
+
+        let positions: Vec<usize> = (0..signal_store.channels.len()).collect();
+        let mut chart_pane = ChartPane::default();
+        chart_pane.set_data(&signal_store, &device_profile, &positions, (0, 10), 100);
+
+        let ids: Vec<&str> = chart_pane.channels.iter().map(|(_, id)| id.as_str()).collect();
+        assert_eq!(ids, ["@000", "@001", "alpha", "beta"]);
+        // Every trace gets the samples of its own channel
+        let get_values = |id: &str| -> Vec<f64> {
+            let (position, _) =
+                chart_pane.channels.iter().find(|(_, channel)| channel == id).unwrap();
+            chart_pane.data.1[&(*position as u128 + 1)].iter().copied().collect()
+        };
+        assert_eq!(get_values("@001"), (0..10).map(|n| 100.0 + n as f64).collect::<Vec<f64>>());
+        assert_eq!(get_values("alpha"), (0..10).map(|n| n as f64).collect::<Vec<f64>>());
+        assert_eq!(chart_pane.data.0.len(), 10);
+    }
+
+    #[test]
+    fn virtual_channel_in_pane_data() {
+        let mut device_profile = DeviceProfile::new("test");
+        device_profile.add_expression();
+        device_profile.set_expression(0, "p = @000 * @001".to_string());
+
+        let mut signal_store = SignalStore::new();
+        signal_store.directory =
+            std::env::temp_dir().join(format!("rtm-virtual-{}", std::process::id()));
+        for sample in 0..10 {
+            let mut frame = Map::new();
+            frame.insert("@000".to_string(), Value::from(sample as f64));
+            frame.insert("@001".to_string(), Value::from(2.0));
+            device_profile.apply_expressions(&mut frame);
+            signal_store.push_frame(&frame, &HashMap::new()).unwrap();
+        }
+
+        let positions: Vec<usize> = (0..signal_store.channels.len()).collect();
+        let mut chart_pane = ChartPane::default();
+        chart_pane.set_data(&signal_store, &device_profile, &positions, (0, 10), 100);
+
+        let (position, _) = chart_pane.channels.iter().find(|(_, id)| id == "p").unwrap();
+        let values: Vec<f64> = chart_pane.data.1[&(*position as u128 + 1)].iter().copied().collect();
+        assert_eq!(values, (0..10).map(|n| 2.0 * n as f64).collect::<Vec<f64>>());
+    }
+
+    #[test]
+    fn filtered_channel_keeps_following_channels() {
+        let mut device_profile = DeviceProfile::new("test");
+        let mut filter = ChannelFilter::new(FilterKind::MovingAverage);
+        filter.set_field(FilterField::Input, "@000".to_string());
+        device_profile.filters.push(filter);
+
+        let mut signal_store = SignalStore::new();
+        signal_store.directory =
+            std::env::temp_dir().join(format!("rtm-filtered-{}", std::process::id()));
+        for sample in 0..10 {
+            let mut frame = Map::new();
+            frame.insert("@000".to_string(), Value::from(sample as f64));
+            frame.insert("@001".to_string(), Value::from(-1.0));
+            device_profile.apply_filters(&mut frame);
+            signal_store.push_frame(&frame, &HashMap::new()).unwrap();
+        }
+
+        // Output @000_f is sorted between its input and the next channel in the frame
+        let positions: Vec<usize> = (0..signal_store.channels.len()).collect();
+        let mut chart_pane = ChartPane::default();
+        chart_pane.set_data(&signal_store, &device_profile, &positions, (0, 10), 100);
+        let ids: Vec<&str> = chart_pane.channels.iter().map(|(_, id)| id.as_str()).collect();
+        assert_eq!(ids, ["@000", "@000_f", "@001"]);
+        for (position, id) in chart_pane.channels.iter() {
+            let values = &chart_pane.data.1[&(*position as u128 + 1)];
+            match id.as_str() {
+                "@000" => assert_eq!(values[9], 9.0),
+                "@000_f" => assert_eq!(values[9], 4.5),
+                _ => assert!(values.iter().all(|value| *value == -1.0)),
+            }
+        }
+    }
+}
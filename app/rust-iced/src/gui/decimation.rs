@@ -0,0 +1,122 @@
+/*
+
+MIT License
+
+Copyright (c) 2024 Petr Zakopal, Deparment of Electric Drives and Traction, CTU FEE
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+/*----------------------------------------------------------------------------*/
+
+use std::fmt;
+
+use plotters_iced::sample::lttb::{DataPoint, LttbSource};
+use serde::{Deserialize, Serialize};
+
+// Method used for reducing the number of drawn points of one trace to the pixel width of the chart
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum DecimationMode {
+    Off,        // Every point of the trace is drawn
+    Lttb,       // Largest-Triangle-Three-Buckets, keeps the visual shape of the trace
+    #[default]
+    MinMax,     // Min and max of every pixel column, no spikes are hidden
+}
+
+impl DecimationMode {
+    pub const ALL: [DecimationMode; 3] = [
+        DecimationMode::Off,
+        DecimationMode::Lttb,
+        DecimationMode::MinMax,
+    ];
+}
+
+// Displayed in the PickList
+impl fmt::Display for DecimationMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecimationMode::Off => write!(f, "Off"),
+            DecimationMode::Lttb => write!(f, "LTTB"),
+            DecimationMode::MinMax => write!(f, "Min/Max"),
+        }
+    }
+}
+
+// One point of a trace, X values must be increasing for the decimation to work
+#[derive(Debug, Clone, Copy)]
+pub struct TracePoint {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl DataPoint for TracePoint {
+    fn x(&self) -> f64 {
+        self.x
+    }
+
+    fn y(&self) -> f64 {
+        self.y
+    }
+}
+
+// Reducing the points of a trace to approximately target_points using the selected method
+pub fn decimate(points: &[TracePoint], target_points: usize, mode: DecimationMode) -> Vec<TracePoint> {
+    if points.len() <= target_points {
+        return points.to_vec();
+    }
+
+    match mode {
+        DecimationMode::Off => points.to_vec(),
+        DecimationMode::Lttb => points.lttb(target_points).copied().collect(),
+        // Every bucket gives two points, so half of the buckets is used
+        DecimationMode::MinMax => decimate_min_max(points, (target_points / 2).max(1)),
+    }
+}
+
+// Min/max envelope of the trace, every bucket is represented by its min and max point in the
+// order in which they occurred, so the trace keeps its shape and spikes stay visible
+fn decimate_min_max(points: &[TracePoint], buckets: usize) -> Vec<TracePoint> {
+    let mut decimated: Vec<TracePoint> = Vec::with_capacity(buckets * 2);
+    let bucket_size = points.len().div_ceil(buckets);
+
+    for bucket in points.chunks(bucket_size) {
+        let mut min_point = bucket[0];
+        let mut max_point = bucket[0];
+        for point in bucket {
+            if point.y < min_point.y {
+                min_point = *point;
+            }
+            if point.y > max_point.y {
+                max_point = *point;
+            }
+        }
+
+        if min_point.x == max_point.x {
+            decimated.push(min_point);
+        } else if min_point.x < max_point.x {
+            decimated.push(min_point);
+            decimated.push(max_point);
+        } else {
+            decimated.push(max_point);
+            decimated.push(min_point);
+        }
+    }
+
+    decimated
+}
@@ -0,0 +1,189 @@
+/*
+
+MIT License
+
+Copyright (c) 2024 Petr Zakopal, Deparment of Electric Drives and Traction, CTU FEE
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+/*----------------------------------------------------------------------------*/
+
+use iced::{
+    widget::canvas::{Cache, Frame, Geometry},
+    Element, Size, Theme,
+};
+use plotters::{
+    element::{Rectangle, Text},
+    series::LineSeries,
+    style::{Color, FontTransform, IntoFont, Palette, Palette99, RGBColor, ShapeStyle},
+};
+use plotters_iced::{Chart, ChartBuilder, ChartWidget, DrawingBackend, Renderer};
+
+use super::{
+    format_axis_value, get_chart_style_colors, StatisticsScope, CHART_MARGIN,
+    CHART_X_LABEL_AREA_SIZE, CHART_Y_LABEL_AREA_SIZE,
+};
+use crate::{
+    histogram::{Histogram, HISTOGRAM_DEFAULT_BINS},
+    Message,
+};
+
+// Color of the mean and σ markers
+const HISTOGRAM_MARKER_COLOR: RGBColor = RGBColor(220, 20, 60);
+
+// Settings of the histogram desktop selected by user
+#[derive(Debug, Clone)]
+pub struct HistogramSettings {
+    pub channel: Option<String>,        // Variable ID of the analysed channel
+    pub scope: StatisticsScope,         // Visible window or the whole buffer
+    pub bins: usize,                    // Number of bins
+    pub bins_string: String,            // String format of the number of bins inserted by user
+    pub range_min: Option<f64>,         // Range inserted by user, None takes the min and max of
+    pub range_max: Option<f64>,         // the samples
+    pub range_min_string: String,
+    pub range_max_string: String,
+}
+
+impl Default for HistogramSettings {
+    fn default() -> Self {
+        Self {
+            channel: None,
+            scope: StatisticsScope::default(),
+            bins: HISTOGRAM_DEFAULT_BINS,
+            bins_string: HISTOGRAM_DEFAULT_BINS.to_string(),
+            range_min: None,
+            range_max: None,
+            range_min_string: String::new(),
+            range_max_string: String::new(),
+        }
+    }
+}
+
+impl HistogramSettings {
+    // Range inserted by user, only when both limits are inserted
+    pub fn get_range(&self) -> Option<(f64, f64)> {
+        match (self.range_min, self.range_max) {
+            (Some(min), Some(max)) if min < max => Some((min, max)),
+            _ => None,
+        }
+    }
+}
+
+// Bars of the histogram with the mean and the mean ± σ markers
+pub struct HistogramChart<'a> {
+    pub histogram: &'a Histogram,
+    pub label: String,                  // Label of the channel on the X axis
+    pub theme: Theme,
+    pub cache: &'a Cache,               // Cache cleared only when the histogram is recomputed
+}
+
+impl Chart<Message> for HistogramChart<'_> {
+    type State = ();
+
+    fn draw<R: Renderer, F: Fn(&mut Frame)>(&self, renderer: &R, size: Size, f: F) -> Geometry {
+        renderer.draw_cache(self.cache, size, f)
+    }
+
+    fn build_chart<DB: DrawingBackend>(&self, _state: &Self::State, mut builder: ChartBuilder<DB>) {
+        let (bold_line_style_color, light_line_style_color, axis_style_color, label_style_color) =
+            get_chart_style_colors(&self.theme);
+
+        let (start, end) = if self.histogram.is_empty() {
+            (0.0, 1.0)
+        } else {
+            self.histogram.range
+        };
+        let max_count = self.histogram.counts.iter().copied().max().unwrap_or(0).max(1) as f64;
+        let mut chart = builder
+            .x_label_area_size(CHART_X_LABEL_AREA_SIZE)
+            .y_label_area_size(CHART_Y_LABEL_AREA_SIZE)
+            .margin(CHART_MARGIN)
+            .build_cartesian_2d(start..end, 0.0..max_count * 1.1)
+            .expect("failed to build histogram chart");
+
+        chart
+            .configure_mesh()
+            .bold_line_style(bold_line_style_color)
+            .light_line_style(light_line_style_color)
+            .axis_style(ShapeStyle::from(axis_style_color).stroke_width(1))
+            .y_labels(10)
+            .x_labels(10)
+            .y_label_style(
+                ("sans-serif", 15)
+                    .into_font()
+                    .color(&label_style_color)
+                    .transform(FontTransform::Rotate90),
+            )
+            .y_label_formatter(&|y: &f64| format!("{:.0}", y))
+            .x_label_style(("sans-serif", 15).into_font().color(&label_style_color))
+            .x_label_formatter(&|x: &f64| format_axis_value(*x))
+            .x_desc(self.label.as_str())
+            .draw()
+            .expect("failed to draw histogram mesh");
+
+        let bin_width = self.histogram.get_bin_width();
+        let bar_color = Palette99::pick(1).to_rgba();
+        chart
+            .draw_series(self.histogram.counts.iter().enumerate().map(|(bin, count)| {
+                let left = start + bin as f64 * bin_width;
+                Rectangle::new(
+                    [(left, 0.0), (left + bin_width, *count as f64)],
+                    bar_color.mix(0.7).filled(),
+                )
+            }))
+            .expect("failed to draw histogram bars");
+
+        // Mean and mean ± σ, markers outside the range are not drawn
+        let statistics = &self.histogram.statistics;
+        if statistics.is_empty() {
+            return;
+        }
+        let sigma = statistics.standard_deviation();
+        let markers = [
+            (statistics.mean, "μ", 2),
+            (statistics.mean - sigma, "-σ", 1),
+            (statistics.mean + sigma, "+σ", 1),
+        ];
+        for (x, label, stroke_width) in markers {
+            if !(start..=end).contains(&x) {
+                continue;
+            }
+            chart
+                .draw_series(LineSeries::new(
+                    [(x, 0.0), (x, max_count * 1.1)],
+                    ShapeStyle::from(HISTOGRAM_MARKER_COLOR).stroke_width(stroke_width),
+                ))
+                .expect("failed to draw histogram marker");
+            chart
+                .draw_series(std::iter::once(Text::new(
+                    label,
+                    (x, max_count * 1.08),
+                    ("sans-serif", 14).into_font().color(&label_style_color),
+                )))
+                .expect("failed to draw histogram marker label");
+        }
+    }
+}
+
+impl<'a> HistogramChart<'a> {
+    pub fn view(self) -> Element<'a, Message> {
+        ChartWidget::new(self).into()
+    }
+}
@@ -0,0 +1,156 @@
+/*
+
+MIT License
+
+Copyright (c) 2024 Petr Zakopal, Deparment of Electric Drives and Traction, CTU FEE
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+/*----------------------------------------------------------------------------*/
+
+use iced::{
+    widget::canvas::{Cache, Frame, Geometry},
+    Element, Length, Size, Theme,
+};
+use plotters::{
+    element::{Rectangle, Text},
+    series::LineSeries,
+    style::{Color, IntoFont, Palette, Palette99, ShapeStyle},
+};
+use plotters_iced::{Chart, ChartBuilder, ChartWidget, DrawingBackend, Renderer};
+
+use super::{get_chart_style_colors, CHART_MARGIN, CHART_Y_LABEL_AREA_SIZE};
+use crate::{bit_fields::LogicLane, Message};
+
+// Height of one lane of the logic analyzer in pixels
+pub const LOGIC_LANE_HEIGHT: f32 = 28.0;
+// Height of the axis and margins of the logic analyzer chart in pixels
+const LOGIC_CHART_BORDER_HEIGHT: f32 = 45.0;
+// Segments narrower than this number of pixels are drawn without the label
+const LOGIC_LABEL_MIN_WIDTH: f64 = 30.0;
+
+// Logic analyzer lanes drawn under the chart panes with the same X range, single bits are drawn
+// as digital traces, multi-bit fields as a bus with the decoded labels
+pub struct LogicChart<'a> {
+    pub lanes: &'a [LogicLane],
+    pub x_range: (f64, f64),            // Same samples as the X axis of the chart panes
+    pub theme: Theme,
+    pub cache: &'a Cache,               // Cache cleared only when the lanes are refreshed
+}
+
+impl Chart<Message> for LogicChart<'_> {
+    type State = ();
+
+    fn draw<R: Renderer, F: Fn(&mut Frame)>(&self, renderer: &R, size: Size, f: F) -> Geometry {
+        renderer.draw_cache(self.cache, size, f)
+    }
+
+    fn build_chart<DB: DrawingBackend>(&self, _state: &Self::State, mut builder: ChartBuilder<DB>) {
+        let (bold_line_style_color, light_line_style_color, axis_style_color, label_style_color) =
+            get_chart_style_colors(&self.theme);
+
+        // Lane 0 is on the top, the Y axis goes from the lane count to 0
+        let lane_count = self.lanes.len().max(1) as f64;
+        let (start, end) = self.x_range;
+        let mut chart = builder
+            .x_label_area_size(25)
+            // Same horizontal layout as the chart panes, so the samples are aligned
+            .y_label_area_size(CHART_Y_LABEL_AREA_SIZE)
+            .margin_left(CHART_MARGIN)
+            .margin_right(CHART_MARGIN)
+            .margin_top(5)
+            .build_cartesian_2d(start..end.max(start + 1.0), 0.0..lane_count)
+            .expect("failed to build logic chart");
+
+        chart
+            .configure_mesh()
+            .bold_line_style(bold_line_style_color)
+            .light_line_style(light_line_style_color)
+            .axis_style(ShapeStyle::from(axis_style_color).stroke_width(1))
+            .disable_y_mesh()
+            .y_labels(0)
+            .x_labels(10)
+            .x_label_style(("sans-serif", 15).into_font().color(&label_style_color))
+            .x_label_formatter(&|x: &f64| format!("{:.0}", x))
+            .draw()
+            .expect("failed to draw logic mesh");
+
+        let (plot_width, _) = chart.plotting_area().dim_in_pixel();
+        let samples_per_pixel = (end - start).max(1.0) / plot_width.max(1) as f64;
+        for (lane_index, lane) in self.lanes.iter().enumerate() {
+            let color = Palette99::pick(lane_index).to_rgba();
+            let low = lane_count - lane_index as f64 - 0.8;
+            let high = lane_count - lane_index as f64 - 0.2;
+
+            if lane.width == 1 {
+                // Digital trace stepping between the low and high level
+                let mut points: Vec<(f64, f64)> = Vec::with_capacity(lane.segments.len() * 2);
+                for (first, last, value) in lane.segments.iter() {
+                    let level = if *value == 0 { low } else { high };
+                    points.push((*first, level));
+                    points.push((*last, level));
+                }
+                chart
+                    .draw_series(LineSeries::new(points, ShapeStyle::from(color).stroke_width(2)))
+                    .expect("failed to draw logic trace");
+            } else {
+                // Bus with one box per value and the decoded label when there is enough space
+                chart
+                    .draw_series(lane.segments.iter().map(|(first, last, _)| {
+                        Rectangle::new([(*first, low), (*last, high)], ShapeStyle::from(color).stroke_width(1))
+                    }))
+                    .expect("failed to draw logic bus");
+                chart
+                    .draw_series(
+                        lane.segments
+                            .iter()
+                            .filter(|(first, last, _)| (last - first) / samples_per_pixel >= LOGIC_LABEL_MIN_WIDTH)
+                            .map(|(first, _, value)| {
+                                Text::new(
+                                    lane.get_label(*value),
+                                    (first + 3.0 * samples_per_pixel, high - 0.1),
+                                    ("sans-serif", 13).into_font().color(&label_style_color),
+                                )
+                            }),
+                    )
+                    .expect("failed to draw logic bus labels");
+            }
+
+            // Name of the lane at the left edge of the plotting area
+            chart
+                .draw_series(std::iter::once(Text::new(
+                    lane.name.clone(),
+                    (start, lane_count - lane_index as f64),
+                    ("sans-serif", 12).into_font().color(&label_style_color.mix(0.7)),
+                )))
+                .expect("failed to draw logic lane name");
+        }
+    }
+}
+
+impl<'a> LogicChart<'a> {
+    pub fn view(self) -> Element<'a, Message> {
+        let height = self.lanes.len() as f32 * LOGIC_LANE_HEIGHT + LOGIC_CHART_BORDER_HEIGHT;
+        ChartWidget::new(self)
+            .width(Length::Fill)
+            .height(Length::Fixed(height))
+            .into()
+    }
+}
@@ -0,0 +1,306 @@
+/*
+
+MIT License
+
+Copyright (c) 2024 Petr Zakopal, Deparment of Electric Drives and Traction, CTU FEE
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+/*----------------------------------------------------------------------------*/
+
+use iced::{
+    widget::canvas::{Cache, Frame, Geometry},
+    Element, Size, Theme,
+};
+use plotters::{
+    element::{Circle, Rectangle, Text},
+    series::LineSeries,
+    style::{Color, FontTransform, IntoFont, Palette, Palette99, RGBColor, ShapeStyle},
+};
+use plotters_iced::{Chart, ChartBuilder, ChartWidget, DrawingBackend, Renderer};
+
+use super::{
+    decimation::{decimate, DecimationMode, TracePoint},
+    format_axis_value, get_chart_style_colors, CHART_MARGIN, CHART_X_LABEL_AREA_SIZE,
+    CHART_Y_LABEL_AREA_SIZE,
+};
+use crate::{
+    spectrum::{MagnitudeScale, Spectrogram, Spectrum, WindowFunction},
+    Message,
+};
+
+// Settings of the spectrum desktop selected by user
+#[derive(Debug, Clone, Default)]
+pub struct SpectrumSettings {
+    pub channel: Option<String>,        // Variable ID of the analysed channel
+    pub window: WindowFunction,         // Window applied before the FFT
+    pub scale: MagnitudeScale,          // Linear or dB magnitude
+    pub sample_rate: Option<f64>,       // Sample rate inserted by user, None takes the rate from
+                                        // the receive times of the frames
+    pub sample_rate_string: String,     // String format of the sample rate inserted by user
+    pub fundamental: Option<f64>,       // Fundamental frequency for the THD, None takes the
+                                        // highest peak
+    pub fundamental_string: String,     // String format of the fundamental inserted by user
+}
+
+// Range of the spectrogram colour map below the highest magnitude, weaker cells have the
+// lowest colour
+pub const SPECTROGRAM_DYNAMIC_RANGE_DB: f64 = 100.0;
+
+// Colour stops of the spectrogram colour map from the weakest to the strongest magnitude
+const COLORMAP_STOPS: [(u8, u8, u8); 5] = [
+    (13, 8, 135),
+    (126, 3, 168),
+    (204, 71, 120),
+    (248, 149, 64),
+    (240, 249, 33),
+];
+
+// Colour of the normalised magnitude 0.0..=1.0 interpolated between the colour stops
+pub fn get_colormap_color(value: f64) -> RGBColor {
+    let position = value.clamp(0.0, 1.0) * (COLORMAP_STOPS.len() - 1) as f64;
+    let index = (position.floor() as usize).min(COLORMAP_STOPS.len() - 2);
+    let fraction = position - index as f64;
+    let (low, high) = (COLORMAP_STOPS[index], COLORMAP_STOPS[index + 1]);
+    let mix = |low: u8, high: u8| (low as f64 + (high as f64 - low as f64) * fraction).round() as u8;
+    RGBColor(mix(low.0, high.0), mix(low.1, high.1), mix(low.2, high.2))
+}
+
+// Settings of the spectrogram desktop selected by user
+#[derive(Debug, Clone)]
+pub struct SpectrogramSettings {
+    pub channel: Option<String>,        // Variable ID of the analysed channel
+    pub window: WindowFunction,         // Window applied to every segment
+    pub segment_length: usize,          // Number of samples of one segment
+    pub sample_rate: Option<f64>,       // Sample rate inserted by user, None takes the rate from
+                                        // the receive times of the frames
+    pub sample_rate_string: String,     // String format of the sample rate inserted by user
+}
+
+impl Default for SpectrogramSettings {
+    fn default() -> Self {
+        Self {
+            channel: None,
+            window: WindowFunction::default(),
+            segment_length: 256,
+            sample_rate: None,
+            sample_rate_string: String::new(),
+        }
+    }
+}
+
+// Colour-mapped chart of the spectrogram, X axis are the samples of the store as in the default
+// chart, Y axis is the frequency
+pub struct SpectrogramChart<'a> {
+    pub spectrogram: &'a Spectrogram,
+    pub theme: Theme,
+    pub cache: &'a Cache,               // Cache cleared only when the spectrogram is recomputed
+}
+
+impl Chart<Message> for SpectrogramChart<'_> {
+    type State = ();
+
+    fn draw<R: Renderer, F: Fn(&mut Frame)>(&self, renderer: &R, size: Size, f: F) -> Geometry {
+        renderer.draw_cache(self.cache, size, f)
+    }
+
+    fn build_chart<DB: DrawingBackend>(&self, _state: &Self::State, mut builder: ChartBuilder<DB>) {
+        let (bold_line_style_color, light_line_style_color, axis_style_color, label_style_color) =
+            get_chart_style_colors(&self.theme);
+
+        let (start, end) = self.spectrogram.get_sample_range();
+        let end = end.max(start + 1.0);
+        let max_frequency = self.spectrogram.get_max_frequency().max(f64::MIN_POSITIVE);
+
+        let mut chart = builder
+            .x_label_area_size(CHART_X_LABEL_AREA_SIZE)
+            .y_label_area_size(CHART_Y_LABEL_AREA_SIZE)
+            .margin(CHART_MARGIN)
+            .build_cartesian_2d(start..end, 0.0..max_frequency)
+            .expect("failed to build spectrogram chart");
+
+        // Cells are drawn before the mesh, so the grid stays visible
+        let max_magnitude = self.spectrogram.get_min_max().map_or(0.0, |(_, max)| max);
+        let min_magnitude = max_magnitude - SPECTROGRAM_DYNAMIC_RANGE_DB;
+        let half_hop = self.spectrogram.hop as f64 / 2.0;
+        let segment_half = self.spectrogram.segment_length as f64 / 2.0;
+        for (column_index, column) in self.spectrogram.columns.iter().enumerate() {
+            // Column is centred at the middle of its segment
+            let center = start + segment_half + (column_index * self.spectrogram.hop) as f64;
+            let (left, right) = (
+                (center - half_hop).max(start),
+                (center + half_hop).min(end),
+            );
+            let row_height = max_frequency / column.len().max(1) as f64;
+            chart
+                .draw_series(column.iter().enumerate().map(|(row, magnitude)| {
+                    let color = get_colormap_color(
+                        (magnitude - min_magnitude) / SPECTROGRAM_DYNAMIC_RANGE_DB,
+                    );
+                    Rectangle::new(
+                        [
+                            (left, row as f64 * row_height),
+                            (right, (row + 1) as f64 * row_height),
+                        ],
+                        color.filled(),
+                    )
+                }))
+                .expect("failed to draw spectrogram column");
+        }
+
+        chart
+            .configure_mesh()
+            .bold_line_style(bold_line_style_color)
+            .light_line_style(light_line_style_color)
+            .axis_style(ShapeStyle::from(axis_style_color).stroke_width(1))
+            .y_labels(10)
+            .x_labels(10)
+            .y_label_style(
+                ("sans-serif", 15)
+                    .into_font()
+                    .color(&label_style_color)
+                    .transform(FontTransform::Rotate90),
+            )
+            .y_label_formatter(&|y: &f64| format_axis_value(*y))
+            .x_label_style(("sans-serif", 15).into_font().color(&label_style_color))
+            .x_label_formatter(&|x: &f64| format!("{}", *x as u128))
+            .draw()
+            .expect("failed to draw spectrogram mesh");
+    }
+}
+
+impl<'a> SpectrogramChart<'a> {
+    pub fn view(self) -> Element<'a, Message> {
+        ChartWidget::new(self).into()
+    }
+}
+
+// Chart of the magnitude spectrum with the marked peaks
+pub struct SpectrumChart<'a> {
+    pub spectrum: &'a Spectrum,
+    pub peaks: Vec<(f64, f64)>,         // Marked peaks as (frequency, magnitude)
+    pub scale: MagnitudeScale,
+    pub theme: Theme,
+    pub cache: &'a Cache,               // Cache cleared only when the spectrum is recomputed
+}
+
+impl Chart<Message> for SpectrumChart<'_> {
+    type State = ();
+
+    fn draw<R: Renderer, F: Fn(&mut Frame)>(&self, renderer: &R, size: Size, f: F) -> Geometry {
+        renderer.draw_cache(self.cache, size, f)
+    }
+
+    fn build_chart<DB: DrawingBackend>(&self, _state: &Self::State, mut builder: ChartBuilder<DB>) {
+        let (bold_line_style_color, light_line_style_color, axis_style_color, label_style_color) =
+            get_chart_style_colors(&self.theme);
+
+        let max_frequency = self
+            .spectrum
+            .frequencies
+            .last()
+            .copied()
+            .filter(|frequency| *frequency > 0.0)
+            .unwrap_or(1.0);
+        let scaled: Vec<TracePoint> = self
+            .spectrum
+            .frequencies
+            .iter()
+            .zip(self.spectrum.magnitudes.iter())
+            .map(|(&x, &magnitude)| TracePoint {
+                x,
+                y: self.scale.apply(magnitude),
+            })
+            .collect();
+        let (min_y, max_y) = scaled
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), point| {
+                (min.min(point.y), max.max(point.y))
+            });
+        let (min_y, max_y) = match self.scale {
+            _ if min_y > max_y => (0.0, 1.0),
+            // The noise floor of the dB scale is limited, so the peaks stay readable
+            MagnitudeScale::Decibel => ((max_y - 140.0).max(min_y), max_y + 10.0),
+            MagnitudeScale::Linear => (0.0, max_y.max(f64::MIN_POSITIVE) * 1.1),
+        };
+
+        let mut chart = builder
+            .x_label_area_size(CHART_X_LABEL_AREA_SIZE)
+            .y_label_area_size(CHART_Y_LABEL_AREA_SIZE)
+            .margin(CHART_MARGIN)
+            .build_cartesian_2d(0.0..max_frequency, min_y..max_y)
+            .expect("failed to build spectrum chart");
+
+        chart
+            .configure_mesh()
+            .bold_line_style(bold_line_style_color)
+            .light_line_style(light_line_style_color)
+            .axis_style(ShapeStyle::from(axis_style_color).stroke_width(1))
+            .y_labels(10)
+            .x_labels(10)
+            .y_label_style(
+                ("sans-serif", 15)
+                    .into_font()
+                    .color(&label_style_color)
+                    .transform(FontTransform::Rotate90),
+            )
+            .y_label_formatter(&|y: &f64| format_axis_value(*y))
+            .x_label_style(("sans-serif", 15).into_font().color(&label_style_color))
+            .x_label_formatter(&|x: &f64| format_axis_value(*x))
+            .x_desc("Frequency")
+            .draw()
+            .expect("failed to draw spectrum mesh");
+
+        let (plot_width, _) = chart.plotting_area().dim_in_pixel();
+        let decimated_points = decimate(&scaled, plot_width as usize, DecimationMode::MinMax);
+        chart
+            .draw_series(LineSeries::new(
+                decimated_points.iter().map(|point| (point.x, point.y)),
+                ShapeStyle::from(Palette99::pick(1).to_rgba()).stroke_width(1),
+            ))
+            .expect("failed to draw spectrum");
+
+        // Marked peaks with their frequency
+        let peak_color = Palette99::pick(2).to_rgba();
+        for (frequency, magnitude) in self.peaks.iter() {
+            let y = self.scale.apply(*magnitude).clamp(min_y, max_y);
+            chart
+                .draw_series(std::iter::once(Circle::new(
+                    (*frequency, y),
+                    4,
+                    ShapeStyle::from(peak_color).filled(),
+                )))
+                .expect("failed to draw spectrum peak");
+            chart
+                .draw_series(std::iter::once(Text::new(
+                    format_axis_value(*frequency),
+                    (*frequency, y),
+                    ("sans-serif", 14).into_font().color(&label_style_color),
+                )))
+                .expect("failed to draw spectrum peak label");
+        }
+    }
+}
+
+impl<'a> SpectrumChart<'a> {
+    pub fn view(self) -> Element<'a, Message> {
+        ChartWidget::new(self).into()
+    }
+}
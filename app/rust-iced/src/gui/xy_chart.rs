@@ -0,0 +1,236 @@
+/*
+
+MIT License
+
+Copyright (c) 2024 Petr Zakopal, Deparment of Electric Drives and Traction, CTU FEE
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+/*----------------------------------------------------------------------------*/
+
+use iced::{
+    widget::canvas::{Cache, Frame, Geometry},
+    Element, Size, Theme,
+};
+use plotters::{
+    series::LineSeries,
+    style::{Color, FontTransform, IntoFont, Palette, Palette99, ShapeStyle},
+};
+use plotters_iced::{Chart, ChartBuilder, ChartWidget, DrawingBackend, Renderer};
+
+use super::{
+    format_axis_value, get_chart_style_colors, CHART_MARGIN, CHART_X_LABEL_AREA_SIZE,
+    CHART_Y_LABEL_AREA_SIZE,
+};
+use crate::Message;
+
+// Largest number of points drawn in the XY chart, longer persistence takes every n-th sample
+pub const XY_MAX_POINTS: usize = 20_000;
+// Number of parts of the trajectory drawn with the increasing opacity when fading
+const XY_FADE_STEPS: usize = 10;
+
+// Settings of the XY desktop selected by user
+#[derive(Debug, Clone)]
+pub struct XySettings {
+    pub x_channel: Option<String>,      // Variable ID of the channel on the X axis
+    pub y_channel: Option<String>,      // Variable ID of the channel on the Y axis
+    pub persistence: usize,             // Number of the newest samples of the chart range drawn
+    pub persistence_string: String,     // String format of the persistence inserted by user
+    pub fade: bool,                     // Older points are drawn more transparent
+    pub equal_aspect: bool,             // Same units per pixel on both axes, circles stay round
+    pub space_vector: bool,             // Newest point drawn as a vector from the origin, e.g. the
+                                        // current vector from the alpha and beta channels
+    pub angle_channel: Option<String>,  // Variable ID of the rotor angle in radians drawn with the
+                                        // space vector
+}
+
+impl Default for XySettings {
+    fn default() -> Self {
+        Self {
+            x_channel: None,
+            y_channel: None,
+            persistence: 2000,
+            persistence_string: "2000".to_string(),
+            fade: true,
+            equal_aspect: true,
+            space_vector: false,
+            angle_channel: None,
+        }
+    }
+}
+
+// Chart of one channel against another, points are in the order of the samples
+pub struct XyChart<'a> {
+    pub points: &'a [(f64, f64)],
+    pub x_label: String,
+    pub y_label: String,
+    pub fade: bool,
+    pub equal_aspect: bool,
+    pub space_vector: Option<(f64, f64)>, // Newest point drawn as a vector from the origin
+    pub rotor_angle: Option<f64>,       // Rotor angle drawn as a line of the vector length
+    pub theme: Theme,
+    pub cache: &'a Cache,               // Cache cleared only when the points are refreshed
+}
+
+impl XyChart<'_> {
+    // Range of the points with a margin, ranges of zero span are widened, the origin is included
+    // with the space vector
+    fn get_ranges(&self) -> ((f64, f64), (f64, f64)) {
+        let origin = self.space_vector.map(|_| (0.0, 0.0));
+        let (x_range, y_range) = self.points.iter().chain(origin.iter()).fold(
+            (
+                (f64::INFINITY, f64::NEG_INFINITY),
+                (f64::INFINITY, f64::NEG_INFINITY),
+            ),
+            |(x_range, y_range), (x, y)| {
+                (
+                    (x_range.0.min(*x), x_range.1.max(*x)),
+                    (y_range.0.min(*y), y_range.1.max(*y)),
+                )
+            },
+        );
+        let pad = |(min, max): (f64, f64)| {
+            if min > max {
+                (-1.0, 1.0)
+            } else if min == max {
+                (min - 1.0, max + 1.0)
+            } else {
+                let margin = (max - min) * 0.05;
+                (min - margin, max + margin)
+            }
+        };
+        (pad(x_range), pad(y_range))
+    }
+}
+
+// Widening the ranges around their centres, so both axes have the same units per pixel
+fn get_equal_aspect_ranges(
+    x_range: (f64, f64),
+    y_range: (f64, f64),
+    width: u32,
+    height: u32,
+) -> ((f64, f64), (f64, f64)) {
+    let (width, height) = (width.max(1) as f64, height.max(1) as f64);
+    let units_per_pixel = ((x_range.1 - x_range.0) / width).max((y_range.1 - y_range.0) / height);
+    let widen = |(min, max): (f64, f64), pixels: f64| {
+        let center = (min + max) / 2.0;
+        let half_span = units_per_pixel * pixels / 2.0;
+        (center - half_span, center + half_span)
+    };
+    (widen(x_range, width), widen(y_range, height))
+}
+
+impl Chart<Message> for XyChart<'_> {
+    type State = ();
+
+    fn draw<R: Renderer, F: Fn(&mut Frame)>(&self, renderer: &R, size: Size, f: F) -> Geometry {
+        renderer.draw_cache(self.cache, size, f)
+    }
+
+    fn build_chart<DB: DrawingBackend>(&self, _state: &Self::State, mut builder: ChartBuilder<DB>) {
+        let (bold_line_style_color, light_line_style_color, axis_style_color, label_style_color) =
+            get_chart_style_colors(&self.theme);
+
+        builder
+            .x_label_area_size(CHART_X_LABEL_AREA_SIZE)
+            .y_label_area_size(CHART_Y_LABEL_AREA_SIZE)
+            .margin(CHART_MARGIN);
+        let (mut x_range, mut y_range) = self.get_ranges();
+        if self.equal_aspect {
+            // Size of the plotting area does not depend on the ranges, so it is taken from a
+            // chart which is not drawn
+            let (width, height) = builder
+                .build_cartesian_2d(x_range.0..x_range.1, y_range.0..y_range.1)
+                .expect("failed to build XY chart")
+                .plotting_area()
+                .dim_in_pixel();
+            (x_range, y_range) = get_equal_aspect_ranges(x_range, y_range, width, height);
+        }
+
+        let mut chart = builder
+            .build_cartesian_2d(x_range.0..x_range.1, y_range.0..y_range.1)
+            .expect("failed to build XY chart");
+
+        chart
+            .configure_mesh()
+            .bold_line_style(bold_line_style_color)
+            .light_line_style(light_line_style_color)
+            .axis_style(ShapeStyle::from(axis_style_color).stroke_width(1))
+            .y_labels(10)
+            .x_labels(10)
+            .y_label_style(
+                ("sans-serif", 15)
+                    .into_font()
+                    .color(&label_style_color)
+                    .transform(FontTransform::Rotate90),
+            )
+            .y_label_formatter(&|y: &f64| format_axis_value(*y))
+            .x_label_style(("sans-serif", 15).into_font().color(&label_style_color))
+            .x_label_formatter(&|x: &f64| format_axis_value(*x))
+            .x_desc(self.x_label.as_str())
+            .y_desc(self.y_label.as_str())
+            .draw()
+            .expect("failed to draw XY mesh");
+
+        // Trajectory is split into parts, the newest part is opaque, the parts overlap by one
+        // point, so the trajectory stays connected
+        let color = Palette99::pick(1);
+        let steps = if self.fade { XY_FADE_STEPS } else { 1 };
+        let part_length = self.points.len().div_ceil(steps).max(1);
+        // Short trajectories have fewer parts than steps, the newest part must still be opaque
+        let part_count = self.points.len().div_ceil(part_length);
+        for (part, points) in self.points.chunks(part_length).enumerate() {
+            let first = (part * part_length).saturating_sub(1);
+            let last = part * part_length + points.len();
+            let opacity = (part + 1) as f64 / part_count as f64;
+            chart
+                .draw_series(LineSeries::new(
+                    self.points[first..last].iter().copied(),
+                    ShapeStyle::from(color.mix(opacity)).stroke_width(1),
+                ))
+                .expect("failed to draw XY trajectory");
+        }
+
+        // Space vector from the origin with the rotor angle, so the rotation of the vector with
+        // the rotor can be seen
+        if let Some((x, y)) = self.space_vector {
+            let vector_style = ShapeStyle::from(Palette99::pick(2).to_rgba()).stroke_width(3);
+            chart
+                .draw_series(LineSeries::new([(0.0, 0.0), (x, y)], vector_style))
+                .expect("failed to draw space vector");
+            if let Some(angle) = self.rotor_angle {
+                let length = x.hypot(y);
+                let (sin, cos) = angle.sin_cos();
+                chart
+                    .draw_series(LineSeries::new(
+                        [(0.0, 0.0), (length * cos, length * sin)],
+                        ShapeStyle::from(label_style_color.mix(0.6)).stroke_width(1),
+                    ))
+                    .expect("failed to draw rotor angle");
+            }
+        }
+    }
+}
+
+impl<'a> XyChart<'a> {
+    pub fn view(self) -> Element<'a, Message> {
+        ChartWidget::new(self).into()
+    }
+}
@@ -0,0 +1,177 @@
+/*
+
+MIT License
+
+Copyright (c) 2024 Petr Zakopal, Deparment of Electric Drives and Traction, CTU FEE
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+/*----------------------------------------------------------------------------*/
+
+use crate::statistics::RunningStatistics;
+
+// Largest number of samples put into the histogram, longer ranges take every n-th sample
+pub const HISTOGRAM_MAX_SAMPLES: usize = 1 << 20;
+// Number of bins of the histogram by default
+pub const HISTOGRAM_DEFAULT_BINS: usize = 100;
+
+// Distribution of the scaled values of one channel
+#[derive(Debug, Clone, Default)]
+pub struct Histogram {
+    pub range: (f64, f64),              // Range of the bins, values outside are counted separately
+    pub counts: Vec<u64>,               // Number of samples in every bin
+    pub outside: u64,                   // Number of samples outside the range
+    pub samples: u64,                   // Number of samples put into the histogram
+    pub statistics: RunningStatistics,  // Statistics of all samples of the range, not only of the
+                                        // samples put into the histogram
+    pub stuck_bits: Vec<(u32, bool)>,   // Bits of the fixed point word with the same value in all
+                                        // samples as (bit, value)
+}
+
+impl Histogram {
+    // Histogram of the raw samples scaled by the scale, the range is the min and max of the
+    // scaled samples when not selected, NaN samples (channel missing in a frame) are skipped
+    // the stuck bits are searched in the fixed point words received with the samples
+    pub fn compute(
+        raw_samples: &[f64],
+        words: &[u32],
+        scale: impl Fn(f64) -> f64,
+        bins: usize,
+        range: Option<(f64, f64)>,
+        statistics: RunningStatistics,
+    ) -> Self {
+        let samples: Vec<f64> = raw_samples
+            .iter()
+            .filter(|sample| !sample.is_nan())
+            .map(|sample| scale(*sample))
+            .collect();
+        if samples.is_empty() || bins == 0 {
+            return Self {
+                statistics,
+                ..Default::default()
+            };
+        }
+
+        let range = range.unwrap_or_else(|| {
+            samples
+                .iter()
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), sample| {
+                    (min.min(*sample), max.max(*sample))
+                })
+        });
+        // Constant signal is put into one bin in the middle
+        let range = if range.0 < range.1 {
+            range
+        } else {
+            (range.0 - 0.5, range.0 + 0.5)
+        };
+
+        let mut counts = vec![0; bins];
+        let mut outside = 0;
+        let bin_width = (range.1 - range.0) / bins as f64;
+        for sample in samples.iter() {
+            if *sample < range.0 || *sample > range.1 {
+                outside += 1;
+                continue;
+            }
+            // Max of the range belongs to the last bin
+            let bin = (((sample - range.0) / bin_width) as usize).min(bins - 1);
+            counts[bin] += 1;
+        }
+
+        Self {
+            range,
+            counts,
+            outside,
+            samples: samples.len() as u64,
+            statistics,
+            stuck_bits: get_stuck_bits(words),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    pub fn get_bin_width(&self) -> f64 {
+        (self.range.1 - self.range.0) / self.counts.len().max(1) as f64
+    }
+}
+
+// Bits of the fixed point words which never change while the other bits do, e.g. a stuck or
+// not connected bit of an ADC, nothing is reported for a constant signal
+fn get_stuck_bits(words: &[u32]) -> Vec<(u32, bool)> {
+    let (mut ones, mut zeros) = (0u32, 0u32);
+    for &word in words.iter() {
+        ones |= word;
+        zeros |= !word;
+    }
+    let changing = ones & zeros;
+    if changing == 0 {
+        return Vec::new();
+    }
+    // Only the bits under the highest changing bit, the upper bits are the sign extension
+    let highest_changing_bit = 31 - changing.leading_zeros();
+    (0..highest_changing_bit)
+        .filter(|bit| changing & (1 << bit) == 0)
+        .map(|bit| (bit, ones & (1 << bit) != 0))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stuck_bits_under_sign_extension() {
+        // Values around zero with the LSB stuck at 0 and the bit 1 stuck at 1, the upper bits of
+        // the negative values are the sign extension and are not reported
+        let words: Vec<u32> = [-6i32, 2, -10, 14, 6].iter().map(|value| *value as u32).collect();
+        assert_eq!(get_stuck_bits(&words), [(0, false), (1, true)]);
+    }
+
+    #[test]
+    fn stuck_bits_of_positive_words() {
+        // Bits above the highest changing bit are not reported
+        assert_eq!(get_stuck_bits(&[0b0010, 0b0110, 0b1010]), [(0, false), (1, true)]);
+        assert_eq!(get_stuck_bits(&[0b01, 0b10]), []);
+        // Constant signal and no words
+        assert_eq!(get_stuck_bits(&[5, 5, 5]), []);
+        assert_eq!(get_stuck_bits(&[]), []);
+    }
+
+    #[test]
+    fn counts_and_outside_samples() {
+        let samples = [0.0, 1.0, 2.0, 3.0, 4.0, f64::NAN, 10.0];
+        let histogram = Histogram::compute(
+            &samples,
+            &[],
+            |sample| sample * 2.0,
+            4,
+            Some((0.0, 8.0)),
+            RunningStatistics::default(),
+        );
+        // Max of the range belongs to the last bin
+        assert_eq!(histogram.counts, [1, 1, 1, 2]);
+        assert_eq!(histogram.outside, 1);
+        assert_eq!(histogram.samples, 6);
+        assert_eq!(histogram.get_bin_width(), 2.0);
+    }
+}
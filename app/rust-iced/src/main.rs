@@ -26,34 +26,150 @@ SOFTWARE.
 /*----------------------------------------------------------------------------*/
 
 use std::cell::RefCell;
-use std::collections::{HashMap, VecDeque};
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::prelude::*;
 use std::{u128, usize};
 
 use chrono::Local;
 use com_core::{BroadcastBFValues, BroadcastValues, SerialPortConnectInfo, WatchChannelValues};
+use com_core::message::take_frame_words;
 use iced::widget::{button, Button, Column, Container, PickList, Row, Text, TextInput};
 use iced::{executor, theme, Alignment, Application, Command, Element, Length, Settings, Theme};
-use native_dialog::MessageDialog;
+use native_dialog::{FileDialog, MessageDialog};
 use serialport::SerialPort;
 use sudo;
 use tokio::sync::broadcast;
 
 use plotters::prelude::*;
 use plotters_iced::{Chart, ChartBuilder, ChartWidget, DrawingBackend};
+pub mod alarms;
+pub mod bit_fields;
+pub mod channel_definition;
+pub mod channels;
 pub mod com_core;
+pub mod expression;
+pub mod filters;
 pub mod gui;
+pub mod histogram;
+pub mod markers;
+pub mod reference;
+pub mod signal_store;
+pub mod spectrum;
+pub mod statistics;
+pub mod step_response;
+pub mod transforms;
+pub mod trigger;
 pub mod utils;
-use std::time::{SystemTime, UNIX_EPOCH};
+pub mod watch;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 use gui::{*};
+use gui::decimation::DecimationMode;
+use gui::histogram_chart::{HistogramChart, HistogramSettings};
+use gui::logic_chart::LogicChart;
+use gui::xy_chart::{XyChart, XySettings, XY_MAX_POINTS};
+use gui::spectrum_chart::{
+    SpectrogramChart, SpectrogramSettings, SpectrumChart, SpectrumSettings,
+    SPECTROGRAM_DYNAMIC_RANGE_DB,
+};
+use channel_definition::ChannelDefinitionFile;
+use channels::{ChannelConfig, ChannelField, DeviceProfile};
+use clap::{Parser, Subcommand};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use signal_store::SignalStore;
+use histogram::{Histogram, HISTOGRAM_MAX_SAMPLES};
+use markers::MarkerList;
+use reference::{ReferenceAlignment, ReferenceSettings, ReferenceTrace, REFERENCE_MAX_POINTS};
+use spectrum::{
+    MagnitudeScale, Spectrogram, Spectrum, WindowFunction, MAX_FFT_LENGTH,
+    SPECTROGRAM_MAX_SAMPLES, SPECTROGRAM_SEGMENT_LENGTHS, SPECTRUM_PEAK_COUNT,
+};
+use statistics::RunningStatistics;
+use step_response::{StepResponse, StepResponseSettings};
+use watch::WatchTable;
+use alarms::{AlarmAction, AlarmCondition, AlarmEvent, AlarmField, AlarmLog, AlarmRule, AlarmSeverity};
+use bit_fields::{BitField, BitFieldField, LogicLane};
+use filters::{ChannelFilter, FilterField, FilterKind};
+use transforms::{DriveTransform, TransformKind};
+use trigger::{Trigger, TriggerArming, TriggerEdge};
 
 // Width of the desktop selection column for interface
 // default/debugging/debugging free etc.
 const DESKTOP_SELECTION_COLUMN_WIDTH: f32 = 120.0;
 
+// Maximum number of min/max buckets taken from the signal store for one chart, longer ranges are
+// reduced using the min/max pyramid of the store
+const CHART_MAX_BUCKETS: usize = 2000;
+// Maximum number of raw samples taken for the panes decimated by the Off or LTTB mode, so the
+// decimation works with the raw samples, longer ranges are reduced by the min/max buckets
+const CHART_MAX_RAW_SAMPLES: usize = 200_000;
+
+
+// Command line arguments of the application, without a subcommand the GUI is started
+#[derive(Parser)]
+#[command(version, about = "Real-Time Monitor of the signals from the FPGA")]
+struct Cli {
+    /// Channel definition file (JSON) with names, Q formats, units, scales and panes of variables
+    #[arg(short, long)]
+    channels: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Option<CliCommand>,
+}
+
+#[derive(Subcommand)]
+enum CliCommand {
+    /// Generate the monitConnector assignments and NUMBER_OF_VARIABLES for monit.v
+    Codegen {
+        /// Channel definition file (JSON)
+        definition: PathBuf,
+        /// Output file, the Verilog is printed to the stdout when not set
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+// Generating the Verilog from the channel definition file without starting the GUI
+fn run_codegen(definition: &Path, output: Option<&PathBuf>) {
+    let channel_definition = match ChannelDefinitionFile::load(definition) {
+        Ok(channel_definition) => channel_definition,
+        Err(e) => {
+            eprintln!("Error loading the channel definition file {:#?}", e);
+            std::process::exit(1);
+        }
+    };
+    let verilog = channel_definition.generate_verilog();
+    match output {
+        Some(output) => {
+            if let Err(e) = std::fs::write(output, verilog) {
+                eprintln!("Error writing the generated Verilog {:#?}", e);
+                std::process::exit(1);
+            }
+        }
+        None => print!("{}", verilog),
+    }
+}
 
 fn main() -> iced::Result {
+    let cli = Cli::parse();
+    if let Some(CliCommand::Codegen { definition, output }) = &cli.command {
+        run_codegen(definition, output.as_ref());
+        return Ok(());
+    }
+
+    // Channel definition is loaded before escalating, so the errors are visible in the terminal
+    let channel_definition = match &cli.channels {
+        Some(path) => match ChannelDefinitionFile::load(path) {
+            Ok(channel_definition) => Some(channel_definition),
+            Err(e) => {
+                eprintln!("Error loading the channel definition file {:#?}", e);
+                std::process::exit(1);
+            }
+        },
+        None => None,
+    };
+
     sudo::escalate_if_needed().expect("Could not run as a sudo.");
     //let mut file_test = File::create("/home/petr/test.txt").unwrap();
     //file_test.write_all(b"Hello there").expect("Error");
@@ -64,12 +180,14 @@ fn main() -> iced::Result {
     let app_flags = AppFlags {
         broadcast_sender: sender,
         broadcast_receiver: receiver,
+        channel_definition,
     };
     Rtm::run(Settings::with_flags(app_flags))
 }
 pub struct AppFlags {
     broadcast_receiver: broadcast::Receiver<serde_json::Value>,
     broadcast_sender: broadcast::Sender<serde_json::Value>,
+    channel_definition: Option<ChannelDefinitionFile>, // Channel map loaded from the command line
 }
 pub struct Rtm {
     broadcast_data_bf_channel: BroadcastBFValues,                       // Backend to Frontend Channels
@@ -77,10 +195,50 @@ pub struct Rtm {
     serial_port_handle: Option<Box<dyn SerialPort>>,                    // Handle for serial port connection object
     serial_control_on_off_channel: Option<WatchChannelValues>,          // Tokio watch channels for enabling and disabling the loop for fetching data in the backend
     serial_data_fetch_handle: Option<tokio::task::JoinHandle<()>>,      // Tokio thread handle for fetching serial data in the backend
-    data: serde_json::Value,                                            // Data received from backend in a subscription which is passed to the new_data_points
-    new_data_points: (VecDeque<u128>, HashMap<u128, VecDeque<f64>>),    // Data received from backend in a tuple (x-samples, HashMap with data from variables)
+    data: serde_json::Value,                                            // Data received from backend in a subscription which is passed to the signal_store
+    signal_store: SignalStore,                                          // All received data, older samples are kept in memory mapped files on the disk
+    history_view: HistoryView,                                          // Scrolling back through the history in the default mode
+    chart_panes: Vec<ChartPane>,                                        // Data, caches and Y axes of the stacked chart panes, refreshed only on the render tick
+    render_settings: RenderSettings,                                    // FPS at which the chart is redrawn and the measured effective FPS
+    chart_settings: ChartSettings,                                      // Settings of the chart view shared by all panes - markers, statistics
+    selected_pane: usize,                                               // Chart pane edited by the Y axis, decimation and cursor controls
+    chart_zoom: ChartZoom,                                              // Ranges selected by zooming and panning, X shared by all panes
+    chart_zoom_history: Vec<ChartZoom>,                                 // Undo stack of the chart zooms
+    trigger: Trigger,                                                   // Software oscilloscope trigger used in the trigger mode
+    chart_statistics: Vec<(String, RunningStatistics)>,                 // Labels and scaled statistics of the visible channels, refreshed only on the render tick
+    step_settings: StepResponseSettings,                                // Response and reference channels of the step response analysis
+    step_response: Option<StepResponse>,                                // Result of the last step response analysis annotated in the chart
+    step_sample_rate: Option<f64>,                                      // Sample rate of the analysed region estimated from the receive times
+    logic_lanes: Vec<LogicLane>,                                        // Bit fields of the status words in the chart range, refreshed only on the render tick
+    logic_cache: iced::widget::canvas::Cache,                           // Cache of the logic analyzer lanes cleared when the lanes are refreshed
+    reference: Option<ReferenceTrace>,                                  // Pinned snapshot or loaded capture drawn faded behind the live traces
+    reference_settings: ReferenceSettings,                              // Alignment, offset and difference channel of the reference
+    alarm_log: AlarmLog,                                                // Timestamped events of the limit alarms
+    markers: MarkerList,                                                // Notes on the timeline of the capture
+    watch_table: WatchTable,                                            // Latest values, min, max, mean and update rates of all channels
+    spectrum_settings: SpectrumSettings,                                // Channel, window, scale and sample rate of the spectrum desktop
+    spectrum: Spectrum,                                                 // Spectrum of the selected channel, recomputed only on the render tick
+    spectrum_samples: usize,                                            // Number of samples from which the spectrum was computed
+    spectrum_sample_rate_measured: bool,                                // Sample rate estimated from the receive times, not inserted by user
+    spectrum_cache: iced::widget::canvas::Cache,                        // Cache of the spectrum chart cleared when the spectrum is recomputed
+    spectrogram_settings: SpectrogramSettings,                          // Channel, window, segment length and sample rate of the spectrogram desktop
+    spectrogram: Arc<Spectrogram>,                                      // Newest spectrogram computed by the worker
+    spectrogram_sample_rate_measured: bool,                             // Sample rate of the spectrogram estimated from the receive times
+    spectrogram_requested: bool,                                        // New data or settings are waiting for the next spectrogram
+    spectrogram_computing: bool,                                        // Worker is computing the spectrogram, only one runs at a time
+    spectrogram_cache: iced::widget::canvas::Cache,                     // Cache of the spectrogram chart cleared when a new spectrogram arrives
+    histogram_settings: HistogramSettings,                              // Channel, scope, bins and range of the histogram desktop
+    histogram: Histogram,                                               // Histogram of the selected channel, recomputed only on the render tick
+    histogram_cache: iced::widget::canvas::Cache,                       // Cache of the histogram chart cleared when the histogram is recomputed
+    xy_settings: XySettings,                                            // Channels, persistence and aspect of the XY desktop
+    xy_points: Vec<(f64, f64)>,                                         // Scaled points of the XY chart, refreshed only on the render tick
+    xy_rotor_angle: Option<f64>,                                        // Newest scaled rotor angle drawn with the space vector
+    xy_cache: iced::widget::canvas::Cache,                              // Cache of the XY chart cleared when the points are refreshed
+    device_profile: DeviceProfile,                                      // Names, units, colors and scaling of the channels of the connected device
+    saved_profiles: Vec<String>,                                        // Names of the device profiles saved on the disk
+    chart_hold: Option<(usize, usize)>,                                 // Range of the samples frozen in the chart, None displays live data
     display_mode: Option<String>,                                       // Running buffer or default mode selected value, will have to make a struct for it
-    display_mode_select_values: Vec<String>,                            // List of possible modes running | default | add more later
+    display_mode_select_values: Vec<String>,                            // List of possible modes running | default | trigger | add more later
     serial_ports_available: SerialPortData,                             // Struct with data about serial ports (baud rate, names, info)
     running_buffer: RunningBuffer,                                      // Running buffer settings - size in string and parsed size
     navigation_layout: NavigationLayout,                                // Desktops - default | debug | debug free | add more
@@ -103,7 +261,7 @@ pub enum Message {
     StopSerialDataFetch,                // Sending control signal via tokio watch channel to STOP the loop for fetching the data via serial port
     DropSerialDataFetch,                // Dropping the initialized thread from tokio spawn for backend fetching data with all checks
     ExternalDataReceived(serde_json::Value), // Message for handling received data from the backend via subscription to a broadcast channel, moving the dat to the frontend component
-    ClearFigure,                        // Clearing fetched data from the vectors in a HashMap in a new_data
+    ClearFigure,                        // Clearing fetched data from the signal store
     SetDisplayMode(String),             // For seting the display mode of chart and data, initially default | running buffer
     GetAvailableSerialPortData,         // Fetching the available serial ports in the gui to later
                                         // pass the information to a PickList to be able to select available ports
@@ -116,6 +274,858 @@ pub enum Message {
     StopOutputFile,                     // Stop saving the obtained data to the recentlyu created file
     InitializeRtm,                      // Chain of actions to initialize the control watch channels, serial port and spawn a backend thread for fetching the data
     ThemeChanged(Theme),                // Change the theme to selected theme
+    SetHistorySpan(String),             // Set the number of samples displayed when scrolling through the history
+    SetHistoryPosition(f64),            // Set the last displayed sample when scrolling through the history
+    FollowLiveHistory,                  // Stop scrolling through the history and display the newest data
+    RenderTick(Instant),                // Redrawing the chart at the selected FPS
+    SetRenderFps(String),               // Set the FPS at which the chart is redrawn
+    SetDecimationMode(DecimationMode),  // Set the method for reducing the traces to the pixel width of the chart
+    SetYScaleMode(YScaleMode),          // Set the method for obtaining the range of the Y axis
+    SetYManualMin(String),              // Set the manual min of the Y axis
+    SetYManualMax(String),              // Set the manual max of the Y axis
+    ResetZoom,                          // Return the chart axes to the automatic scaling
+    ChartViewportChanged(usize, ChartViewport), // Zoom or pan of a chart pane, saved to the zoom history
+    ChartPanMoved(usize, ChartViewport), // Continuing pan of a chart pane, not saved to the zoom history
+    ChartBoxSelecting(usize, Option<((f64, f64), (f64, f64))>), // Box zoom being selected in a chart pane
+    SetPaneCount(String),               // Set the number of stacked chart panes of the desktop
+    AssignChannelPane(String, String),  // Assign the channel (variable ID) to the chart pane
+    SelectPane(String),                 // Select the chart pane edited by the Y axis, decimation and cursor controls
+    UndoZoom,                           // Return to the previous viewport from the zoom history
+    ToggleMeasurementCursors,           // Show or hide the measurement cursors in the selected chart pane
+    MoveMeasurementCursor(usize, CursorHandle, f64), // Dragging of a measurement cursor of the pane to a new position
+    SetTriggerChannel(String),          // Set the variable ID of the trigger channel
+    SetTriggerEdge(TriggerEdge),        // Set the edge of the trigger channel crossing the level
+    SetTriggerLevel(String),            // Set the trigger level
+    SetTriggerPreSamples(String),       // Set the number of samples displayed before the trigger
+    SetTriggerPostSamples(String),      // Set the number of samples displayed after the trigger
+    SetTriggerArming(TriggerArming),    // Set the arming of the trigger auto | normal | single
+    ArmTrigger,                         // Arm the trigger again, e.g. after the single capture
+    SetChannelField(String, ChannelField, String), // Set a field of the channel (variable ID) in the channel table
+    SetChannelVisible(String, bool),    // Show or hide the channel (variable ID) in the charts
+    SetProfileName(String),             // Set the name of the device profile
+    SaveProfile,                        // Save the device profile to the profile directory
+    LoadProfile(String),                // Load the saved device profile
+    ToggleStatistics,                   // Show or hide the side panel with the channel statistics
+    ToggleMarkers,                      // Show or hide the side panel with the list of the markers
+    SetMarkerNote(String),              // Set the note of the next marker
+    AddLiveMarker,                      // Drop a marker at the sample being received, also by the M key
+    AddMarker,                          // Add a marker at the cursor X1, or in the middle of the displayed range
+    SetExistingMarkerNote(usize, String), // Change the note of the marker
+    SubmitMarkerNote,                   // Save the markers after the note of a marker was edited
+    RemoveMarker(usize),                // Remove the marker from the timeline
+    JumpToMarker(usize),                // Move the displayed range around the marker
+    ExportCsv,                          // Save the displayed range with the event column of the markers
+    CsvExported(String, Result<(), String>), // Displayed range written to the file by the worker
+    SetStatisticsScope(StatisticsScope), // Set the samples from which the statistics are computed
+    ToggleHold,                         // Freeze the chart while the acquisition continues, release jumps back to live
+    SetSpectrumChannel(String),         // Set the variable ID of the channel in the spectrum desktop
+    SetSpectrumWindow(WindowFunction),  // Set the window applied before the FFT
+    SetSpectrumScale(MagnitudeScale),   // Set the linear or dB magnitude of the spectrum
+    SetSpectrumSampleRate(String),      // Set the sample rate, empty value estimates it from the receive times
+    SetSpectrumFundamental(String),     // Set the fundamental for the THD, empty value takes the highest peak
+    SetSpectrogramChannel(String),      // Set the variable ID of the channel in the spectrogram desktop
+    SetSpectrogramWindow(WindowFunction), // Set the window applied to every segment of the spectrogram
+    SetSpectrogramSegmentLength(usize), // Set the number of samples of one segment of the spectrogram
+    SetSpectrogramSampleRate(String),   // Set the sample rate, empty value estimates it from the receive times
+    SpectrogramComputed(Arc<Spectrogram>), // Spectrogram finished by the worker
+    SetStepChannel(String),             // Set the variable ID of the response of the step response analysis
+    SetStepReference(String),           // Set the variable ID of the reference of the step response analysis
+    SetStepSettlingBand(String),        // Set the settling band in percent of the step
+    AnalyzeStep,                        // Analyse the step between the cursors or in the displayed range
+    ClearStep,                          // Remove the step response results from the chart
+    PinReference,                       // Keep the displayed samples as the reference trace
+    LoadReference,                      // Load a previous capture from the output file as the reference trace
+    ClearReference,                     // Remove the reference trace from the chart
+    SetReferenceAlignment(ReferenceAlignment), // Align the reference by the X axis values or by the trigger
+    SetReferenceOffset(String),         // Set the manual shift of the reference in samples
+    SetReferenceDifference(Option<String>), // Set the channel whose difference to the reference is displayed
+    SetHistogramChannel(String),        // Set the variable ID of the channel in the histogram desktop
+    SetHistogramScope(StatisticsScope), // Set the samples from which the histogram is computed
+    SetHistogramBins(String),           // Set the number of bins of the histogram
+    SetHistogramRangeMin(String),       // Set the min of the histogram range, empty value takes the samples
+    SetHistogramRangeMax(String),       // Set the max of the histogram range, empty value takes the samples
+    SetXyChannelX(String),              // Set the variable ID of the channel on the X axis of the XY chart
+    SetXyChannelY(String),              // Set the variable ID of the channel on the Y axis of the XY chart
+    SetXyPersistence(String),           // Set the number of the newest samples drawn in the XY chart
+    SetXyFade(bool),                    // Draw the older points of the XY chart more transparent
+    SetXyEqualAspect(bool),             // Keep the same units per pixel on both axes of the XY chart
+    SetXyAngleChannel(String),          // Set the variable ID of the rotor angle drawn with the space vector
+    SetXySpaceVector(bool),             // Draw the newest point of the XY chart as a vector from the origin
+    AddTransform,                       // Add a Clarke/Park/polar transform to the device profile
+    RemoveTransform(usize),             // Remove the transform from the device profile
+    SetTransformKind(usize, TransformKind), // Set the kind of the transform
+    SetTransformInput(usize, usize, String), // Set the variable ID of an input of the transform
+    SetTransformOutput(usize, usize, String), // Set the variable ID of a derived channel of the transform
+    AddExpression,                      // Add a virtual channel defined by an expression to the device profile
+    RemoveExpression(usize),            // Remove the virtual channel from the device profile
+    SetExpression(usize, String),       // Set the definition "name = expression" of the virtual channel
+    AddFilter,                          // Add a filtered copy of a channel to the device profile
+    AddBitField,                        // Add a named bit or multi-bit field of a status word to the device profile
+    RemoveBitField(usize),              // Remove the bit field from the device profile
+    SetBitFieldField(usize, BitFieldField, String), // Set the channel, name, position or labels of the bit field
+    RemoveFilter(usize),                // Remove the filter from the device profile
+    SetFilterKind(usize, FilterKind),   // Set the kind of the filter
+    SetFilterField(usize, FilterField, String), // Set the channel, output or a parameter of the filter
+    AddAlarm,                           // Add a limit alarm rule to the device profile
+    RemoveAlarm(usize),                 // Remove the alarm rule from the device profile
+    SetAlarmCondition(usize, AlarmCondition), // Set the condition raising the alarm
+    SetAlarmSeverity(usize, AlarmSeverity), // Set the severity of the alarm
+    SetAlarmField(usize, AlarmField, String), // Set the channel or a limit of the alarm
+    SetAlarmAction(usize, AlarmAction, bool), // Enable or disable an action of the alarm
+    ClearAlarmLog,                      // Remove all events from the event log
+    AlarmBufferSaved(String, Result<(), String>), // Buffer of the alarm written to the file by the worker
+}
+
+impl Rtm {
+    // Saving one received frame to the signal store and to the output file, words are the fixed
+    // point words of the received variables, returns the workers started by the raised alarms
+    fn store_frame(
+        &mut self,
+        obj: &serde_json::Map<String, serde_json::Value>,
+        words: &HashMap<String, u32>,
+    ) -> Command<Message> {
+        // Derived, virtual and filtered channels of the device profile are added to the frame before it is
+        // stored, so they behave as the received channels in the charts, statistics and exports
+        let derived_frame;
+        let obj = if self.device_profile.transforms.is_empty()
+            && self.device_profile.expressions.is_empty()
+            && self.device_profile.filters.is_empty()
+        {
+            obj
+        } else {
+            let mut frame = obj.clone();
+            self.device_profile.apply_transforms(&mut frame);
+            self.device_profile.apply_expressions(&mut frame);
+            self.device_profile.apply_filters(&mut frame);
+            derived_frame = frame;
+            &derived_frame
+        };
+
+        // Saving the frame to the signal store which keeps the whole history, older samples are
+        // kept in memory mapped files on the disk
+        let x_axis_value = match self.signal_store.push_frame(obj, words) {
+            Ok(x_axis_value) => x_axis_value,
+            Err(e) => {
+                println!("Error saving the received data to the signal store {:#?}", e);
+                return Command::none();
+            }
+        };
+        self.watch_table.push_frame(obj, words, Instant::now());
+        let alarm_events = self
+            .device_profile
+            .check_alarms(obj, x_axis_value, Instant::now());
+        let command = self.handle_alarm_events(alarm_events);
+
+        // Writing sample at X axis and the corresponding Y values to the ouput file
+        let header = if !self.output_file.enable_output || self.output_file.handler.is_none() {
+            None
+        } else {
+            // Channels which appear after the header was written, e.g. a variable streamed later or
+            // a transform added while recording, continue the capture in a new file with the
+            // extended header, so none of their values are lost
+            match &self.output_file.columns {
+                None => Some(obj.keys().cloned().collect::<Vec<String>>()),
+                Some(columns) => {
+                    let new_columns: Vec<String> = obj
+                        .keys()
+                        .filter(|key| !columns.contains(key))
+                        .cloned()
+                        .collect();
+                    if new_columns.is_empty() {
+                        None
+                    } else {
+                        let mut columns = columns.clone();
+                        columns.extend(new_columns.iter().cloned());
+                        if self.create_output_file(&format!("-from-{}", x_axis_value)) {
+                            println!(
+                                "New channels {:?} received, the capture continues in {}",
+                                new_columns,
+                                self.output_file.path.as_deref().unwrap_or_default()
+                            );
+                            Some(columns)
+                        } else {
+                            // Output is stopped instead of writing rows which miss the new channels
+                            println!("New channels {:?} cannot be saved, the output is stopped", new_columns);
+                            self.output_file.enable_output = false;
+                            None
+                        }
+                    }
+                }
+            }
+        };
+        if self.output_file.enable_output {
+            if let Some(file_handler) = &mut self.output_file.handler {
+                // Header with the variable IDs, so the capture can be loaded back as a reference
+                if let Some(columns) = header {
+                    utils::write_output_to_file(
+                        file_handler,
+                        format!("x,{},event\n", columns.join(",")),
+                    );
+                    self.output_file.columns = Some(columns);
+                }
+                let Some(columns) = &self.output_file.columns else {
+                    return command;
+                };
+                let formatted_string = format!("{}", x_axis_value);
+                utils::write_output_to_file(file_handler, formatted_string);
+
+                // Loop by variables of the header, so the values stay in their columns also when
+                // a frame misses some variables, the values are scaled by the device profile
+                for key in columns.iter() {
+                    let value = obj.get(key).and_then(|value| value.as_f64());
+                    let scaled_value = self
+                        .device_profile
+                        .scale(key, value.unwrap_or(f64::NAN));
+                    let formatted_string = format!(",{}", scaled_value);
+                    utils::write_output_to_file(file_handler, formatted_string);
+                }
+
+                // Writing the notes of the markers at the sample and new line after X axis value and
+                // corresponding Y values
+                let event = self.markers.get_csv_event(x_axis_value);
+                utils::write_output_to_file(file_handler, format!(",{}\n", event));
+            }
+        }
+        command
+    }
+
+    // Actions of the raised alarms, the events with the log or highlight action are kept in the log
+    // the buffers of the auto save action are written by the returned workers
+    fn handle_alarm_events(&mut self, events: Vec<AlarmEvent>) -> Command<Message> {
+        let mut commands = Vec::new();
+        for mut event in events {
+            let Some(rule) = self.device_profile.alarms.get(event.rule) else {
+                continue;
+            };
+            if event.raised {
+                println!("Alarm {}: {}", event.severity, event.message);
+                if rule.auto_stop {
+                    if let Some(watch_channel) = self.serial_control_on_off_channel.as_mut() {
+                        let _ = watch_channel.tx.send(false);
+                    }
+                    event.message += " | Acquisition stopped";
+                }
+                if rule.auto_save {
+                    let (start, end) = self.get_chart_range();
+                    let local_time = Local::now().format("%Y-%m-%d-%H:%M:%S").to_string();
+                    let file_name = "./../".to_owned() + &local_time + "-rtm-alarm.csv";
+                    event.message += &format!(" | Saving to {}", file_name);
+                    commands.push(self.save_range(file_name, start, end, Message::AlarmBufferSaved));
+                }
+            }
+            if event.log || event.highlight {
+                self.alarm_log.push(event);
+            }
+        }
+        Command::batch(commands)
+    }
+
+    // Worker saving the samples start..end with the scaling and the markers at the time of the
+    // request, e.g. the buffer of an alarm or the exported range, the acquisition and the rendering
+    // continue while the file is written, the result is sent with the saved message
+    fn save_range(
+        &self,
+        file_name: String,
+        start: usize,
+        end: usize,
+        saved: fn(String, Result<(), String>) -> Message,
+    ) -> Command<Message> {
+        let snapshot = self.signal_store.get_snapshot(start, end);
+        let channel_configs: HashMap<String, ChannelConfig> = snapshot
+            .channels
+            .iter()
+            .map(|(id, _)| (id.clone(), self.device_profile.get_channel(id)))
+            .collect();
+        let markers = self.markers.clone();
+        Command::perform(
+            async move {
+                let saved_file_name = file_name.clone();
+                let result = tokio::task::spawn_blocking(move || {
+                    let scale = |id: &str, value: f64| match channel_configs.get(id) {
+                        Some(channel_config) => channel_config.scale(value),
+                        None => value,
+                    };
+                    let marker_event = |sample: u128| markers.get_csv_event(sample);
+                    snapshot
+                        .write_csv(&saved_file_name, scale, marker_event)
+                        .map_err(|e| e.to_string())
+                })
+                .await
+                .unwrap_or_else(|e| Err(e.to_string()));
+                (file_name, result)
+            },
+            move |(file_name, result)| saved(file_name, result),
+        )
+    }
+
+    // Creating the capture file, the header is written with the first stored frame, returns false
+    // when the file could not be created and the previous file stays open
+    fn create_output_file(&mut self, name_suffix: &str) -> bool {
+        let local_time = Local::now().format("%Y-%m-%d-%H:%M:%S").to_string();
+        let mut file_name = format!("./../{}-rtm-capture{}.csv", local_time, name_suffix);
+        // File created in the same second is not overwritten
+        let mut counter = 1;
+        while std::path::Path::new(&file_name).exists() {
+            counter += 1;
+            file_name = format!("./../{}-rtm-capture{}-{}.csv", local_time, name_suffix, counter);
+        }
+        match File::create(&file_name) {
+            Ok(file) => {
+                self.output_file.handler = Some(file);
+                self.output_file.columns = None;
+                self.output_file.path = Some(file_name);
+                // Markers file is created with the capture, so it exists even without new markers
+                self.save_markers();
+                true
+            }
+            Err(e) => {
+                println!("Error creating the output file {} {:#?}", file_name, e);
+                false
+            }
+        }
+    }
+
+    // Markers are saved next to the created output file
+    fn save_markers(&self) {
+        if let Some(path) = &self.output_file.path {
+            if let Err(e) = self.markers.save(path) {
+                println!("Error saving the markers {:#?}", e);
+            }
+        }
+    }
+
+    // Range of the samples displayed in the chart based on the display mode
+    fn get_chart_range(&self) -> (usize, usize) {
+        // Zoomed chart displays the selected samples while the acquisition continues
+        if let Some(x_range) = self.chart_zoom.x {
+            let end = (x_range.1.ceil().max(0.0) as usize + 1).min(self.signal_store.len());
+            let start = (x_range.0.floor().max(0.0) as usize).min(end);
+            return (start, end);
+        }
+
+        // Held chart keeps the range from the moment of freezing, the store keeps recording
+        if let Some(range) = self.chart_hold {
+            return range;
+        }
+
+        match &self.display_mode {
+            Some(display_mode) if display_mode == "running" && self.running_buffer.size != 0 => (
+                self.signal_store
+                    .len()
+                    .saturating_sub(self.running_buffer.size as usize),
+                self.signal_store.len(),
+            ),
+            Some(display_mode) if display_mode == "trigger" => {
+                self.trigger.get_range(self.signal_store.len())
+            }
+            _ => self.history_view.get_range(self.signal_store.len()),
+        }
+    }
+
+    // Taking the data for the chart panes from the signal store, called only on the render tick
+    // every pane gets only the channels assigned to it and its own Y range, the X range is shared
+    fn refresh_chart_data(&mut self) {
+        let (chart_range_start, chart_range_end) = self.get_chart_range();
+        self.chart_settings.trigger_marker = match &self.display_mode {
+            Some(display_mode) if display_mode == "trigger" => self.trigger.get_marker(),
+            _ => None,
+        };
+
+        let pane_layout = self.navigation_layout.get_pane_layout();
+        // Final value with the settling band of the step response
+        self.chart_settings.step_annotation = self.step_response.as_ref().map(|step_response| {
+            let band = (step_response.final_value - step_response.initial_value)
+                * step_response.settling_band
+                / 100.0;
+            StepAnnotation {
+                pane: pane_layout.get_pane(&step_response.channel),
+                markers: step_response.get_markers(),
+                levels: vec![
+                    step_response.final_value,
+                    step_response.final_value + band,
+                    step_response.final_value - band,
+                ],
+            }
+        });
+        // Markers with the notes in the displayed range
+        self.chart_settings.timeline_markers = self
+            .markers
+            .markers
+            .iter()
+            .filter(|marker| {
+                (chart_range_start as u128..chart_range_end as u128).contains(&marker.sample)
+            })
+            .map(|marker| (marker.sample as f64, marker.note.clone()))
+            .collect();
+        // Markers of the reference capture are shifted with the reference
+        if let Some(reference) = &self.reference {
+            let shift = self.get_reference_shift();
+            self.chart_settings.timeline_markers.extend(
+                reference
+                    .markers
+                    .iter()
+                    .map(|marker| (marker.sample as f64 + shift, marker))
+                    .filter(|(x, _)| (chart_range_start as f64..chart_range_end as f64).contains(x))
+                    .map(|(x, marker)| (x, format!("{} (reference)", marker.note))),
+            );
+        }
+
+        // Raised alarms with the highlight action in the displayed range
+        self.chart_settings.alarm_markers = self
+            .alarm_log
+            .events
+            .iter()
+            .filter(|event| {
+                event.raised
+                    && event.highlight
+                    && (chart_range_start as u128..chart_range_end as u128).contains(&event.sample)
+            })
+            .map(|event| AlarmMarker {
+                pane: pane_layout.get_pane(&event.channel),
+                sample: event.sample as f64,
+                severity: event.severity,
+            })
+            .collect();
+        self.chart_panes
+            .resize_with(pane_layout.pane_count.max(1), Default::default);
+
+        // Live channel minus the reference at the same displayed sample
+        let reference_shift = self.get_reference_shift();
+        let reference_difference = match (&self.reference, &self.reference_settings.difference_channel) {
+            (Some(reference), Some(id)) => self.signal_store.channel_indexes.get(id).map(|&position| {
+                let stored = &self.signal_store.channels[position];
+                let stride = ((chart_range_end - chart_range_start) / REFERENCE_MAX_POINTS).max(1);
+                let points: Vec<(f64, f64)> = (chart_range_start..chart_range_end)
+                    .step_by(stride)
+                    .filter_map(|sample| {
+                        let live = self.device_profile.scale(id, stored.get(sample));
+                        reference
+                            .get_value(id, sample as f64, reference_shift)
+                            .map(|value| (sample as f64, live - value))
+                            .filter(|(_, difference)| !difference.is_nan())
+                    })
+                    .collect();
+                OverlayTrace {
+                    id: id.clone(),
+                    points,
+                    difference: true,
+                }
+            }),
+            _ => None,
+        };
+
+        for (pane, chart_pane) in self.chart_panes.iter_mut().enumerate() {
+            // Positions of the visible channels in the signal store assigned to this pane
+            let positions: Vec<usize> = self
+                .signal_store
+                .channels
+                .iter()
+                .enumerate()
+                .filter(|(_, channel)| {
+                    pane_layout.get_pane(&channel.id) == pane
+                        && self.device_profile.is_visible(&channel.id)
+                })
+                .map(|(position, _)| position)
+                .collect();
+
+            // The min/max buckets of the store are the min/max decimation, the other modes get the
+            // raw samples while the range is short enough
+            let max_buckets = match pane_layout.get_pane_settings(pane).decimation {
+                DecimationMode::Off | DecimationMode::Lttb
+                    if chart_range_end - chart_range_start <= CHART_MAX_RAW_SAMPLES =>
+                {
+                    CHART_MAX_RAW_SAMPLES / 2
+                }
+                _ => CHART_MAX_BUCKETS,
+            };
+            chart_pane.set_data(
+                &self.signal_store,
+                &self.device_profile,
+                &positions,
+                (chart_range_start, chart_range_end),
+                max_buckets,
+            );
+
+            let scale_min_max =
+                |id: &str, min_max: (f64, f64)| self.device_profile.scale_min_max(id, min_max);
+            let y_range = pane_layout.get_pane_settings(pane).get_y_range(
+                self.signal_store.channels_min_max(
+                    &positions,
+                    chart_range_start,
+                    chart_range_end,
+                    scale_min_max,
+                ),
+                self.signal_store.channels_min_max(
+                    &positions,
+                    0,
+                    self.signal_store.len(),
+                    scale_min_max,
+                ),
+                &mut chart_pane.y_sticky_range,
+            );
+            chart_pane.viewport = ChartViewport {
+                x: self.chart_zoom.x.unwrap_or((
+                    chart_range_start as f64,
+                    chart_range_end.saturating_sub(1).max(chart_range_start + 1) as f64,
+                )),
+                y: self.chart_zoom.y.get(&pane).copied().unwrap_or(y_range),
+            };
+
+            // Reference traces of the visible channels assigned to this pane
+            chart_pane.overlays = match &self.reference {
+                Some(reference) => reference
+                    .channels
+                    .iter()
+                    .filter(|(id, _)| {
+                        pane_layout.get_pane(id) == pane && self.device_profile.is_visible(id)
+                    })
+                    .map(|(id, _)| OverlayTrace {
+                        id: id.clone(),
+                        points: reference.get_points(id, reference_shift, chart_pane.viewport.x),
+                        difference: false,
+                    })
+                    .collect(),
+                None => Vec::new(),
+            };
+            if let Some(difference) = reference_difference
+                .as_ref()
+                .filter(|difference| pane_layout.get_pane(&difference.id) == pane)
+            {
+                chart_pane.overlays.push(difference.clone());
+            }
+            chart_pane.cache.clear();
+        }
+
+        // Logic analyzer lanes of the bit fields with the same range as the chart panes
+        self.logic_lanes = self
+            .device_profile
+            .bit_fields
+            .iter()
+            .filter_map(|bit_field| {
+                LogicLane::new(bit_field, &self.signal_store, chart_range_start, chart_range_end)
+            })
+            .collect();
+        self.logic_cache.clear();
+
+        // Statistics of the visible channels scaled by the device profile
+        self.chart_statistics.clear();
+        if self.chart_settings.show_statistics {
+            let (statistics_start, statistics_end) = match self.chart_settings.statistics_scope {
+                StatisticsScope::VisibleWindow => (chart_range_start, chart_range_end),
+                StatisticsScope::WholeBuffer => (0, self.signal_store.len()),
+            };
+            for channel in self.signal_store.channels.iter() {
+                if !self.device_profile.is_visible(&channel.id) {
+                    continue;
+                }
+                let channel_config = self.device_profile.get_channel(&channel.id);
+                self.chart_statistics.push((
+                    self.device_profile.get_label(&channel.id),
+                    channel
+                        .statistics(statistics_start, statistics_end)
+                        .scaled(channel_config.gain, channel_config.offset),
+                ));
+            }
+        }
+    }
+
+    // Spectrum of the selected channel from the samples displayed in the chart, the newest
+    // MAX_FFT_LENGTH samples are used for longer ranges, called only on the render tick
+    fn refresh_spectrum(&mut self) {
+        self.spectrum_cache.clear();
+        let Some((start, samples)) =
+            self.get_analysed_samples(self.spectrum_settings.channel.as_deref(), MAX_FFT_LENGTH)
+        else {
+            self.spectrum = Spectrum::default();
+            self.spectrum_samples = 0;
+            return;
+        };
+
+        let (sample_rate, measured) = self.get_analysed_sample_rate(
+            self.spectrum_settings.sample_rate,
+            start,
+            start + samples.len(),
+        );
+        self.spectrum_sample_rate_measured = measured;
+        self.spectrum = Spectrum::compute(&samples, self.spectrum_settings.window, sample_rate);
+        self.spectrum_samples = samples.len();
+    }
+
+    // Shift of the reference to the displayed X axis in samples, the alignment by trigger places
+    // the trigger of the reference on the displayed trigger
+    fn get_reference_shift(&self) -> f64 {
+        let Some(reference) = &self.reference else {
+            return 0.0;
+        };
+        let trigger_shift = match self.reference_settings.alignment {
+            ReferenceAlignment::Sample => 0.0,
+            ReferenceAlignment::Trigger => {
+                match (
+                    self.chart_settings.trigger_marker,
+                    reference.get_trigger_sample(&self.trigger),
+                ) {
+                    (Some((trigger_x, _)), Some(reference_trigger)) => {
+                        trigger_x - reference_trigger
+                    }
+                    _ => 0.0,
+                }
+            }
+        };
+        trigger_shift + self.reference_settings.offset
+    }
+
+    // Step response of the selected channel between the measurement cursors, or in the displayed
+    // range when the cursors are hidden
+    fn analyze_step_response(&mut self) {
+        self.step_response = None;
+        let Some(channel) = self.step_settings.channel.clone() else {
+            println!("Step response channel is not selected.");
+            return;
+        };
+        let (start, end) = match self.get_cursors() {
+            Some(cursors) => {
+                let [a, b] = cursors
+                    .x
+                    .map(|x| (x.round().max(0.0) as usize).min(self.signal_store.len()));
+                (a.min(b), a.max(b))
+            }
+            None => self.get_chart_range(),
+        };
+        let read = |id: &str| -> Option<Vec<f64>> {
+            let position = *self.signal_store.channel_indexes.get(id)?;
+            let stored = &self.signal_store.channels[position];
+            Some(
+                (start..end)
+                    .map(|index| self.device_profile.scale(id, stored.get(index)))
+                    .collect(),
+            )
+        };
+        let Some(response) = read(&channel) else {
+            return;
+        };
+        let reference = self.step_settings.reference.as_deref().and_then(read);
+
+        self.step_response = StepResponse::analyze(
+            &channel,
+            start,
+            &response,
+            reference.as_deref(),
+            self.step_settings.settling_band,
+        );
+        self.step_sample_rate = self.signal_store.get_sample_rate(start, end);
+        if self.step_response.is_none() {
+            println!("No step found in the samples {}..{} of {}.", start, end, channel);
+            let _ = MessageDialog::new()
+                .set_title("No step found!")
+                .set_text("Select the region with the step by the measurement cursors or by zooming the chart.")
+                .set_type(native_dialog::MessageType::Warning)
+                .show_alert();
+        }
+    }
+
+    // Histogram of the selected channel over the visible window or the whole buffer, the
+    // statistics are taken from the store, so they include all samples of the range
+    fn refresh_histogram(&mut self) {
+        self.histogram_cache.clear();
+        let position = self
+            .histogram_settings
+            .channel
+            .as_ref()
+            .and_then(|id| self.signal_store.channel_indexes.get(id));
+        let Some(position) = position else {
+            self.histogram = Histogram::default();
+            return;
+        };
+
+        let (start, end) = match self.histogram_settings.scope {
+            StatisticsScope::VisibleWindow => self.get_chart_range(),
+            StatisticsScope::WholeBuffer => (0, self.signal_store.len()),
+        };
+        let channel = &self.signal_store.channels[*position];
+        let channel_config = self.device_profile.get_channel(&channel.id);
+        let step = (end.saturating_sub(start)).div_ceil(HISTOGRAM_MAX_SAMPLES).max(1);
+        let raw_samples: Vec<f64> = (start..end).step_by(step).map(|index| channel.get(index)).collect();
+        // Virtual and filtered channels have no received words, so no stuck bits are reported
+        let words: Vec<u32> = (start..end)
+            .step_by(step)
+            .filter_map(|index| channel.get_word(index))
+            .collect();
+        self.histogram = Histogram::compute(
+            &raw_samples,
+            &words,
+            |sample| channel_config.scale(sample),
+            self.histogram_settings.bins,
+            self.histogram_settings.get_range(),
+            channel
+                .statistics(start, end)
+                .scaled(channel_config.gain, channel_config.offset),
+        );
+    }
+
+    // Pairs of the samples of the XY channels from the newest samples of the chart range, scaled
+    // by the device profile, pairs with a missing sample are skipped
+    fn refresh_xy_points(&mut self) {
+        self.xy_cache.clear();
+        self.xy_points.clear();
+        self.xy_rotor_angle = None;
+        let positions = match (&self.xy_settings.x_channel, &self.xy_settings.y_channel) {
+            (Some(x_id), Some(y_id)) => (
+                self.signal_store.channel_indexes.get(x_id).copied(),
+                self.signal_store.channel_indexes.get(y_id).copied(),
+            ),
+            _ => return,
+        };
+        let (Some(x_position), Some(y_position)) = positions else {
+            return;
+        };
+
+        let (chart_range_start, chart_range_end) = self.get_chart_range();
+        let start = chart_range_start.max(chart_range_end.saturating_sub(self.xy_settings.persistence));
+        let step = (chart_range_end - start).div_ceil(XY_MAX_POINTS).max(1);
+        let x_channel = &self.signal_store.channels[x_position];
+        let y_channel = &self.signal_store.channels[y_position];
+        for index in (start..chart_range_end).step_by(step) {
+            let x = self.device_profile.scale(&x_channel.id, x_channel.get(index));
+            let y = self.device_profile.scale(&y_channel.id, y_channel.get(index));
+            if !x.is_nan() && !y.is_nan() {
+                self.xy_points.push((x, y));
+            }
+        }
+
+        // Rotor angle at the newest sample, drawn as the direction of the d axis
+        if let Some(angle_position) = self
+            .xy_settings
+            .angle_channel
+            .as_ref()
+            .and_then(|id| self.signal_store.channel_indexes.get(id))
+        {
+            let angle_channel = &self.signal_store.channels[*angle_position];
+            let angle = self.device_profile.scale(
+                &angle_channel.id,
+                angle_channel.get(chart_range_end.saturating_sub(1)),
+            );
+            self.xy_rotor_angle = (chart_range_end > 0 && !angle.is_nan()).then_some(angle);
+        }
+    }
+
+    // Starting the worker computing the spectrogram of the selected channel from the samples
+    // displayed in the chart, the samples are read and the short-time FFT runs off the update
+    // loop and only one computation runs at a time, the newest data are taken when the running
+    // one finishes, the worker continues the current spectrogram with the new segments
+    fn request_spectrogram(&mut self) -> Command<Message> {
+        if !self.spectrogram_requested || self.spectrogram_computing {
+            return Command::none();
+        }
+        self.spectrogram_requested = false;
+
+        let position = self
+            .spectrogram_settings
+            .channel
+            .as_ref()
+            .and_then(|id| self.signal_store.channel_indexes.get(id));
+        let Some(&position) = position else {
+            self.spectrogram = Arc::new(Spectrogram::default());
+            self.spectrogram_cache.clear();
+            return Command::none();
+        };
+        let (chart_range_start, chart_range_end) = self.get_chart_range();
+        let start = chart_range_start.max(chart_range_end.saturating_sub(SPECTROGRAM_MAX_SAMPLES));
+        let (sample_rate, measured) = self.get_analysed_sample_rate(
+            self.spectrogram_settings.sample_rate,
+            start,
+            chart_range_end,
+        );
+        self.spectrogram_sample_rate_measured = measured;
+        let window = self.spectrogram_settings.window;
+        let segment_length = self.spectrogram_settings.segment_length;
+        let channel = self.signal_store.channels[position].id.clone();
+        let channel_config = self.device_profile.get_channel(&channel);
+        let path = self.signal_store.get_channel_path(position);
+        let previous = self.spectrogram.clone();
+
+        self.spectrogram_computing = true;
+        Command::perform(
+            async move {
+                tokio::task::spawn_blocking(move || {
+                    // Samples scaled by the device profile read from the backing file of the channel
+                    let read = |first: usize, end: usize| -> Vec<f64> {
+                        match signal_store::read_samples(&path, first, end) {
+                            Ok(samples) => samples
+                                .into_iter()
+                                .map(|sample| channel_config.scale(sample))
+                                .collect(),
+                            Err(e) => {
+                                println!("Error reading the samples of the spectrogram {:#?}", e);
+                                vec![f64::NAN; end - first]
+                            }
+                        }
+                    };
+                    previous.update(
+                        &channel,
+                        window,
+                        segment_length,
+                        sample_rate,
+                        (start, chart_range_end),
+                        read,
+                    )
+                })
+                .await
+                .unwrap_or_else(|e| {
+                    println!("Error computing the spectrogram {:#?}", e);
+                    Spectrogram::default()
+                })
+            },
+            |spectrogram| Message::SpectrogramComputed(Arc::new(spectrogram)),
+        )
+    }
+
+    // Newest samples of the channel (variable ID) in the range displayed in the chart, at most
+    // max_samples, scaled by the device profile, returns the index of the first sample
+    fn get_analysed_samples(&self, id: Option<&str>, max_samples: usize) -> Option<(usize, Vec<f64>)> {
+        let position = *self.signal_store.channel_indexes.get(id?)?;
+        let (chart_range_start, chart_range_end) = self.get_chart_range();
+        let start = chart_range_start.max(chart_range_end.saturating_sub(max_samples));
+        let channel = &self.signal_store.channels[position];
+        let samples = (start..chart_range_end)
+            .map(|index| self.device_profile.scale(&channel.id, channel.get(index)))
+            .collect();
+        Some((start, samples))
+    }
+
+    // Sample rate inserted by user, else estimated from the receive times of the samples, without
+    // any the frequency axis is in cycles per sample, returns (sample rate, estimated)
+    fn get_analysed_sample_rate(&self, inserted: Option<f64>, start: usize, end: usize) -> (f64, bool) {
+        match (inserted, self.signal_store.get_sample_rate(start, end)) {
+            (Some(sample_rate), _) => (sample_rate, false),
+            (None, Some(sample_rate)) => (sample_rate, true),
+            (None, None) => (1.0, false),
+        }
+    }
+
+    // Chart pane edited by the Y axis, decimation and cursor controls, limited to the panes of the
+    // selected desktop
+    fn get_selected_pane(&self) -> usize {
+        self.selected_pane
+            .min(self.navigation_layout.get_pane_layout().pane_count.max(1) - 1)
+    }
+
+    // Enabled measurement cursors of the selected pane, or of the first pane with the cursors
+    fn get_cursors(&self) -> Option<&MeasurementCursors> {
+        let selected = self
+            .chart_panes
+            .get(self.get_selected_pane())
+            .filter(|chart_pane| chart_pane.cursors.enabled);
+        selected
+            .or_else(|| self.chart_panes.iter().find(|chart_pane| chart_pane.cursors.enabled))
+            .map(|chart_pane| &chart_pane.cursors)
+    }
+
+    // Clearing the accumulated ranges of the sticky max mode in all panes
+    fn reset_sticky_ranges(&mut self) {
+        for chart_pane in self.chart_panes.iter_mut() {
+            chart_pane.y_sticky_range = None;
+        }
+    }
 }
 
 impl Application for Rtm {
@@ -125,9 +1135,6 @@ impl Application for Rtm {
     type Flags = AppFlags;
 
     fn new(flags: Self::Flags) -> (Self, iced::Command<Message>) {
-        // Vector for samples in X axis which is used as an input to the HashMap
-        let x_axis_values_internal: VecDeque<u128> = VecDeque::new();
-
         // Vector available serial ports
         let mut serial_com_vec_port_names_internal: Vec<String> = Vec::new();
         // Not selected value as a default value
@@ -156,12 +1163,21 @@ impl Application for Rtm {
                 "default".to_string(),
                 "debug".to_string(),
                 "debug free".to_string(),
+                "channels".to_string(),
+                "watch".to_string(),
+                "spectrum".to_string(),
+                "spectrogram".to_string(),
+                "xy".to_string(),
+                "histogram".to_string(),
+                "alarms".to_string(),
             ],
             // Currently active desktop
             selected_desktop: Some("default".to_string()),
+            // Every desktop starts with one chart pane with all channels
+            pane_layouts: HashMap::new(),
         };
 
-        let app = Rtm {
+        let mut app = Rtm {
             // Initialization of struct with broadcast channels, connecting backend and frontend
             // compontens
             broadcast_data_bf_channel: BroadcastBFValues {
@@ -174,15 +1190,64 @@ impl Application for Rtm {
             serial_control_on_off_channel: None,
             serial_data_fetch_handle: None,
             data: serde_json::Value::default(),
-            new_data_points: (x_axis_values_internal, HashMap::new()),
+            signal_store: SignalStore::new(),
+            history_view: Default::default(),
+            chart_panes: vec![ChartPane::default()],
+            render_settings: Default::default(),
+            chart_settings: Default::default(),
+            selected_pane: 0,
+            chart_zoom: Default::default(),
+            chart_zoom_history: Vec::new(),
+            trigger: Default::default(),
+            chart_statistics: Vec::new(),
+            step_settings: Default::default(),
+            step_response: None,
+            step_sample_rate: None,
+            logic_lanes: Vec::new(),
+            logic_cache: Default::default(),
+            reference: None,
+            reference_settings: Default::default(),
+            alarm_log: Default::default(),
+            markers: Default::default(),
+            watch_table: Default::default(),
+            spectrum_settings: Default::default(),
+            spectrum: Default::default(),
+            spectrum_samples: 0,
+            spectrum_sample_rate_measured: false,
+            spectrum_cache: Default::default(),
+            spectrogram_settings: Default::default(),
+            spectrogram: Default::default(),
+            spectrogram_sample_rate_measured: false,
+            spectrogram_requested: false,
+            spectrogram_computing: false,
+            spectrogram_cache: Default::default(),
+            histogram_settings: Default::default(),
+            histogram: Default::default(),
+            histogram_cache: Default::default(),
+            xy_settings: Default::default(),
+            xy_points: Vec::new(),
+            xy_rotor_angle: None,
+            xy_cache: Default::default(),
+            device_profile: DeviceProfile::new("default"),
+            saved_profiles: DeviceProfile::list_saved(),
+            chart_hold: None,
             display_mode: Some("default".to_string()),
-            display_mode_select_values: vec!["default".to_string(), "running".to_string()],
+            display_mode_select_values: vec![
+                "default".to_string(),
+                "running".to_string(),
+                "trigger".to_string(),
+            ],
             serial_ports_available: serial_ports_available_internal,
             running_buffer: Default::default(),
             navigation_layout: navigation_layout_internal,
             output_file: Default::default(),
             theme: iced::Theme::TokyoNightStorm.into(),
         };
+        // Names, units, scaling and panes of the channels from the channel definition file
+        if let Some(channel_definition) = &flags.channel_definition {
+            channel_definition.apply(&mut app.device_profile, &mut app.navigation_layout);
+        }
+
         (app, Command::none())
     }
 
@@ -191,6 +1256,13 @@ impl Application for Rtm {
     }
 
     fn update(&mut self, message: Self::Message) -> Command<Message> {
+        // Every message except the render tick can change what is drawn in the chart, so the
+        // chart is refreshed on the next render tick, the finished spectrogram changes only its
+        // own chart
+        if !matches!(message, Message::RenderTick(_) | Message::SpectrogramComputed(_)) {
+            self.render_settings.dirty = true;
+        }
+
         match message {
             Message::ThemeChanged(theme) => {
                 self.theme = theme;
@@ -406,180 +1478,62 @@ impl Application for Rtm {
                 Command::none()
             }
             Message::StartSerialDataFetch => {
+                // Time without the samples is counted again from the restart
+                self.device_profile.reset_alarms();
                 if let Some(watch_channel) = self.serial_control_on_off_channel.as_mut() {
                     let _ = watch_channel.tx.send(true);
                 }
                 Command::none()
             }
             Message::ExternalDataReceived(message) => {
-                self.data = message.clone();
-
-                let mut index = 0;
-
-                // If the data to be processed are valid
-                if let Some(obj) = self.data.as_object() {
-                    // If there were no data, starting to write the data to the vectors
-                    // start with 0
-                    if self.new_data_points.0.len() == 0 {
-                        self.new_data_points.0.push_back(0);
-                    } else {
-                        // Otherwise increment the x axis value
-                        self.new_data_points
-                            .0
-                            .push_back(self.new_data_points.0.back().unwrap() + 1);
-                    }
-
-                    // Writing sample at X axis to the ouput file
-                    if self.output_file.enable_output == true {
-                        if let Some(file_handler) = &mut self.output_file.handler {
-                            let index_string = self
-                                .new_data_points
-                                .0
-                                .get(self.new_data_points.0.len() - 1)
-                                .unwrap();
-                            let formatted_string = format!("{}", index_string);
-                            utils::write_output_to_file(file_handler, formatted_string);
-                        }
-                    }
-
-                    // Loop by variables in the received data
-                    for (key, value) in obj {
-                        index = index + 1; // the HashMap is indexed from 1 here
-
-                        //                        println!("obj length, {}", obj.len());
-                        //                        println!("working on index: {}", index);
-                        //                        println!("0 length: {}", self.new_data_points.0.len());
-
-                        // Saving data to the HaashMap part to VecDeque objects for variables
-                        self.new_data_points
-                            .1
-                            .entry(index)
-                            .or_insert_with(VecDeque::new)
-                            .push_back(value.as_f64().unwrap()); // Pushing the new data to the
-                                                                 // VecDeque object in a HashMap
-
-                        // If the output file data saving is enabled
-                        if self.output_file.enable_output == true {
-                            if let Some(v) = self.output_file.handler.as_mut() {
-                                println!("0 direct len {}", self.new_data_points.0.len());
-                                println!(
-                                    "data points index: {}, data at self.. :{:#?}",
-                                    index,
-                                    self.new_data_points.1.get(&index).unwrap().len()
-                                );
-                                // Preparing string of a current variable value
-                                let data_string = self
-                                    .new_data_points
-                                    .1
-                                    .get(&index)
-                                    .unwrap()
-                                    .get(self.new_data_points.0.len() - 1) // is zero based, so
-                                                                           // when using length,
-                                                                           // need to subtract 1
-                                    .unwrap();
-                                let formatted_string = format!(",{}", data_string); // format the
-                                                                                    // obtained
-                                                                                    // data
-                                // Write the formatted data to the output file
-                                // writing current variable value
-                                match v.write_all(formatted_string.as_ref()) {
-                                    Ok(v) => {
-                                        println!("Ok writing a file for data {:#?}, {:#?}", key, v);
-                                    }
-                                    Err(e) => {
-                                        println!("Error writing a file {:#?}", e);
-                                    }
-                                }
-                            }
-                        }
-
-                        // Firstly popping Y values, because checking the length of X axis value
-                        // and based on that length, the Y value vectors should have same length
-                        // and after popping Y values, the X value can be popped, otherwise if
-                        // changed the position of X popping, other indexes at Y would be popped
-                        
-                        // Checks for running buffer mode, to pop the data after the buffer length
-                        // is reached
-                        if self.new_data_points.0.len()
-                            == self.running_buffer.size as usize + 1 as usize // the + 1 is used
-                                                                              // because when
-                                                                              // length is 200 the
-                                                                              // valu still should
-                                                                              // not be popped,
-                                                                              // because there
-                                                                              // would be only 199
-                                                                              // values then, so
-                                                                              // when the length
-                                                                              // reaches 201 and
-                                                                              // comparing with 200
-                                                                              // + 1, the 1 value
-                                                                              // is popped, so the
-                                                                              // displyed length is
-                                                                              // 200
-                            && self.display_mode == Some("running".to_string())
-                        {
-                            // Popping Y axis values and shrinking the VecDeque
-                            self.new_data_points.1.entry(index).or_default().pop_front();
-                            self.new_data_points
-                                .1
-                                .entry(index)
-                                .or_default()
-                                .shrink_to_fit();
-                        }
-                    } // Here ends looping through variable objects in received data JSON
-
-                    // And popping of data is solved
-
-                    // Checking the X sample values to same length as for Y values
-                    // and popping the data
-                    if self.new_data_points.0.len()
-                        == self.running_buffer.size as usize + 1 as usize
-                        && self.display_mode == Some("running".to_string())
-                    {
-                        //                        println!("LENGTH: {:#?}", self.new_data_points.0.len());
-                        self.new_data_points.0.pop_front();
-                        self.new_data_points.0.shrink_to_fit();
-                        //                        println!("LENGTH: {:#?}", self.new_data_points.0.len());
-                    }
+                // The backend sends the frames in batches as a JSON array, so the GUI is not
+                // updated for every single frame, single frames are still accepted
+                let frames: Vec<serde_json::Value> = match message {
+                    serde_json::Value::Array(frames) => frames,
+                    frame => vec![frame],
+                };
 
-                    // Writing new line after X axis value and corresponding Y values were written
-                    // to output file
-                    if let Some(v) = self.output_file.handler.as_mut() {
-                        match v.write_all(b"\n") {
-                            Ok(v) => {
-                                println!("Ok writing a file ending part, {:#?}", v);
-                            }
-                            Err(e) => {
-                                println!("Error writing a file {:#?}", e);
-                            }
-                        }
+                let mut commands = Vec::new();
+                for mut frame in frames {
+                    // If the data to be processed are valid
+                    if let Some(obj) = frame.as_object_mut() {
+                        let words = take_frame_words(obj);
+                        commands.push(self.store_frame(obj, &words));
                     }
+                    self.data = frame;
                 }
 
-                //                println!("Length of 0 {}", self.new_data_points.0.len());
-                //                if let Some(test_data) = self.new_data_points.1.get(&1) {
-                //                    println!("Length of 1 {:#?}", test_data.len());
-                //                }
-                //                if let Some(test_data) = self.new_data_points.1.get(&2) {
-                //                    println!("Length of 2 {:#?}", test_data.len());
-                //                }
-                //                if let Some(test_data) = self.new_data_points.1.get(&3) {
-                //                    println!("Length of 3 {:#?}", test_data.len());
-                //                }
-                //                if let Some(test_data) = self.new_data_points.1.get(&4) {
-                //                    println!("Length of 4 {:#?}", test_data.len());
-                //                }
+                // Checking the newly stored samples for the trigger event
+                if self.display_mode.as_deref() == Some("trigger") {
+                    self.trigger.process(&self.signal_store, &self.device_profile);
+                }
 
-                Command::none()
+                Command::batch(commands)
             }
             Message::ClearFigure => {
-                self.new_data_points.0.clear();
-                self.new_data_points.0.shrink_to_fit();
-                self.new_data_points.1.clear();
+                self.signal_store.clear();
+                self.watch_table.clear();
+                self.history_view.end = None;
+                // Averages and integrals of the virtual channels and the filters start again with
+                // the new data
+                self.device_profile.compile_expressions();
+                self.device_profile.reset_filters();
+                self.device_profile.reset_alarms();
+                self.alarm_log.clear_highlights();
+                self.markers.clear();
+                // Samples start again from zero, so the open capture continues in a new file and
+                // the markers saved with the previous file are kept
+                if self.output_file.handler.is_some() {
+                    self.create_output_file("");
+                }
                 Command::none()
             }
             Message::SetDisplayMode(mode) => {
                 println!("mode is: {}", mode);
+                // Trigger starts with the samples received after the mode was selected
+                if mode == "trigger" {
+                    self.trigger.arm(&self.signal_store);
+                }
                 self.display_mode = Some(mode);
 
                 Command::none()
@@ -631,11 +1585,7 @@ impl Application for Rtm {
                 Command::none()
             }
             Message::CreateOutputFile => {
-                let local_time = Local::now().format("%Y-%m-%d-%H:%M:%S").to_string();
-                let file_name = "./../".to_owned() + &local_time + "-rtm-capture.csv";
-                let file_test = File::create(file_name.to_string()).unwrap();
-
-                self.output_file.handler = Some(file_test);
+                self.create_output_file("");
                 Command::none()
             }
             Message::StartOutputFile => {
@@ -646,97 +1596,1993 @@ impl Application for Rtm {
                 self.output_file.enable_output = false;
                 Command::none()
             }
-        }
-    }
+            Message::SetHistorySpan(span) => {
+                self.history_view.span_string = span.clone();
+                let result: Result<u64, _> = span.parse();
+                self.history_view.span = match result {
+                    Ok(number) => number,
+                    Err(e) => {
+                        println!("Error parsing the history span. {:#?}", e);
+                        0
+                    }
+                };
 
-    fn subscription(&self) -> iced::Subscription<Self::Message> {
-        // Subscription of frontend to the backend data
-        // the channels are initialized before running the GUI in the main function
-        let broadcast_listener_subscription = iced::subscription::unfold(
-            "broadcast listener main",
-            self.broadcast_data_bf_channel.rx.take(),
-            move |mut receiver| async move {
-                let message = receiver.as_mut().unwrap().recv().await.unwrap();
-                (Message::ExternalDataReceived(message), receiver)
-            },
-        );
+                Command::none()
+            }
+            Message::SetTriggerChannel(channel) => {
+                self.trigger.channel = Some(channel);
+                self.trigger.arm(&self.signal_store);
 
-        // Batch is here used to be able to add more subscriptions later
-        iced::Subscription::batch(vec![broadcast_listener_subscription])
-    }
+                Command::none()
+            }
+            Message::SetTriggerEdge(edge) => {
+                self.trigger.edge = edge;
 
-    fn view(&self) -> iced::Element<'_, Self::Message> {
+                Command::none()
+            }
+            Message::SetTriggerLevel(level) => {
+                self.trigger.level_string = level.clone();
+                let result: Result<f64, _> = level.parse();
+                match result {
+                    Ok(number) => self.trigger.level = number,
+                    Err(e) => {
+                        println!("Error parsing the trigger level. {:#?}", e);
+                    }
+                };
 
-        // Changing themes
-        let theme_widget: PickList<'_, Theme, &[Theme], Theme, Message> =
-            iced::widget::pick_list::PickList::new(
-                iced::Theme::ALL,
-                Some(self.theme.clone()),
-                Message::ThemeChanged,
-            );
+                Command::none()
+            }
+            Message::SetTriggerPreSamples(samples) => {
+                self.trigger.pre_samples_string = samples.clone();
+                let result: Result<usize, _> = samples.parse();
+                self.trigger.pre_samples = match result {
+                    Ok(number) => number,
+                    Err(e) => {
+                        println!("Error parsing the trigger pre samples. {:#?}", e);
+                        0
+                    }
+                };
 
-        // Chain of action used to initialize all needed to fetch data
-        let initialize_rtm_button: Button<Message> = Button::new("Initialize RTM")
-            .style(theme::Button::Destructive)
-            .on_press(Message::InitializeRtm);
+                Command::none()
+            }
+            Message::SetTriggerPostSamples(samples) => {
+                self.trigger.post_samples_string = samples.clone();
+                let result: Result<usize, _> = samples.parse();
+                self.trigger.post_samples = match result {
+                    Ok(number) => number,
+                    Err(e) => {
+                        println!("Error parsing the trigger post samples. {:#?}", e);
+                        0
+                    }
+                };
 
-        // Changing dekstops
-        let desktop_selection_title = Text::<'_, Theme>::new("Desktop");
-        let desktop_selection_widget: PickList<'_, String, &[String], String, Message> =
-            iced::widget::pick_list::PickList::new(
-                self.navigation_layout.available_desktops.as_ref(),
-                self.navigation_layout.selected_desktop.clone(),
-                Message::ChangeDesktop,
+                Command::none()
+            }
+            Message::SetTriggerArming(arming) => {
+                self.trigger.arming = arming;
+                self.trigger.arm(&self.signal_store);
+
+                Command::none()
+            }
+            Message::ArmTrigger => {
+                self.trigger.arm(&self.signal_store);
+
+                Command::none()
+            }
+            Message::SetHistoryPosition(position) => {
+                // Moving the slider to the end means following the newest data again
+                if position as usize >= self.signal_store.len() {
+                    self.history_view.end = None;
+                } else {
+                    self.history_view.end = Some(position as u64);
+                }
+
+                Command::none()
+            }
+            Message::FollowLiveHistory => {
+                self.history_view.end = None;
+                Command::none()
+            }
+            Message::RenderTick(instant) => {
+                // Channels without samples are checked also when no frames are received, but only
+                // while the data are fetched
+                let fetching = self
+                    .serial_control_on_off_channel
+                    .as_ref()
+                    .is_some_and(|watch_channel| *watch_channel.tx.borrow());
+                let mut alarm_command = Command::none();
+                if fetching {
+                    let alarm_events = self
+                        .device_profile
+                        .check_stale_alarms(self.signal_store.len() as u128, instant);
+                    if !alarm_events.is_empty() {
+                        alarm_command = self.handle_alarm_events(alarm_events);
+                        self.render_settings.dirty = true;
+                    }
+                }
+
+                // Redrawing the chart at the selected FPS independent of the acquisition rate
+                let rendered = self.render_settings.dirty;
+                if rendered {
+                    self.refresh_chart_data();
+                    match self.navigation_layout.selected_desktop.as_deref() {
+                        Some("spectrum") => self.refresh_spectrum(),
+                        Some("spectrogram") => self.spectrogram_requested = true,
+                        Some("xy") => self.refresh_xy_points(),
+                        Some("histogram") => self.refresh_histogram(),
+                        _ => {}
+                    }
+                    self.render_settings.dirty = false;
+                }
+                self.render_settings.measure(instant, rendered);
+                self.watch_table.measure(instant);
+
+                Command::batch([alarm_command, self.request_spectrogram()])
+            }
+            Message::SetDecimationMode(mode) => {
+                println!("decimation mode is: {}", mode);
+                let pane = self.get_selected_pane();
+                self.navigation_layout
+                    .get_pane_layout_mut()
+                    .get_pane_settings_mut(pane)
+                    .decimation = mode;
+
+                Command::none()
+            }
+            Message::SetYScaleMode(mode) => {
+                println!("Y scale mode is: {}", mode);
+                let pane = self.get_selected_pane();
+                self.navigation_layout
+                    .get_pane_layout_mut()
+                    .get_pane_settings_mut(pane)
+                    .y_scale = mode;
+                if let Some(chart_pane) = self.chart_panes.get_mut(pane) {
+                    chart_pane.y_sticky_range = None;
+                }
+
+                Command::none()
+            }
+            Message::SetYManualMin(min) => {
+                let pane = self.get_selected_pane();
+                let pane_settings = self
+                    .navigation_layout
+                    .get_pane_layout_mut()
+                    .get_pane_settings_mut(pane);
+                pane_settings.y_manual_min_string = min.clone();
+                let result: Result<f64, _> = min.parse();
+                match result {
+                    Ok(number) => {
+                        pane_settings.y_manual_min = number;
+                        pane_settings.y_scale = YScaleMode::Manual;
+                    }
+                    Err(e) => println!("Error parsing the Y axis min. {:#?}", e),
+                }
+
+                Command::none()
+            }
+            Message::SetYManualMax(max) => {
+                let pane = self.get_selected_pane();
+                let pane_settings = self
+                    .navigation_layout
+                    .get_pane_layout_mut()
+                    .get_pane_settings_mut(pane);
+                pane_settings.y_manual_max_string = max.clone();
+                let result: Result<f64, _> = max.parse();
+                match result {
+                    Ok(number) => {
+                        pane_settings.y_manual_max = number;
+                        pane_settings.y_scale = YScaleMode::Manual;
+                    }
+                    Err(e) => println!("Error parsing the Y axis max. {:#?}", e),
+                }
+
+                Command::none()
+            }
+            Message::SetChannelField(id, field, value) => {
+                self.device_profile.set_field(&id, field, value);
+                Command::none()
+            }
+            Message::SetChannelVisible(id, visible) => {
+                self.device_profile.set_visible(&id, visible);
+                Command::none()
+            }
+            Message::SetProfileName(name) => {
+                self.device_profile.name = name;
+                Command::none()
+            }
+            Message::SaveProfile => {
+                // Chart panes of the desktops are saved with the profile
+                self.device_profile.pane_layouts = self.navigation_layout.pane_layouts.clone();
+                if self.device_profile.name.trim().is_empty() {
+                    println!("Device profile name is empty.");
+                } else if let Err(e) = self.device_profile.save() {
+                    println!("Error saving the device profile {:#?}", e);
+                    let _ = MessageDialog::new()
+                        .set_title("Error saving the device profile!")
+                        .set_text("The device profile could not be written to the profile directory.")
+                        .set_type(native_dialog::MessageType::Error)
+                        .show_alert();
+                }
+                self.saved_profiles = DeviceProfile::list_saved();
+                Command::none()
+            }
+            Message::LoadProfile(name) => {
+                match DeviceProfile::load(&name) {
+                    Ok(profile) => {
+                        // Profiles saved before the pane layouts were added keep the current panes
+                        if !profile.pane_layouts.is_empty() {
+                            self.navigation_layout.pane_layouts = profile.pane_layouts.clone();
+                        }
+                        self.device_profile = profile;
+                    }
+                    Err(e) => {
+                        println!("Error loading the device profile {:#?}", e);
+                        let _ = MessageDialog::new()
+                            .set_title("Error loading the device profile!")
+                            .set_text("The selected device profile could not be read.")
+                            .set_type(native_dialog::MessageType::Error)
+                            .show_alert();
+                    }
+                }
+                Command::none()
+            }
+            Message::ToggleStatistics => {
+                self.chart_settings.show_statistics = !self.chart_settings.show_statistics;
+                Command::none()
+            }
+            Message::ToggleMarkers => {
+                self.chart_settings.show_markers = !self.chart_settings.show_markers;
+                Command::none()
+            }
+            Message::SetMarkerNote(note) => {
+                self.markers.note = note;
+                Command::none()
+            }
+            Message::AddLiveMarker => {
+                // While the data are fetched the marker is placed at the next received sample, so it
+                // is written to the event column of the output file with that sample
+                let fetching = self
+                    .serial_control_on_off_channel
+                    .as_ref()
+                    .is_some_and(|watch_channel| *watch_channel.tx.borrow());
+                let sample = match fetching {
+                    true => self.signal_store.len(),
+                    false => self.signal_store.len().saturating_sub(1),
+                };
+                self.markers.add(sample as u128);
+                self.markers.note.clear();
+                self.save_markers();
+                Command::none()
+            }
+            Message::AddMarker => {
+                let sample = match self.get_cursors() {
+                    Some(cursors) => cursors.x[0].round().max(0.0),
+                    None => {
+                        let (start, end) = self.get_chart_range();
+                        ((start + end) / 2) as f64
+                    }
+                };
+                self.markers.add(sample as u128);
+                self.markers.note.clear();
+                self.save_markers();
+                Command::none()
+            }
+            Message::SetExistingMarkerNote(index, note) => {
+                if let Some(marker) = self.markers.markers.get_mut(index) {
+                    marker.note = note;
+                }
+                Command::none()
+            }
+            Message::SubmitMarkerNote => {
+                self.save_markers();
+                Command::none()
+            }
+            Message::RemoveMarker(index) => {
+                self.markers.remove(index);
+                self.save_markers();
+                Command::none()
+            }
+            Message::JumpToMarker(index) => {
+                // Displayed span is kept and the marker is placed in the middle, the zoomed range
+                // stays while the acquisition continues
+                if let Some(marker) = self.markers.markers.get(index) {
+                    let (start, end) = self.get_chart_range();
+                    let half_span = (end.saturating_sub(start).max(2) / 2) as f64;
+                    self.chart_zoom_history.push(self.chart_zoom.clone());
+                    self.chart_zoom.x = Some((
+                        marker.sample as f64 - half_span,
+                        marker.sample as f64 + half_span,
+                    ));
+                }
+                Command::none()
+            }
+            Message::ExportCsv => {
+                let (start, end) = self.get_chart_range();
+                let local_time = Local::now().format("%Y-%m-%d-%H:%M:%S").to_string();
+                let file_name = "./../".to_owned() + &local_time + "-rtm-export.csv";
+                self.save_range(file_name, start, end, Message::CsvExported)
+            }
+            Message::CsvExported(file_name, result) => {
+                match result {
+                    Ok(()) => println!("Displayed samples exported to {}", file_name),
+                    Err(e) => println!("Error exporting the displayed samples {:#?}", e),
+                }
+                Command::none()
+            }
+            Message::SetStatisticsScope(scope) => {
+                self.chart_settings.statistics_scope = scope;
+                Command::none()
+            }
+            Message::SetSpectrumChannel(channel) => {
+                self.spectrum_settings.channel = Some(channel);
+                Command::none()
+            }
+            Message::SetSpectrumWindow(window) => {
+                self.spectrum_settings.window = window;
+                Command::none()
+            }
+            Message::SetSpectrumScale(scale) => {
+                self.spectrum_settings.scale = scale;
+                Command::none()
+            }
+            Message::SetSpectrumSampleRate(sample_rate) => {
+                self.spectrum_settings.sample_rate_string = sample_rate.clone();
+                if sample_rate.trim().is_empty() {
+                    self.spectrum_settings.sample_rate = None;
+                } else {
+                    match sample_rate.trim().parse::<f64>() {
+                        Ok(number) if number > 0.0 => {
+                            self.spectrum_settings.sample_rate = Some(number)
+                        }
+                        Ok(_) => println!("Sample rate must be positive."),
+                        Err(e) => println!("Error parsing the sample rate. {:#?}", e),
+                    }
+                }
+                Command::none()
+            }
+            Message::SetSpectrumFundamental(fundamental) => {
+                self.spectrum_settings.fundamental_string = fundamental.clone();
+                if fundamental.trim().is_empty() {
+                    self.spectrum_settings.fundamental = None;
+                } else {
+                    match fundamental.trim().parse::<f64>() {
+                        Ok(number) if number > 0.0 => {
+                            self.spectrum_settings.fundamental = Some(number)
+                        }
+                        Ok(_) => println!("Fundamental frequency must be positive."),
+                        Err(e) => println!("Error parsing the fundamental frequency. {:#?}", e),
+                    }
+                }
+                Command::none()
+            }
+            Message::SetSpectrogramChannel(channel) => {
+                self.spectrogram_settings.channel = Some(channel);
+                Command::none()
+            }
+            Message::SetSpectrogramWindow(window) => {
+                self.spectrogram_settings.window = window;
+                Command::none()
+            }
+            Message::SetSpectrogramSegmentLength(segment_length) => {
+                self.spectrogram_settings.segment_length = segment_length;
+                Command::none()
+            }
+            Message::SetSpectrogramSampleRate(sample_rate) => {
+                self.spectrogram_settings.sample_rate_string = sample_rate.clone();
+                if sample_rate.trim().is_empty() {
+                    self.spectrogram_settings.sample_rate = None;
+                } else {
+                    match sample_rate.trim().parse::<f64>() {
+                        Ok(number) if number > 0.0 => {
+                            self.spectrogram_settings.sample_rate = Some(number)
+                        }
+                        Ok(_) => println!("Sample rate must be positive."),
+                        Err(e) => println!("Error parsing the sample rate. {:#?}", e),
+                    }
+                }
+                Command::none()
+            }
+            Message::SpectrogramComputed(spectrogram) => {
+                self.spectrogram = spectrogram;
+                self.spectrogram_computing = false;
+                self.spectrogram_cache.clear();
+                Command::none()
+            }
+            Message::SetStepChannel(channel) => {
+                self.step_settings.channel = Some(channel);
+                Command::none()
+            }
+            Message::SetStepReference(channel) => {
+                self.step_settings.reference = Some(channel);
+                Command::none()
+            }
+            Message::SetStepSettlingBand(band) => {
+                self.step_settings.settling_band_string = band.clone();
+                let result: Result<f64, _> = band.parse();
+                match result {
+                    Ok(number) if number > 0.0 => self.step_settings.settling_band = number,
+                    Ok(_) => println!("Settling band must be positive."),
+                    Err(e) => println!("Error parsing the settling band. {:#?}", e),
+                }
+                Command::none()
+            }
+            Message::AnalyzeStep => {
+                self.analyze_step_response();
+                Command::none()
+            }
+            Message::ClearStep => {
+                self.step_response = None;
+                Command::none()
+            }
+            Message::PinReference => {
+                let (start, end) = self.get_chart_range();
+                // Trigger of the captured window is kept for the alignment by trigger
+                let trigger_sample = match (&self.display_mode, self.trigger.capture) {
+                    (Some(display_mode), Some(capture))
+                        if display_mode == "trigger" && capture.triggered =>
+                    {
+                        Some(capture.trigger_sample as f64)
+                    }
+                    _ => None,
+                };
+                self.reference = ReferenceTrace::pin(
+                    &self.signal_store,
+                    &self.device_profile,
+                    start,
+                    end,
+                    trigger_sample,
+                    format!("Snapshot {}", Local::now().format("%H:%M:%S")),
+                );
+                if self.reference.is_none() {
+                    println!("No samples to pin as the reference.");
+                }
+                Command::none()
+            }
+            Message::LoadReference => {
+                let path = FileDialog::new()
+                    .set_location("./../")
+                    .add_filter("Capture", &["csv"])
+                    .show_open_single_file();
+                match path {
+                    Ok(Some(path)) => {
+                        match ReferenceTrace::load(
+                            &path.to_string_lossy(),
+                            &self.signal_store.channel_ids(),
+                        ) {
+                            Ok(reference) => self.reference = Some(reference),
+                            Err(e) => {
+                                println!("{}", e);
+                                let _ = MessageDialog::new()
+                                    .set_title("Error loading the reference!")
+                                    .set_text(&e)
+                                    .set_type(native_dialog::MessageType::Error)
+                                    .show_alert();
+                            }
+                        }
+                    }
+                    Ok(None) => {}
+                    Err(e) => println!("Error opening the file dialog {:#?}", e),
+                }
+                Command::none()
+            }
+            Message::ClearReference => {
+                self.reference = None;
+                self.reference_settings.difference_channel = None;
+                Command::none()
+            }
+            Message::SetReferenceAlignment(alignment) => {
+                self.reference_settings.alignment = alignment;
+                Command::none()
+            }
+            Message::SetReferenceOffset(offset) => {
+                self.reference_settings.offset_string = offset.clone();
+                let result: Result<f64, _> = offset.parse();
+                match result {
+                    Ok(number) => self.reference_settings.offset = number,
+                    Err(e) => println!("Error parsing the reference offset. {:#?}", e),
+                }
+                Command::none()
+            }
+            Message::SetReferenceDifference(channel) => {
+                self.reference_settings.difference_channel = channel;
+                Command::none()
+            }
+            Message::SetHistogramChannel(channel) => {
+                self.histogram_settings.channel = Some(channel);
+                Command::none()
+            }
+            Message::SetHistogramScope(scope) => {
+                self.histogram_settings.scope = scope;
+                Command::none()
+            }
+            Message::SetHistogramBins(bins) => {
+                self.histogram_settings.bins_string = bins.clone();
+                let result: Result<usize, _> = bins.parse();
+                match result {
+                    Ok(number) if number > 0 => self.histogram_settings.bins = number,
+                    Ok(_) => println!("Histogram must have at least one bin."),
+                    Err(e) => println!("Error parsing the number of bins. {:#?}", e),
+                }
+                Command::none()
+            }
+            Message::SetHistogramRangeMin(min) => {
+                self.histogram_settings.range_min_string = min.clone();
+                self.histogram_settings.range_min = if min.trim().is_empty() {
+                    None
+                } else {
+                    match min.trim().parse::<f64>() {
+                        Ok(number) => Some(number),
+                        Err(e) => {
+                            println!("Error parsing the histogram min. {:#?}", e);
+                            self.histogram_settings.range_min
+                        }
+                    }
+                };
+                Command::none()
+            }
+            Message::SetHistogramRangeMax(max) => {
+                self.histogram_settings.range_max_string = max.clone();
+                self.histogram_settings.range_max = if max.trim().is_empty() {
+                    None
+                } else {
+                    match max.trim().parse::<f64>() {
+                        Ok(number) => Some(number),
+                        Err(e) => {
+                            println!("Error parsing the histogram max. {:#?}", e);
+                            self.histogram_settings.range_max
+                        }
+                    }
+                };
+                Command::none()
+            }
+            Message::SetXyChannelX(channel) => {
+                self.xy_settings.x_channel = Some(channel);
+                Command::none()
+            }
+            Message::SetXyChannelY(channel) => {
+                self.xy_settings.y_channel = Some(channel);
+                Command::none()
+            }
+            Message::SetXyPersistence(persistence) => {
+                self.xy_settings.persistence_string = persistence.clone();
+                let result: Result<usize, _> = persistence.parse();
+                match result {
+                    Ok(number) if number > 0 => self.xy_settings.persistence = number,
+                    Ok(_) => println!("Persistence must be at least one sample."),
+                    Err(e) => println!("Error parsing the persistence. {:#?}", e),
+                }
+                Command::none()
+            }
+            Message::SetXyFade(fade) => {
+                self.xy_settings.fade = fade;
+                Command::none()
+            }
+            Message::SetXyEqualAspect(equal_aspect) => {
+                self.xy_settings.equal_aspect = equal_aspect;
+                Command::none()
+            }
+            Message::SetXyAngleChannel(channel) => {
+                self.xy_settings.angle_channel = Some(channel);
+                Command::none()
+            }
+            Message::SetXySpaceVector(space_vector) => {
+                self.xy_settings.space_vector = space_vector;
+                Command::none()
+            }
+            Message::AddTransform => {
+                self.device_profile
+                    .transforms
+                    .push(DriveTransform::new(TransformKind::default()));
+                Command::none()
+            }
+            Message::RemoveTransform(index) => {
+                if index < self.device_profile.transforms.len() {
+                    self.device_profile.transforms.remove(index);
+                }
+                Command::none()
+            }
+            Message::SetTransformKind(index, kind) => {
+                if let Some(transform) = self.device_profile.transforms.get_mut(index) {
+                    transform.set_kind(kind);
+                }
+                Command::none()
+            }
+            Message::SetTransformInput(index, input, id) => {
+                if let Some(transform) = self.device_profile.transforms.get_mut(index) {
+                    transform.inputs[input] = id.trim().to_string();
+                }
+                Command::none()
+            }
+            Message::SetTransformOutput(index, output, id) => {
+                if let Some(transform) = self.device_profile.transforms.get_mut(index) {
+                    transform.outputs[output] = id.trim().to_string();
+                }
+                Command::none()
+            }
+            Message::AddExpression => {
+                self.device_profile.add_expression();
+                Command::none()
+            }
+            Message::RemoveExpression(index) => {
+                self.device_profile.remove_expression(index);
+                Command::none()
+            }
+            Message::SetExpression(index, expression) => {
+                self.device_profile.set_expression(index, expression);
+                Command::none()
+            }
+            Message::AddBitField => {
+                self.device_profile.bit_fields.push(BitField::default());
+                Command::none()
+            }
+            Message::RemoveBitField(index) => {
+                if index < self.device_profile.bit_fields.len() {
+                    self.device_profile.bit_fields.remove(index);
+                }
+                Command::none()
+            }
+            Message::SetBitFieldField(index, field, value) => {
+                if let Some(bit_field) = self.device_profile.bit_fields.get_mut(index) {
+                    bit_field.set_field(field, value);
+                }
+                Command::none()
+            }
+            Message::AddFilter => {
+                self.device_profile
+                    .filters
+                    .push(ChannelFilter::new(FilterKind::default()));
+                Command::none()
+            }
+            Message::RemoveFilter(index) => {
+                if index < self.device_profile.filters.len() {
+                    self.device_profile.filters.remove(index);
+                }
+                Command::none()
+            }
+            Message::SetFilterKind(index, kind) => {
+                if let Some(filter) = self.device_profile.filters.get_mut(index) {
+                    filter.set_kind(kind);
+                }
+                Command::none()
+            }
+            Message::SetFilterField(index, field, value) => {
+                if let Some(filter) = self.device_profile.filters.get_mut(index) {
+                    filter.set_field(field, value);
+                }
+                Command::none()
+            }
+            Message::AddAlarm => {
+                self.device_profile.alarms.push(AlarmRule::default());
+                Command::none()
+            }
+            Message::RemoveAlarm(index) => {
+                if index < self.device_profile.alarms.len() {
+                    self.device_profile.alarms.remove(index);
+                }
+                Command::none()
+            }
+            Message::SetAlarmCondition(index, condition) => {
+                if let Some(rule) = self.device_profile.alarms.get_mut(index) {
+                    rule.condition = condition;
+                    rule.reset();
+                }
+                Command::none()
+            }
+            Message::SetAlarmSeverity(index, severity) => {
+                if let Some(rule) = self.device_profile.alarms.get_mut(index) {
+                    rule.severity = severity;
+                }
+                Command::none()
+            }
+            Message::SetAlarmField(index, field, value) => {
+                if let Some(rule) = self.device_profile.alarms.get_mut(index) {
+                    rule.set_field(field, value);
+                }
+                Command::none()
+            }
+            Message::SetAlarmAction(index, action, enabled) => {
+                if let Some(rule) = self.device_profile.alarms.get_mut(index) {
+                    rule.set_action(action, enabled);
+                }
+                Command::none()
+            }
+            Message::AlarmBufferSaved(file_name, result) => {
+                // The events of the alarm are updated when the file is written
+                let saving = format!("Saving to {}", file_name);
+                let saved = match result {
+                    Ok(()) => format!("Saved to {}", file_name),
+                    Err(e) => {
+                        println!("Error saving the buffer of the alarm {:#?}", e);
+                        format!("Saving to {} failed", file_name)
+                    }
+                };
+                for event in self.alarm_log.events.iter_mut() {
+                    event.message = event.message.replace(&saving, &saved);
+                }
+                Command::none()
+            }
+            Message::ClearAlarmLog => {
+                self.alarm_log.clear();
+                Command::none()
+            }
+            Message::ToggleHold => {
+                self.chart_hold = match self.chart_hold {
+                    Some(_) => {
+                        // Releasing the hold returns to the live data
+                        self.chart_zoom = ChartZoom::default();
+                        self.history_view.end = None;
+                        None
+                    }
+                    None => Some(self.get_chart_range()),
+                };
+                Command::none()
+            }
+            Message::ResetZoom => {
+                if self.chart_zoom.is_zoomed() {
+                    self.chart_zoom_history
+                        .push(std::mem::take(&mut self.chart_zoom));
+                }
+                for pane_settings in self.navigation_layout.get_pane_layout_mut().panes.iter_mut() {
+                    pane_settings.reset_zoom();
+                }
+                self.reset_sticky_ranges();
+                Command::none()
+            }
+            Message::ChartViewportChanged(pane, viewport) => {
+                self.chart_zoom_history.push(self.chart_zoom.clone());
+                self.chart_zoom.set(pane, viewport);
+                if let Some(chart_pane) = self.chart_panes.get_mut(pane) {
+                    chart_pane.box_selection = None;
+                }
+                Command::none()
+            }
+            Message::ChartPanMoved(pane, viewport) => {
+                self.chart_zoom.set(pane, viewport);
+                Command::none()
+            }
+            Message::ChartBoxSelecting(pane, box_selection) => {
+                if let Some(chart_pane) = self.chart_panes.get_mut(pane) {
+                    chart_pane.box_selection = box_selection;
+                }
+                Command::none()
+            }
+            Message::SetPaneCount(count) => {
+                let pane_layout = self.navigation_layout.get_pane_layout_mut();
+                pane_layout.pane_count_string = count.clone();
+                let result: Result<usize, _> = count.parse();
+                match result {
+                    Ok(number) if number > 0 => pane_layout.pane_count = number,
+                    Ok(_) => println!("Number of chart panes must be higher than 0."),
+                    Err(e) => println!("Error parsing the number of chart panes. {:#?}", e),
+                }
+                Command::none()
+            }
+            Message::AssignChannelPane(channel, pane_name) => {
+                let pane_layout = self.navigation_layout.get_pane_layout_mut();
+                if let Some(pane) = pane_layout
+                    .get_pane_names()
+                    .iter()
+                    .position(|name| *name == pane_name)
+                {
+                    pane_layout.channel_panes.insert(channel, pane);
+                }
+                Command::none()
+            }
+            Message::SelectPane(pane_name) => {
+                if let Some(pane) = self
+                    .navigation_layout
+                    .get_pane_layout()
+                    .get_pane_names()
+                    .iter()
+                    .position(|name| *name == pane_name)
+                {
+                    self.selected_pane = pane;
+                }
+                Command::none()
+            }
+            Message::ToggleMeasurementCursors => {
+                let pane = self.get_selected_pane();
+                if let Some(chart_pane) = self.chart_panes.get_mut(pane) {
+                    let viewport = chart_pane.viewport;
+                    chart_pane.cursors.toggle(viewport);
+                }
+                Command::none()
+            }
+            Message::MoveMeasurementCursor(pane, handle, value) => {
+                if let Some(chart_pane) = self.chart_panes.get_mut(pane) {
+                    chart_pane.cursors.move_cursor(handle, value);
+                }
+                Command::none()
+            }
+            Message::UndoZoom => {
+                if let Some(zoom) = self.chart_zoom_history.pop() {
+                    self.chart_zoom = zoom;
+                }
+                Command::none()
+            }
+            Message::SetRenderFps(fps) => {
+                self.render_settings.fps_string = fps.clone();
+                let result: Result<u32, _> = fps.parse();
+                match result {
+                    Ok(number) if number > 0 => self.render_settings.fps = number,
+                    Ok(_) => println!("Render FPS must be higher than 0."),
+                    Err(e) => println!("Error parsing the render FPS. {:#?}", e),
+                }
+
+                Command::none()
+            }
+        }
+    }
+
+    fn subscription(&self) -> iced::Subscription<Self::Message> {
+        // Subscription of frontend to the backend data
+        // the channels are initialized before running the GUI in the main function
+        let broadcast_listener_subscription = iced::subscription::unfold(
+            "broadcast listener main",
+            self.broadcast_data_bf_channel.rx.take(),
+            move |mut receiver| async move {
+                let message = receiver.as_mut().unwrap().recv().await.unwrap();
+                (Message::ExternalDataReceived(message), receiver)
+            },
+        );
+
+        // Render tick redrawing the chart at the selected FPS
+        let render_tick_subscription = iced::time::every(std::time::Duration::from_secs_f64(
+            1.0 / self.render_settings.fps as f64,
+        ))
+        .map(Message::RenderTick);
+
+        // Marker dropped by the M key, the key is not passed here while a text input is focused
+        let marker_hotkey_subscription = iced::keyboard::on_key_press(|key, modifiers| {
+            match key.as_ref() {
+                iced::keyboard::Key::Character("m") if modifiers.is_empty() => {
+                    Some(Message::AddLiveMarker)
+                }
+                _ => None,
+            }
+        });
+
+        // Batch is here used to be able to add more subscriptions later
+        iced::Subscription::batch(vec![
+            broadcast_listener_subscription,
+            render_tick_subscription,
+            marker_hotkey_subscription,
+        ])
+    }
+
+    fn view(&self) -> iced::Element<'_, Self::Message> {
+
+        // Changing themes
+        let theme_widget: PickList<'_, Theme, &[Theme], Theme, Message> =
+            iced::widget::pick_list::PickList::new(
+                iced::Theme::ALL,
+                Some(self.theme.clone()),
+                Message::ThemeChanged,
+            );
+
+        // Chain of action used to initialize all needed to fetch data
+        let initialize_rtm_button: Button<Message> = Button::new("Initialize RTM")
+            .style(theme::Button::Destructive)
+            .on_press(Message::InitializeRtm);
+
+        // Changing dekstops
+        let desktop_selection_title = Text::<'_, Theme>::new("Desktop");
+        let desktop_selection_widget: PickList<'_, String, &[String], String, Message> =
+            iced::widget::pick_list::PickList::new(
+                self.navigation_layout.available_desktops.as_ref(),
+                self.navigation_layout.selected_desktop.clone(),
+                Message::ChangeDesktop,
+            );
+        let desktop_selection_widget_debug_free: PickList<'_, String, &[String], String, Message> =
+            iced::widget::pick_list::PickList::new(
+                self.navigation_layout.available_desktops.as_ref(),
+                self.navigation_layout.selected_desktop.clone(),
+                Message::ChangeDesktop,
+            );
+
+        // Running buffer settings
+        let running_buffer_size_text_info_widget = Text::<'_, Theme>::new(format!(
+            "Running Buffer Size: {}",
+            self.running_buffer.size
+        ));
+
+        // Baud rate settings
+        let baud_rate_text_info_widget = Text::<'_, Theme>::new(format!(
+            "BaudRate: {}",
+            self.serial_ports_available.baud_rate_string
+        ));
+
+        // Printing out selected serial port
+        let serial_port_text_info_widget = Text::<'_, Theme>::new(format!(
+            "SerialPort: {}",
+            self.serial_ports_available.selected
+        ));
+
+
+        // Running buffer
+        let text_progress_bar = Text::<'_, Theme>::new("Running buffer");
+
+        // Number of samples in the running buffer, the signal store keeps all the samples, so
+        // only the last running_buffer.size samples are displayed
+        let running_buffer_filled =
+            (self.signal_store.len() as u64).min(self.running_buffer.size) as f32;
+
+        let text_progress_bar_value = Text::<'_, Theme>::new(format!(
+            "Progress value: {:#?}%",
+            100.0 * running_buffer_filled / self.running_buffer.size as f32
+        ));
+
+        // Displaying X samples from x axis
+        let x_axis_data_internal = self.signal_store.len().saturating_sub(1);
+
+        // Range of the samples displayed in the chart based on the display mode
+        let (_chart_range_start, chart_range_end) = self.get_chart_range();
+
+        // Scrolling back through the history in the default mode
+        let history_span_title = Text::<'_, Theme>::new("History span");
+
+        let history_span_input_widget: TextInput<'_, Message> =
+            TextInput::new("Whole run", &self.history_view.span_string)
+                .on_input(Message::SetHistorySpan)
+                .width(125);
+
+        let history_position_widget = iced::widget::Slider::new(
+            0.0..=self.signal_store.len() as f64,
+            chart_range_end as f64,
+            Message::SetHistoryPosition,
+        );
+
+        let history_live_button: Button<Message> =
+            Button::new("Live").on_press(Message::FollowLiveHistory);
+
+        // Selection mode default | debug
+        let selection_mode_title = Text::<'_, Theme>::new("Selection mode");
+
+        let mode_selection_widget: PickList<'_, String, &[String], String, Message, _, _> =
+            iced::widget::pick_list::PickList::new(
+                self.display_mode_select_values.as_ref(),
+                self.display_mode.clone(),
+                Message::SetDisplayMode,
+            );
+
+        // The Y axis, decimation and cursor controls edit the selected pane
+        let pane_layout = self.navigation_layout.get_pane_layout();
+        let selected_pane = self.get_selected_pane();
+        let pane_settings = pane_layout.get_pane_settings(selected_pane);
+        let pane_selection_widget = iced::widget::pick_list::PickList::new(
+            pane_layout.get_pane_names(),
+            pane_layout.get_pane_names().get(selected_pane).cloned(),
+            Message::SelectPane,
+        );
+
+        // Selecting the decimation of the traces in the chart
+        let decimation_title = Text::<'_, Theme>::new("Decimation");
+
+        let decimation_selection_widget: PickList<'_, DecimationMode, &[DecimationMode], DecimationMode, Message> =
+            iced::widget::pick_list::PickList::new(
+                &DecimationMode::ALL[..],
+                Some(pane_settings.decimation),
+                Message::SetDecimationMode,
+            );
+
+        // Selecting the range of the Y axis of the chart
+        let y_scale_selection_widget: PickList<'_, YScaleMode, &[YScaleMode], YScaleMode, Message> =
+            iced::widget::pick_list::PickList::new(
+                &YScaleMode::ALL[..],
+                Some(pane_settings.y_scale),
+                Message::SetYScaleMode,
+            );
+
+        let y_manual_min_input_widget: TextInput<'_, Message> =
+            TextInput::new("Y min", &pane_settings.y_manual_min_string)
+                .on_input(Message::SetYManualMin)
+                .width(100);
+
+        let y_manual_max_input_widget: TextInput<'_, Message> =
+            TextInput::new("Y max", &pane_settings.y_manual_max_string)
+                .on_input(Message::SetYManualMax)
+                .width(100);
+
+        let reset_zoom_button: Button<Message> =
+            Button::new("Reset zoom").on_press(Message::ResetZoom);
+
+        let undo_zoom_button: Button<Message> =
+            Button::new("Undo zoom").on_press(Message::UndoZoom);
+
+        let measurement_cursors_button: Button<Message> =
+            Button::new("Cursors").on_press(Message::ToggleMeasurementCursors);
+
+        let hold_button: Button<Message> = match self.chart_hold {
+            Some(_) => Button::new("Release").style(theme::Button::Destructive),
+            None => Button::new("Hold"),
+        }
+        .on_press(Message::ToggleHold);
+
+        let hold_button_xy: Button<Message> = match self.chart_hold {
+            Some(_) => Button::new("Release").style(theme::Button::Destructive),
+            None => Button::new("Hold"),
+        }
+        .on_press(Message::ToggleHold);
+
+        // Trigger settings used in the trigger mode
+        let trigger_channel_widget: PickList<'_, String, Vec<String>, String, Message, _, _> =
+            iced::widget::pick_list::PickList::new(
+                self.signal_store.channel_ids(),
+                self.trigger.channel.clone(),
+                Message::SetTriggerChannel,
+            )
+            .placeholder("Channel");
+
+        let trigger_edge_widget: PickList<'_, TriggerEdge, &[TriggerEdge], TriggerEdge, Message, _, _> =
+            iced::widget::pick_list::PickList::new(
+                &TriggerEdge::ALL[..],
+                Some(self.trigger.edge),
+                Message::SetTriggerEdge,
+            );
+
+        let trigger_level_input_widget: TextInput<'_, Message> =
+            TextInput::new("Level", &self.trigger.level_string)
+                .on_input(Message::SetTriggerLevel)
+                .width(100);
+
+        let trigger_pre_samples_input_widget: TextInput<'_, Message> =
+            TextInput::new("Pre samples", &self.trigger.pre_samples_string)
+                .on_input(Message::SetTriggerPreSamples)
+                .width(100);
+
+        let trigger_post_samples_input_widget: TextInput<'_, Message> =
+            TextInput::new("Post samples", &self.trigger.post_samples_string)
+                .on_input(Message::SetTriggerPostSamples)
+                .width(100);
+
+        let trigger_arming_widget: PickList<'_, TriggerArming, &[TriggerArming], TriggerArming, Message, _, _> =
+            iced::widget::pick_list::PickList::new(
+                &TriggerArming::ALL[..],
+                Some(self.trigger.arming),
+                Message::SetTriggerArming,
+            );
+
+        let arm_trigger_button: Button<Message> =
+            Button::new("Arm").on_press(Message::ArmTrigger);
+
+        let trigger_status_widget = Text::<'_, Theme>::new(self.trigger.get_status());
+
+        let statistics_button: Button<Message> =
+            Button::new("Statistics").on_press(Message::ToggleStatistics);
+
+        let statistics_scope_widget: PickList<'_, StatisticsScope, &[StatisticsScope], StatisticsScope, Message, _, _> =
+            iced::widget::pick_list::PickList::new(
+                &StatisticsScope::ALL[..],
+                Some(self.chart_settings.statistics_scope),
+                Message::SetStatisticsScope,
+            );
+
+        let markers_button: Button<Message> =
+            Button::new("Markers").on_press(Message::ToggleMarkers);
+
+        // Side panel with the markers of the capture, the M key drops a marker at the received
+        // sample and the marker buttons move the displayed range to the marker
+        let mut markers_column = Column::new()
+            .spacing(5)
+            .width(280)
+            .push(
+                TextInput::new("Note of the next marker", &self.markers.note)
+                    .on_input(Message::SetMarkerNote)
+                    .on_submit(Message::AddLiveMarker),
+            )
+            .push(
+                Row::new()
+                    .push(Button::new("Mark now").on_press(Message::AddLiveMarker))
+                    .push(Button::new("Add at X1").on_press(Message::AddMarker))
+                    .push(Button::new("Export CSV").on_press(Message::ExportCsv))
+                    .spacing(5),
             );
-        let desktop_selection_widget_debug_free: PickList<'_, String, &[String], String, Message> =
+        for (index, marker) in self.markers.markers.iter().enumerate() {
+            markers_column = markers_column
+                .push(
+                    Row::new()
+                        .push(
+                            Button::new(Text::<'_, Theme>::new(format!("{}", marker.sample)).size(13))
+                                .on_press(Message::JumpToMarker(index)),
+                        )
+                        .push(Text::<'_, Theme>::new(marker.time.clone()).size(13))
+                        .push(
+                            Button::new(Text::<'_, Theme>::new("×").size(13))
+                                .style(theme::Button::Destructive)
+                                .on_press(Message::RemoveMarker(index)),
+                        )
+                        .spacing(5)
+                        .align_items(iced::Alignment::Center),
+                )
+                .push(
+                    TextInput::new("Note", &marker.note)
+                        .on_input(move |note| Message::SetExistingMarkerNote(index, note))
+                        .on_submit(Message::SubmitMarkerNote)
+                        .size(13),
+                );
+        }
+
+        // Side panel with the statistics of the channels next to the chart panes
+        let mut statistics_column = Column::new().spacing(2).width(280);
+        for (label, statistics) in self.chart_statistics.iter() {
+            statistics_column = statistics_column
+                .push(Text::<'_, Theme>::new(label.clone()).size(15))
+                .push(
+                    Text::<'_, Theme>::new(format!(
+                        "Min {} | Max {} | P-P {}",
+                        format_axis_value(statistics.min),
+                        format_axis_value(statistics.max),
+                        format_axis_value(statistics.peak_to_peak()),
+                    ))
+                    .size(13),
+                )
+                .push(
+                    Text::<'_, Theme>::new(format!(
+                        "Mean {} | RMS {} | σ {}",
+                        format_axis_value(statistics.mean),
+                        format_axis_value(statistics.rms()),
+                        format_axis_value(statistics.standard_deviation()),
+                    ))
+                    .size(13),
+                )
+                .push(Text::<'_, Theme>::new(format!("Samples {}", statistics.count)).size(13))
+                .push(iced::widget::Space::new(0, 5));
+        }
+
+        // Stacked chart panes with the synchronised X axis
+        let mut chart_panes_column = Column::new()
+            .spacing(5)
+            .width(Length::Fill)
+            .height(Length::Fill);
+        for (pane, chart_pane) in self.chart_panes.iter().enumerate() {
+            chart_panes_column = chart_panes_column.push(gui::DefaultChart::view(
+                DefaultChart::new(
+                    pane,
+                    chart_pane,
+                    self.theme.clone(),
+                    self.chart_settings.clone(),
+                    pane_layout.get_pane_settings(pane),
+                    &self.device_profile,
+                ),
+            ));
+        }
+        // Logic analyzer lanes under the chart panes, so the state transitions are next to the
+        // analog signals
+        if !self.logic_lanes.is_empty() {
+            chart_panes_column = chart_panes_column.push(
+                LogicChart {
+                    lanes: &self.logic_lanes,
+                    x_range: self.chart_panes.first().map_or((0.0, 1.0), |pane| pane.viewport.x),
+                    theme: self.theme.clone(),
+                    cache: &self.logic_cache,
+                }
+                .view(),
+            );
+        }
+        let chart_panes_row = Row::new()
+            .push(chart_panes_column)
+            .push_maybe(
+                self.chart_settings
+                    .show_statistics
+                    .then_some(iced::widget::Scrollable::new(statistics_column)),
+            )
+            .push_maybe(
+                self.chart_settings
+                    .show_markers
+                    .then_some(iced::widget::Scrollable::new(markers_column)),
+            )
+            .spacing(10)
+            .width(Length::Fill)
+            .height(Length::Fill);
+
+        // Number of the chart panes and the assignment of the channels to them
+        let pane_layout = self.navigation_layout.get_pane_layout();
+        let pane_names = pane_layout.get_pane_names();
+        let mut pane_layout_column = Column::new().spacing(5).push(
+            Row::new()
+                .push(Text::<'_, Theme>::new("Chart panes"))
+                .push(
+                    TextInput::new("Panes", &pane_layout.pane_count_string)
+                        .on_input(Message::SetPaneCount)
+                        .width(100),
+                )
+                .spacing(15)
+                .align_items(iced::Alignment::Center),
+        );
+        for channel_id in self.signal_store.channel_ids() {
+            let selected_pane = pane_names.get(pane_layout.get_pane(&channel_id)).cloned();
+            let channel = channel_id.clone();
+            pane_layout_column = pane_layout_column.push(
+                Row::new()
+                    .push(Text::<'_, Theme>::new(channel_id).width(100))
+                    .push(iced::widget::pick_list::PickList::new(
+                        pane_names.clone(),
+                        selected_pane,
+                        move |pane| Message::AssignChannelPane(channel.clone(), pane),
+                    ))
+                    .spacing(15)
+                    .align_items(iced::Alignment::Center),
+            );
+        }
+
+        // Derived channels of the device profile computed from the received channels
+        let mut transform_column = Column::new().spacing(5).push(
+            Row::new()
+                .push(Text::<'_, Theme>::new("Transforms"))
+                .push(Button::new("Add").on_press(Message::AddTransform))
+                .spacing(15)
+                .align_items(iced::Alignment::Center),
+        );
+        for (index, transform) in self.device_profile.transforms.iter().enumerate() {
+            let mut transform_row = Row::new()
+                .push(iced::widget::pick_list::PickList::new(
+                    &TransformKind::ALL[..],
+                    Some(transform.kind),
+                    move |kind| Message::SetTransformKind(index, kind),
+                ))
+                .spacing(10)
+                .align_items(iced::Alignment::Center);
+            for (input, name) in transform.kind.get_input_names().iter().enumerate() {
+                if name.is_empty() {
+                    continue;
+                }
+                transform_row = transform_row.push(
+                    TextInput::new(name, &transform.inputs[input])
+                        .on_input(move |id| Message::SetTransformInput(index, input, id))
+                        .width(120),
+                );
+            }
+            transform_row = transform_row.push(Text::<'_, Theme>::new("→"));
+            for output in 0..transform.outputs.len() {
+                transform_row = transform_row.push(
+                    TextInput::new("Output ID", &transform.outputs[output])
+                        .on_input(move |id| Message::SetTransformOutput(index, output, id))
+                        .width(120),
+                );
+            }
+            transform_row = transform_row.push(
+                Button::new("Remove")
+                    .style(theme::Button::Destructive)
+                    .on_press(Message::RemoveTransform(index)),
+            );
+            transform_column = transform_column.push(transform_row);
+        }
+
+        // Virtual channels of the device profile defined by expressions
+        let mut expression_column = Column::new().spacing(5).push(
+            Row::new()
+                .push(Text::<'_, Theme>::new("Virtual channels"))
+                .push(Button::new("Add").on_press(Message::AddExpression))
+                .push(
+                    Text::<'_, Theme>::new(
+                        "name = expression, e.g. p = @000 * @001, i_rms = sqrt(avg(@002^2, 1000))",
+                    )
+                    .size(13),
+                )
+                .spacing(15)
+                .align_items(iced::Alignment::Center),
+        );
+        for (index, expression) in self.device_profile.expressions.iter().enumerate() {
+            let expression_row = Row::new()
+                .push(
+                    TextInput::new("name = expression", expression)
+                        .on_input(move |expression| Message::SetExpression(index, expression))
+                        .width(500),
+                )
+                .push(
+                    Button::new("Remove")
+                        .style(theme::Button::Destructive)
+                        .on_press(Message::RemoveExpression(index)),
+                )
+                .push_maybe(
+                    self.device_profile
+                        .get_expression_error(index)
+                        .map(|error| Text::<'_, Theme>::new(error.to_string()).size(13)),
+                )
+                .spacing(10)
+                .align_items(iced::Alignment::Center);
+            expression_column = expression_column.push(expression_row);
+        }
+
+        // Named bits and multi-bit fields of the status words
+        let mut bit_field_column = Column::new().spacing(5).push(
+            Row::new()
+                .push(Text::<'_, Theme>::new("Bit fields"))
+                .push(Button::new("Add").on_press(Message::AddBitField))
+                .spacing(15)
+                .align_items(iced::Alignment::Center),
+        );
+        for (index, bit_field) in self.device_profile.bit_fields.iter().enumerate() {
+            let mut bit_field_row = Row::new()
+                .spacing(10)
+                .align_items(iced::Alignment::Center);
+            for field in BitFieldField::ALL {
+                let width = match field {
+                    BitFieldField::Offset | BitFieldField::Width => 60,
+                    BitFieldField::Labels => 300,
+                    _ => 120,
+                };
+                bit_field_row = bit_field_row.push(
+                    TextInput::new(&field.to_string(), &bit_field.get_input(field))
+                        .on_input(move |value| Message::SetBitFieldField(index, field, value))
+                        .width(width),
+                );
+            }
+            bit_field_row = bit_field_row.push(
+                Button::new("Remove")
+                    .style(theme::Button::Destructive)
+                    .on_press(Message::RemoveBitField(index)),
+            );
+            bit_field_column = bit_field_column.push(bit_field_row);
+        }
+
+        // Limit alarm rules of the channels with their severity and actions
+        let mut alarm_rule_column = Column::new().spacing(5).push(
+            Row::new()
+                .push(Text::<'_, Theme>::new("Alarm rules"))
+                .push(Button::new("Add").on_press(Message::AddAlarm))
+                .spacing(15)
+                .align_items(iced::Alignment::Center),
+        );
+        for (index, rule) in self.device_profile.alarms.iter().enumerate() {
+            let mut alarm_rule_row = Row::new()
+                .push(
+                    TextInput::new(
+                        &AlarmField::Channel.to_string(),
+                        &rule.get_input(AlarmField::Channel),
+                    )
+                    .on_input(move |value| Message::SetAlarmField(index, AlarmField::Channel, value))
+                    .width(120),
+                )
+                .push(iced::widget::pick_list::PickList::new(
+                    &AlarmCondition::ALL[..],
+                    Some(rule.condition),
+                    move |condition| Message::SetAlarmCondition(index, condition),
+                ))
+                .spacing(10)
+                .align_items(iced::Alignment::Center);
+            for field in rule.condition.get_parameters() {
+                let field = *field;
+                alarm_rule_row = alarm_rule_row.push(
+                    TextInput::new(&field.to_string(), &rule.get_input(field))
+                        .on_input(move |value| Message::SetAlarmField(index, field, value))
+                        .width(110),
+                );
+            }
+            alarm_rule_row = alarm_rule_row.push(iced::widget::pick_list::PickList::new(
+                &AlarmSeverity::ALL[..],
+                Some(rule.severity),
+                move |severity| Message::SetAlarmSeverity(index, severity),
+            ));
+            for action in AlarmAction::ALL {
+                alarm_rule_row = alarm_rule_row.push(
+                    iced::widget::Checkbox::new(action.to_string(), rule.get_action(action))
+                        .on_toggle(move |enabled| Message::SetAlarmAction(index, action, enabled)),
+                );
+            }
+            let state = if rule.active { "ACTIVE" } else { "ok" };
+            alarm_rule_row = alarm_rule_row
+                .push(Text::<'_, Theme>::new(state).width(60))
+                .push(
+                    Button::new("Remove")
+                        .style(theme::Button::Destructive)
+                        .on_press(Message::RemoveAlarm(index)),
+                );
+            alarm_rule_column = alarm_rule_column.push(alarm_rule_row);
+        }
+
+        // Event log of the alarms, the newest event is on the top
+        let mut alarm_log_column = Column::new().spacing(3);
+        for event in self.alarm_log.events.iter().rev().filter(|event| event.log) {
+            let color = get_alarm_color(event.severity);
+            alarm_log_column = alarm_log_column.push(
+                Text::<'_, Theme>::new(format!(
+                    "{} | sample {} | {} | {}",
+                    event.time.format("%Y-%m-%d %H:%M:%S%.3f"),
+                    event.sample,
+                    event.severity,
+                    event.message,
+                ))
+                .size(14)
+                .style(match event.raised {
+                    true => iced::Color::from_rgb8(color.0, color.1, color.2),
+                    false => iced::Color::from_rgb8(128, 128, 128),
+                }),
+            );
+        }
+        let alarm_log_header_row = Row::new()
+            .push(Text::<'_, Theme>::new(format!(
+                "Event log ({} events)",
+                self.alarm_log.events.iter().filter(|event| event.log).count()
+            )))
+            .push(Button::new("Clear log").on_press(Message::ClearAlarmLog))
+            .spacing(15)
+            .align_items(iced::Alignment::Center);
+
+        // Banner of the active alarms with the highlight action, displayed above the charts, the
+        // most severe alarm first
+        let mut active_alarms: Vec<&AlarmRule> = self
+            .device_profile
+            .alarms
+            .iter()
+            .filter(|rule| rule.active && rule.highlight)
+            .collect();
+        active_alarms.sort_by_key(|rule| std::cmp::Reverse(rule.severity));
+        let alarm_banner = (!active_alarms.is_empty()).then(|| {
+            let mut alarm_banner_column = Column::new().spacing(2);
+            for rule in active_alarms {
+                let color = get_alarm_color(rule.severity);
+                alarm_banner_column = alarm_banner_column.push(
+                    Text::<'_, Theme>::new(format!(
+                        "ALARM {}: {}",
+                        rule.severity,
+                        rule.describe()
+                    ))
+                    .style(iced::Color::from_rgb8(color.0, color.1, color.2)),
+                );
+            }
+            alarm_banner_column
+        });
+
+        // Filtered copies of the channels, the raw channels stay available
+        let mut filter_column = Column::new().spacing(5).push(
+            Row::new()
+                .push(Text::<'_, Theme>::new("Filters"))
+                .push(Button::new("Add").on_press(Message::AddFilter))
+                .spacing(15)
+                .align_items(iced::Alignment::Center),
+        );
+        for (index, filter) in self.device_profile.filters.iter().enumerate() {
+            let mut filter_row = Row::new()
+                .push(iced::widget::pick_list::PickList::new(
+                    &FilterKind::ALL[..],
+                    Some(filter.kind),
+                    move |kind| Message::SetFilterKind(index, kind),
+                ))
+                .spacing(10)
+                .align_items(iced::Alignment::Center);
+            let fields = [FilterField::Input, FilterField::Output];
+            for field in fields.iter().chain(filter.kind.get_parameters()) {
+                let field = *field;
+                filter_row = filter_row.push(
+                    TextInput::new(&field.to_string(), &filter.get_input(field))
+                        .on_input(move |value| Message::SetFilterField(index, field, value))
+                        .width(130),
+                );
+            }
+            filter_row = filter_row.push(
+                Button::new("Remove")
+                    .style(theme::Button::Destructive)
+                    .on_press(Message::RemoveFilter(index)),
+            );
+            filter_column = filter_column.push(filter_row);
+        }
+
+        // Device profile selection and the channel table of the channels desktop
+        let profile_row = Row::new()
+            .push(Text::<'_, Theme>::new("Device profile"))
+            .push(
+                TextInput::new("Profile name", &self.device_profile.name)
+                    .on_input(Message::SetProfileName)
+                    .width(200),
+            )
+            .push(Button::new("Save").on_press(Message::SaveProfile))
+            .push(
+                iced::widget::pick_list::PickList::new(
+                    self.saved_profiles.clone(),
+                    None::<String>,
+                    Message::LoadProfile,
+                )
+                .placeholder("Load profile"),
+            )
+            .spacing(15)
+            .align_items(iced::Alignment::Center);
+
+        let channel_table_header = Row::new()
+            .push(Text::<'_, Theme>::new("ID").width(80))
+            .push(Text::<'_, Theme>::new("Name").width(160))
+            .push(Text::<'_, Theme>::new("Unit").width(80))
+            .push(Text::<'_, Theme>::new("Color").width(100))
+            .push(Text::<'_, Theme>::new("Width").width(60))
+            .push(Text::<'_, Theme>::new("Gain").width(100))
+            .push(Text::<'_, Theme>::new("Offset").width(100))
+            .push(Text::<'_, Theme>::new("Visible"))
+            .spacing(10);
+        let mut channel_table_column = Column::new().spacing(5).push(channel_table_header);
+        for id in self.signal_store.channel_ids() {
+            let channel_input = |field: ChannelField, placeholder: &str, width: f32| {
+                let channel_id = id.clone();
+                TextInput::new(placeholder, &self.device_profile.get_input(&id, field))
+                    .on_input(move |value| {
+                        Message::SetChannelField(channel_id.clone(), field, value)
+                    })
+                    .width(width)
+            };
+            let channel_id = id.clone();
+            let channel_row = Row::new()
+                .push(Text::<'_, Theme>::new(id.clone()).width(80))
+                .push(channel_input(ChannelField::Name, "Name", 160.0))
+                .push(channel_input(ChannelField::Unit, "Unit", 80.0))
+                .push(channel_input(ChannelField::Color, "#rrggbb", 100.0))
+                .push(channel_input(ChannelField::LineWidth, "Width", 60.0))
+                .push(channel_input(ChannelField::Gain, "Gain", 100.0))
+                .push(channel_input(ChannelField::Offset, "Offset", 100.0))
+                .push(
+                    iced::widget::Checkbox::new("", self.device_profile.get_channel(&id).visible)
+                        .on_toggle(move |visible| {
+                            Message::SetChannelVisible(channel_id.clone(), visible)
+                        }),
+                )
+                .spacing(10)
+                .align_items(iced::Alignment::Center);
+            channel_table_column = channel_table_column.push(channel_row);
+        }
+
+        // Watch table with the latest values of all channels, the values are scaled by the device
+        // profile, the raw hex word is the value as it was sent by the FPGA
+        let watch_table_header = Row::new()
+            .push(Text::<'_, Theme>::new("Channel").width(200))
+            .push(Text::<'_, Theme>::new("Value").width(140))
+            .push(Text::<'_, Theme>::new("Raw hex").width(120))
+            .push(Text::<'_, Theme>::new("Min").width(120))
+            .push(Text::<'_, Theme>::new("Max").width(120))
+            .push(Text::<'_, Theme>::new("Mean").width(120))
+            .push(Text::<'_, Theme>::new("Rate [Hz]").width(100))
+            .spacing(10);
+        let mut watch_table_column = Column::new().spacing(5).push(watch_table_header);
+        for (id, entry) in self.watch_table.entries.iter() {
+            let channel = self.device_profile.get_channel(id);
+            let statistics = entry.statistics.scaled(channel.gain, channel.offset);
+            let (min, max, mean) = if statistics.is_empty() {
+                (f64::NAN, f64::NAN, f64::NAN)
+            } else {
+                (statistics.min, statistics.max, statistics.mean)
+            };
+            let raw_hex = match entry.latest_word {
+                Some(word) => format!("0x{:08X}", word),
+                None => "-".to_string(),
+            };
+            let watch_row = Row::new()
+                .push(Text::<'_, Theme>::new(self.device_profile.get_label(id)).width(200))
+                .push(
+                    Text::<'_, Theme>::new(format_axis_value(channel.scale(entry.latest)))
+                        .width(140),
+                )
+                .push(Text::<'_, Theme>::new(raw_hex).width(120))
+                .push(Text::<'_, Theme>::new(format_axis_value(min)).width(120))
+                .push(Text::<'_, Theme>::new(format_axis_value(max)).width(120))
+                .push(Text::<'_, Theme>::new(format_axis_value(mean)).width(120))
+                .push(Text::<'_, Theme>::new(format!("{:.1}", entry.update_rate)).width(100))
+                .spacing(10);
+            watch_table_column = watch_table_column.push(watch_row);
+        }
+
+        // Spectrum of one channel from the samples displayed in the chart
+        let spectrum_channel_widget: PickList<'_, String, Vec<String>, String, Message, _, _> =
             iced::widget::pick_list::PickList::new(
-                self.navigation_layout.available_desktops.as_ref(),
-                self.navigation_layout.selected_desktop.clone(),
-                Message::ChangeDesktop,
+                self.signal_store.channel_ids(),
+                self.spectrum_settings.channel.clone(),
+                Message::SetSpectrumChannel,
+            )
+            .placeholder("Channel");
+
+        let spectrum_window_widget: PickList<'_, WindowFunction, &[WindowFunction], WindowFunction, Message, _, _> =
+            iced::widget::pick_list::PickList::new(
+                &WindowFunction::ALL[..],
+                Some(self.spectrum_settings.window),
+                Message::SetSpectrumWindow,
             );
 
-        // Running buffer settings
-        let running_buffer_size_text_info_widget = Text::<'_, Theme>::new(format!(
-            "Running Buffer Size: {}",
-            self.running_buffer.size.clone()
-        ));
+        let spectrum_scale_widget: PickList<'_, MagnitudeScale, &[MagnitudeScale], MagnitudeScale, Message, _, _> =
+            iced::widget::pick_list::PickList::new(
+                &MagnitudeScale::ALL[..],
+                Some(self.spectrum_settings.scale),
+                Message::SetSpectrumScale,
+            );
 
-        // Baud rate settings
-        let baud_rate_text_info_widget = Text::<'_, Theme>::new(format!(
-            "BaudRate: {}",
-            self.serial_ports_available.baud_rate_string
-        ));
+        let spectrum_settings_row: Row<Message> = Row::new()
+            .push(spectrum_channel_widget)
+            .push(spectrum_window_widget)
+            .push(spectrum_scale_widget)
+            .push(
+                TextInput::new("Sample rate [Hz]", &self.spectrum_settings.sample_rate_string)
+                    .on_input(Message::SetSpectrumSampleRate)
+                    .width(150),
+            )
+            .push(
+                TextInput::new("Fundamental [Hz]", &self.spectrum_settings.fundamental_string)
+                    .on_input(Message::SetSpectrumFundamental)
+                    .width(150),
+            )
+            .spacing(10)
+            .align_items(Alignment::Center);
 
-        // Printing out selected serial port
-        let serial_port_text_info_widget = Text::<'_, Theme>::new(format!(
-            "SerialPort: {}",
-            self.serial_ports_available.selected
-        ));
+        let spectrum_peaks = self.spectrum.find_peaks(SPECTRUM_PEAK_COUNT);
+        let spectrum_sample_rate_source = if self.spectrum_settings.sample_rate.is_some() {
+            "inserted"
+        } else if self.spectrum_sample_rate_measured {
+            "measured"
+        } else {
+            "unknown, per sample"
+        };
+        let spectrum_thd = match self.spectrum.get_thd(self.spectrum_settings.fundamental) {
+            Some((fundamental, thd)) => format!(
+                "THD {:.3} % (fundamental {} Hz)",
+                thd,
+                format_axis_value(fundamental)
+            ),
+            None => "THD -".to_string(),
+        };
+        let spectrum_info_column = Column::new()
+            .push(Text::<'_, Theme>::new(format!(
+                "Samples {} | Sample rate {} Hz ({}) | Bin width {} Hz | {}",
+                self.spectrum_samples,
+                format_axis_value(self.spectrum.sample_rate),
+                spectrum_sample_rate_source,
+                format_axis_value(self.spectrum.get_bin_width()),
+                spectrum_thd,
+            )))
+            .push(Text::<'_, Theme>::new(format!(
+                "Peaks: {}",
+                spectrum_peaks
+                    .iter()
+                    .map(|(frequency, magnitude)| format!(
+                        "{} Hz / {}",
+                        format_axis_value(*frequency),
+                        format_axis_value(self.spectrum_settings.scale.apply(*magnitude))
+                    ))
+                    .collect::<Vec<String>>()
+                    .join(" | ")
+            )))
+            .spacing(5);
 
+        let spectrum_chart = SpectrumChart {
+            spectrum: &self.spectrum,
+            peaks: spectrum_peaks,
+            scale: self.spectrum_settings.scale,
+            theme: self.theme.clone(),
+            cache: &self.spectrum_cache,
+        }
+        .view();
 
-        // Running buffer
-        let text_progress_bar = Text::<'_, Theme>::new("Running buffer");
+        // Spectrogram of one channel computed by the worker
+        let spectrogram_channel_widget: PickList<'_, String, Vec<String>, String, Message, _, _> =
+            iced::widget::pick_list::PickList::new(
+                self.signal_store.channel_ids(),
+                self.spectrogram_settings.channel.clone(),
+                Message::SetSpectrogramChannel,
+            )
+            .placeholder("Channel");
 
-        let text_progress_bar_value = Text::<'_, Theme>::new(format!(
-            "Progress value: {:#?}%",
-            100.0 * self.new_data_points.0.len() as f32 / self.running_buffer.size.clone() as f32
+        let spectrogram_window_widget: PickList<'_, WindowFunction, &[WindowFunction], WindowFunction, Message, _, _> =
+            iced::widget::pick_list::PickList::new(
+                &WindowFunction::ALL[..],
+                Some(self.spectrogram_settings.window),
+                Message::SetSpectrogramWindow,
+            );
+
+        let spectrogram_segment_length_widget: PickList<'_, usize, &[usize], usize, Message, _, _> =
+            iced::widget::pick_list::PickList::new(
+                &SPECTROGRAM_SEGMENT_LENGTHS[..],
+                Some(self.spectrogram_settings.segment_length),
+                Message::SetSpectrogramSegmentLength,
+            );
+
+        let spectrogram_settings_row: Row<Message> = Row::new()
+            .push(spectrogram_channel_widget)
+            .push(spectrogram_window_widget)
+            .push(Text::<'_, Theme>::new("Segment"))
+            .push(spectrogram_segment_length_widget)
+            .push(
+                TextInput::new("Sample rate [Hz]", &self.spectrogram_settings.sample_rate_string)
+                    .on_input(Message::SetSpectrogramSampleRate)
+                    .width(150),
+            )
+            .spacing(10)
+            .align_items(Alignment::Center);
+
+        let spectrogram_sample_rate_source = if self.spectrogram_settings.sample_rate.is_some() {
+            "inserted"
+        } else if self.spectrogram_sample_rate_measured {
+            "measured"
+        } else {
+            "unknown, per sample"
+        };
+        let spectrogram_info_text = Text::<'_, Theme>::new(format!(
+            "Segments {} | Hop {} | Sample rate {} Hz ({}) | Colour range {} dB below {} dB",
+            self.spectrogram.columns.len(),
+            self.spectrogram.hop,
+            format_axis_value(self.spectrogram.sample_rate),
+            spectrogram_sample_rate_source,
+            SPECTROGRAM_DYNAMIC_RANGE_DB,
+            format_axis_value(self.spectrogram.get_min_max().map_or(0.0, |(_, max)| max)),
         ));
 
-        // Displaying X samples from x axis
-        let x_axis_data_internal = match self.new_data_points.0.back() {
-            Some(&x) => x,
-            None => 0,
+        let spectrogram_chart = SpectrogramChart {
+            spectrogram: &self.spectrogram,
+            theme: self.theme.clone(),
+            cache: &self.spectrogram_cache,
+        }
+        .view();
+
+        // Step response analysis of the region between the cursors or of the displayed range
+        let step_response_row = Row::new()
+            .push(Text::<'_, Theme>::new("Step response"))
+            .push(
+                iced::widget::pick_list::PickList::new(
+                    self.signal_store.channel_ids(),
+                    self.step_settings.channel.clone(),
+                    Message::SetStepChannel,
+                )
+                .placeholder("Response"),
+            )
+            .push(
+                iced::widget::pick_list::PickList::new(
+                    self.signal_store.channel_ids(),
+                    self.step_settings.reference.clone(),
+                    Message::SetStepReference,
+                )
+                .placeholder("Reference"),
+            )
+            .push(Text::<'_, Theme>::new("Band [%]"))
+            .push(
+                TextInput::new("Band", &self.step_settings.settling_band_string)
+                    .on_input(Message::SetStepSettlingBand)
+                    .width(60),
+            )
+            .push(Button::new("Analyze").on_press(Message::AnalyzeStep))
+            .push(Button::new("Clear").on_press(Message::ClearStep))
+            .height(iced::Length::Shrink)
+            .spacing(15)
+            .align_items(iced::Alignment::Center);
+
+        // Times in samples and in seconds when the sample rate is known
+        let format_step_time = |samples: f64| match self.step_sample_rate {
+            Some(sample_rate) => format!(
+                "{} samples ({} s)",
+                format_axis_value(samples),
+                format_axis_value(samples / sample_rate)
+            ),
+            None => format!("{} samples", format_axis_value(samples)),
         };
+        let step_response_readout = self.step_response.as_ref().map(|step_response| {
+            Text::<'_, Theme>::new(format!(
+                "Rise time {} | Settling time {} | Overshoot {:.2} % | Steady-state error {} | Delay {} | Initial {} → Final {}",
+                format_step_time(step_response.get_rise_time()),
+                format_step_time(step_response.get_settling_time()),
+                step_response.overshoot,
+                step_response
+                    .steady_state_error
+                    .map_or("- (no reference)".to_string(), format_axis_value),
+                step_response
+                    .delay
+                    .map_or("- (no reference)".to_string(), format_step_time),
+                format_axis_value(step_response.initial_value),
+                format_axis_value(step_response.final_value),
+            ))
+            .size(14)
+        });
 
-        // Selection mode default | debug
-        let selection_mode_title = Text::<'_, Theme>::new("Selection mode");
+        // Reference trace pinned from the displayed samples or loaded from a previous capture
+        let mut reference_row = Row::new()
+            .push(Text::<'_, Theme>::new("Reference"))
+            .push(Button::new("Pin").on_press(Message::PinReference))
+            .push(Button::new("Load").on_press(Message::LoadReference))
+            .push(iced::widget::pick_list::PickList::new(
+                &ReferenceAlignment::ALL[..],
+                Some(self.reference_settings.alignment),
+                Message::SetReferenceAlignment,
+            ))
+            .push(Text::<'_, Theme>::new("Offset"))
+            .push(
+                TextInput::new("Samples", &self.reference_settings.offset_string)
+                    .on_input(Message::SetReferenceOffset)
+                    .width(80),
+            )
+            .push(
+                iced::widget::pick_list::PickList::new(
+                    self.reference
+                        .as_ref()
+                        .map_or(Vec::new(), |reference| reference.channel_ids()),
+                    self.reference_settings.difference_channel.clone(),
+                    |id| Message::SetReferenceDifference(Some(id)),
+                )
+                .placeholder("Difference"),
+            )
+            .height(iced::Length::Shrink)
+            .spacing(15)
+            .align_items(iced::Alignment::Center);
+        if self.reference_settings.difference_channel.is_some() {
+            reference_row = reference_row
+                .push(Button::new("No difference").on_press(Message::SetReferenceDifference(None)));
+        }
+        if let Some(reference) = &self.reference {
+            reference_row = reference_row
+                .push(Text::<'_, Theme>::new(format!(
+                    "{} | {} samples | Shift {}",
+                    reference.name,
+                    reference.get_sample_count(),
+                    format_axis_value(self.get_reference_shift()),
+                )))
+                .push(Button::new("Clear").on_press(Message::ClearReference));
+        }
 
-        let mode_selection_widget: PickList<'_, String, &[String], String, Message, _, _> =
+        // Distribution of the values of one channel, e.g. for the ADC noise
+        let histogram_settings_row: Row<Message> = Row::new()
+            .push(
+                iced::widget::pick_list::PickList::new(
+                    self.signal_store.channel_ids(),
+                    self.histogram_settings.channel.clone(),
+                    Message::SetHistogramChannel,
+                )
+                .placeholder("Channel"),
+            )
+            .push(iced::widget::pick_list::PickList::new(
+                &StatisticsScope::ALL[..],
+                Some(self.histogram_settings.scope),
+                Message::SetHistogramScope,
+            ))
+            .push(Text::<'_, Theme>::new("Bins"))
+            .push(
+                TextInput::new("Bins", &self.histogram_settings.bins_string)
+                    .on_input(Message::SetHistogramBins)
+                    .width(80),
+            )
+            .push(Text::<'_, Theme>::new("Range"))
+            .push(
+                TextInput::new("Min", &self.histogram_settings.range_min_string)
+                    .on_input(Message::SetHistogramRangeMin)
+                    .width(100),
+            )
+            .push(
+                TextInput::new("Max", &self.histogram_settings.range_max_string)
+                    .on_input(Message::SetHistogramRangeMax)
+                    .width(100),
+            )
+            .spacing(10)
+            .align_items(Alignment::Center);
+
+        let histogram_statistics = &self.histogram.statistics;
+        let histogram_stuck_bits = if self.histogram.stuck_bits.is_empty() {
+            "none".to_string()
+        } else {
+            self.histogram
+                .stuck_bits
+                .iter()
+                .map(|(bit, value)| format!("{}={}", bit, *value as u8))
+                .collect::<Vec<String>>()
+                .join(", ")
+        };
+        let histogram_info_column = Column::new()
+            .push(Text::<'_, Theme>::new(format!(
+                "Samples {} (outside the range {}) | Bin width {} | Mean {} | σ {} | Min {} | Max {}",
+                self.histogram.samples,
+                self.histogram.outside,
+                format_axis_value(self.histogram.get_bin_width()),
+                format_axis_value(histogram_statistics.mean),
+                format_axis_value(histogram_statistics.standard_deviation()),
+                format_axis_value(histogram_statistics.min),
+                format_axis_value(histogram_statistics.max),
+            )))
+            .push(Text::<'_, Theme>::new(format!(
+                "Stuck bits of the fixed point word (bit=value): {}",
+                histogram_stuck_bits
+            )))
+            .spacing(5);
+
+        let histogram_chart = HistogramChart {
+            histogram: &self.histogram,
+            label: self
+                .histogram_settings
+                .channel
+                .as_deref()
+                .map_or(String::new(), |id| self.device_profile.get_label(id)),
+            theme: self.theme.clone(),
+            cache: &self.histogram_cache,
+        }
+        .view();
+
+        // One channel against another, e.g. alpha vs beta currents or a phase-plane trajectory
+        let xy_x_channel_widget: PickList<'_, String, Vec<String>, String, Message, _, _> =
             iced::widget::pick_list::PickList::new(
-                self.display_mode_select_values.as_ref(),
-                self.display_mode.clone(),
-                Message::SetDisplayMode,
-            );
+                self.signal_store.channel_ids(),
+                self.xy_settings.x_channel.clone(),
+                Message::SetXyChannelX,
+            )
+            .placeholder("X channel");
+
+        let xy_y_channel_widget: PickList<'_, String, Vec<String>, String, Message, _, _> =
+            iced::widget::pick_list::PickList::new(
+                self.signal_store.channel_ids(),
+                self.xy_settings.y_channel.clone(),
+                Message::SetXyChannelY,
+            )
+            .placeholder("Y channel");
+
+        let xy_settings_row: Row<Message> = Row::new()
+            .push(xy_x_channel_widget)
+            .push(xy_y_channel_widget)
+            .push(Text::<'_, Theme>::new("Persistence"))
+            .push(
+                TextInput::new("Samples", &self.xy_settings.persistence_string)
+                    .on_input(Message::SetXyPersistence)
+                    .width(100),
+            )
+            .push(
+                iced::widget::Checkbox::new("Fade", self.xy_settings.fade)
+                    .on_toggle(Message::SetXyFade),
+            )
+            .push(
+                iced::widget::Checkbox::new("Equal aspect", self.xy_settings.equal_aspect)
+                    .on_toggle(Message::SetXyEqualAspect),
+            )
+            .push(
+                iced::widget::Checkbox::new("Space vector", self.xy_settings.space_vector)
+                    .on_toggle(Message::SetXySpaceVector),
+            )
+            .push(
+                iced::widget::pick_list::PickList::new(
+                    self.signal_store.channel_ids(),
+                    self.xy_settings.angle_channel.clone(),
+                    Message::SetXyAngleChannel,
+                )
+                .placeholder("Rotor angle"),
+            )
+            .push(hold_button_xy)
+            .spacing(10)
+            .align_items(Alignment::Center);
+
+        let xy_chart = XyChart {
+            points: &self.xy_points,
+            x_label: self
+                .xy_settings
+                .x_channel
+                .as_deref()
+                .map_or(String::new(), |id| self.device_profile.get_label(id)),
+            y_label: self
+                .xy_settings
+                .y_channel
+                .as_deref()
+                .map_or(String::new(), |id| self.device_profile.get_label(id)),
+            fade: self.xy_settings.fade,
+            equal_aspect: self.xy_settings.equal_aspect,
+            space_vector: self
+                .xy_settings
+                .space_vector
+                .then(|| self.xy_points.last().copied())
+                .flatten(),
+            rotor_angle: self.xy_rotor_angle,
+            theme: self.theme.clone(),
+            cache: &self.xy_cache,
+        }
+        .view();
+
+        // Readout of the measurement cursors displayed under the chart
+        let mut measurement_readout_column = Column::new().spacing(2);
+        if let Some(cursors) = self.get_cursors() {
+            for line in cursors.get_readout(&self.signal_store, &self.device_profile) {
+                measurement_readout_column =
+                    measurement_readout_column.push(Text::<'_, Theme>::new(line).size(14));
+            }
+        }
+
+        let y_axis_row = Row::new()
+            .push(Text::<'_, Theme>::new("Pane"))
+            .push(pane_selection_widget)
+            .push(Text::<'_, Theme>::new("Y axis"))
+            .push(y_scale_selection_widget)
+            .push(y_manual_min_input_widget)
+            .push(y_manual_max_input_widget)
+            .push(reset_zoom_button)
+            .push(undo_zoom_button)
+            .push(measurement_cursors_button)
+            .push(hold_button)
+            .push(statistics_button)
+            .push(statistics_scope_widget)
+            .push(markers_button)
+            .height(iced::Length::Shrink)
+            .spacing(15)
+            .align_items(iced::Alignment::Center);
 
         // Selecting serial ports from a dropdown
         let serialport_selection_widget: PickList<'_, String, &[String], String, Message> =
@@ -757,6 +3603,23 @@ impl Application for Rtm {
                 .on_input(Message::SetRunningBuffer)
                 .width(125);
 
+        // Render FPS settings and the measured effective FPS
+        let render_fps_input_widget: TextInput<'_, Message> =
+            TextInput::new("30", &self.render_settings.fps_string)
+                .on_input(Message::SetRenderFps)
+                .width(60);
+
+        let render_fps_text_info_widget = Text::<'_, Theme>::new(format!(
+            "Render FPS: {} | Effective FPS: {:.1}",
+            self.render_settings.fps, self.render_settings.effective_fps
+        ));
+
+        let render_fps_row = Row::new()
+            .push(render_fps_input_widget)
+            .push(render_fps_text_info_widget)
+            .spacing(10)
+            .align_items(iced::Alignment::Center);
+
         // Printing out X axis values
         let x_axis_data_widget =
             Text::<'_, Theme>::new(format!("Samples: {}", x_axis_data_internal));
@@ -848,7 +3711,7 @@ impl Application for Rtm {
         // buffer is full before it is full of data, thus the charts starts moving
         let running_mode_progress_bar_widget = iced::widget::ProgressBar::new(
             0.0..=1.0,
-            self.new_data_points.0.len() as f32 / self.running_buffer.size as f32,
+            running_buffer_filled / self.running_buffer.size as f32,
         );
 
         let tooltip_test = iced::widget::tooltip(
@@ -871,6 +3734,12 @@ impl Application for Rtm {
                     .push(mode_selection_widget)
                     .align_items(iced::Alignment::Start),
             )
+            .push(
+                Column::new()
+                    .push(decimation_title)
+                    .push(decimation_selection_widget)
+                    .align_items(iced::Alignment::Start),
+            )
             .width(Length::Fill)
             .spacing(10)
             .align_items(iced::Alignment::Center);
@@ -928,26 +3797,73 @@ impl Application for Rtm {
                     //.push(text_data_time)
                     .push(buffer_row)
                     .push(running_mode_progress_bar_widget)
-                    .push(gui::DefaultChart::view(DefaultChart::new(
-                        self.new_data_points.clone(),
-                        self.data.clone(),
-                        self.theme.clone(),
-                    )))
+                    .push_maybe(alarm_banner)
+                    .push(y_axis_row)
+                    .push(chart_panes_row)
+                    .push(measurement_readout_column)
+                    .push(step_response_row)
+                    .push_maybe(step_response_readout)
+                    .push(reference_row)
                     .width(Length::Fill)
                     .height(Length::Fill)
                     .align_items(iced::Alignment::Start)
                     .spacing(10);
             }
             Some(display_mode) if display_mode == "default" => {
-                // Only chart column without progress bar is used when the default mode is
+                // History scrolling row
+                let history_row = Row::new()
+                    .push(history_span_title)
+                    .push(history_span_input_widget)
+                    .push(history_position_widget)
+                    .push(history_live_button)
+                    .height(iced::Length::Shrink)
+                    .spacing(15)
+                    .align_items(iced::Alignment::Center);
+
+                // Chart column with history scrolling row is used when the default mode is
                 // selected
                 chart_column = Column::new()
                     //       .push(text_data_time)
-                    .push(gui::DefaultChart::view(DefaultChart::new(
-                        self.new_data_points.clone(),
-                        self.data.clone(),
-                        self.theme.clone(),
-                    )))
+                    .push(history_row)
+                    .push_maybe(alarm_banner)
+                    .push(y_axis_row)
+                    .push(chart_panes_row)
+                    .push(measurement_readout_column)
+                    .push(step_response_row)
+                    .push_maybe(step_response_readout)
+                    .push(reference_row)
+                    .width(Length::Fill)
+                    .height(Length::Fill)
+                    .align_items(iced::Alignment::Start)
+                    .spacing(10);
+            }
+            Some(display_mode) if display_mode == "trigger" => {
+                // Trigger settings row
+                let trigger_row = Row::new()
+                    .push(Text::<'_, Theme>::new("Trigger"))
+                    .push(trigger_channel_widget)
+                    .push(trigger_edge_widget)
+                    .push(trigger_level_input_widget)
+                    .push(trigger_pre_samples_input_widget)
+                    .push(trigger_post_samples_input_widget)
+                    .push(trigger_arming_widget)
+                    .push(arm_trigger_button)
+                    .height(iced::Length::Shrink)
+                    .spacing(15)
+                    .align_items(iced::Alignment::Center);
+
+                // Chart column with the captured window around the trigger when the trigger mode
+                // is selected
+                chart_column = Column::new()
+                    .push(trigger_row)
+                    .push(trigger_status_widget)
+                    .push_maybe(alarm_banner)
+                    .push(y_axis_row)
+                    .push(chart_panes_row)
+                    .push(measurement_readout_column)
+                    .push(step_response_row)
+                    .push_maybe(step_response_readout)
+                    .push(reference_row)
                     .width(Length::Fill)
                     .height(Length::Fill)
                     .align_items(iced::Alignment::Start)
@@ -979,6 +3895,8 @@ impl Application for Rtm {
                     .push(stop_output_file_button)
                     .push(iced::widget::Space::new(0, 10))
                     .push(selection_mode_row)
+                    .push(render_fps_row)
+                    .push(pane_layout_column)
                     .push(data_serialized_text_widget)
                     .push(x_axis_data_widget.clone())
                     .push(data_serialized_widget)
@@ -1024,6 +3942,8 @@ impl Application for Rtm {
                     .push(stop_output_file_button)
                     .push(selection_mode_row)
                     .push(custom_button_from_out)
+                    .push(render_fps_row)
+                    .push(pane_layout_column)
                     .push(data_serialized_text_widget)
                     .push(x_axis_data_widget.clone())
                     .push(data_serialized_widget)
@@ -1062,6 +3982,86 @@ impl Application for Rtm {
                         .push(Row::new().height(Length::Fill)),
                 );
             }
+            Some(display_mode) if display_mode == "channels" => {
+                // Channel table with the device profile, the settings are applied to the charts
+                // and to the output file
+                content = Column::new()
+                    .push(connection_info_row)
+                    .push(profile_row)
+                    .push(transform_column)
+                    .push(expression_column)
+                    .push(filter_column)
+                    .push(bit_field_column)
+                    .push(iced::widget::Scrollable::new(channel_table_column))
+                    .spacing(15)
+                    .width(Length::Fill)
+                    .height(Length::Fill);
+            }
+            Some(display_mode) if display_mode == "alarms" => {
+                // Alarm rules are saved with the device profile, the events are logged also when
+                // another desktop is displayed
+                content = Column::new()
+                    .push(connection_info_row)
+                    .push(alarm_rule_column)
+                    .push(alarm_log_header_row)
+                    .push(iced::widget::Scrollable::new(alarm_log_column))
+                    .spacing(15)
+                    .width(Length::Fill)
+                    .height(Length::Fill);
+            }
+            Some(display_mode) if display_mode == "watch" => {
+                // Watch table for quasi-static signals where a scrolling plot is the wrong tool
+                content = Column::new()
+                    .push(connection_info_row)
+                    .push(clear_chart_button)
+                    .push(iced::widget::Scrollable::new(watch_table_column))
+                    .spacing(15)
+                    .width(Length::Fill)
+                    .height(Length::Fill);
+            }
+            Some(display_mode) if display_mode == "spectrum" => {
+                // Spectrum of the samples displayed in the chart of the selected display mode,
+                // so the held, zoomed or triggered window can be analysed
+                content = Column::new()
+                    .push(connection_info_row)
+                    .push(spectrum_settings_row)
+                    .push(spectrum_info_column)
+                    .push(spectrum_chart)
+                    .spacing(15)
+                    .width(Length::Fill)
+                    .height(Length::Fill);
+            }
+            Some(display_mode) if display_mode == "histogram" => {
+                content = Column::new()
+                    .push(connection_info_row)
+                    .push(histogram_settings_row)
+                    .push(histogram_info_column)
+                    .push(histogram_chart)
+                    .spacing(15)
+                    .width(Length::Fill)
+                    .height(Length::Fill);
+            }
+            Some(display_mode) if display_mode == "xy" => {
+                // XY chart of the newest samples of the chart range of the selected display mode
+                content = Column::new()
+                    .push(connection_info_row)
+                    .push(xy_settings_row)
+                    .push(xy_chart)
+                    .spacing(15)
+                    .width(Length::Fill)
+                    .height(Length::Fill);
+            }
+            Some(display_mode) if display_mode == "spectrogram" => {
+                // Spectrogram scrolls with the chart range of the selected display mode
+                content = Column::new()
+                    .push(connection_info_row)
+                    .push(spectrogram_settings_row)
+                    .push(spectrogram_info_text)
+                    .push(spectrogram_chart)
+                    .spacing(15)
+                    .width(Length::Fill)
+                    .height(Length::Fill);
+            }
 
             _ => {
                 content =
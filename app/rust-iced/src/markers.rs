@@ -0,0 +1,111 @@
+/*
+
+MIT License
+
+Copyright (c) 2024 Petr Zakopal, Deparment of Electric Drives and Traction, CTU FEE
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+/*----------------------------------------------------------------------------*/
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+
+// Timestamped note at a sample of the timeline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Marker {
+    pub sample: u128,   // X axis value of the marked sample
+    pub time: String,   // Local time at which the marker was dropped
+    pub note: String,
+}
+
+// Markers of the current capture ordered by the sample
+#[derive(Debug, Clone, Default)]
+pub struct MarkerList {
+    pub markers: Vec<Marker>,
+    pub note: String,   // Note of the next marker inserted by user
+}
+
+impl MarkerList {
+    // Inserting the marker with the prepared note, the index of the new marker is returned
+    pub fn add(&mut self, sample: u128) -> usize {
+        let note = match self.note.trim() {
+            "" => format!("Marker {}", self.markers.len() + 1),
+            note => note.to_string(),
+        };
+        let index = self.markers.partition_point(|marker| marker.sample <= sample);
+        self.markers.insert(
+            index,
+            Marker {
+                sample,
+                time: Local::now().format("%Y-%m-%d %H:%M:%S%.3f").to_string(),
+                note,
+            },
+        );
+        index
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        if index < self.markers.len() {
+            self.markers.remove(index);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.markers.clear();
+    }
+
+    // Notes of the markers at the sample for the event column of the CSV files, several notes are
+    // separated by a semicolon and the field is quoted when needed
+    pub fn get_csv_event(&self, sample: u128) -> String {
+        let start = self.markers.partition_point(|marker| marker.sample < sample);
+        let notes: Vec<&str> = self.markers[start..]
+            .iter()
+            .take_while(|marker| marker.sample == sample)
+            .map(|marker| marker.note.as_str())
+            .collect();
+        let event = notes.join("; ");
+        if event.contains([',', '"', '\n']) {
+            format!("\"{}\"", event.replace('"', "\"\""))
+        } else {
+            event
+        }
+    }
+
+    // Markers are saved next to the capture file, so also the markers added after the samples were
+    // written are kept with the capture
+    pub fn save(&self, capture_path: &str) -> std::io::Result<()> {
+        let file = std::fs::File::create(Self::get_path(capture_path))?;
+        serde_json::to_writer_pretty(file, &self.markers)?;
+        Ok(())
+    }
+
+    // Markers saved next to the capture, e.g. of a capture loaded as the reference
+    pub fn load(capture_path: &str) -> std::io::Result<Vec<Marker>> {
+        let file = std::fs::File::open(Self::get_path(capture_path))?;
+        let markers: Vec<Marker> = serde_json::from_reader(file)?;
+        Ok(markers)
+    }
+
+    fn get_path(capture_path: &str) -> String {
+        let stem = capture_path.strip_suffix(".csv").unwrap_or(capture_path);
+        format!("{}.markers.json", stem)
+    }
+}
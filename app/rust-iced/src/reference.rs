@@ -0,0 +1,319 @@
+/*
+
+MIT License
+
+Copyright (c) 2024 Petr Zakopal, Deparment of Electric Drives and Traction, CTU FEE
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+/*----------------------------------------------------------------------------*/
+
+use std::fmt;
+use std::fs;
+
+use crate::channels::DeviceProfile;
+use crate::markers::{Marker, MarkerList};
+use crate::signal_store::SignalStore;
+use crate::trigger::Trigger;
+
+// Most samples kept per channel of the reference, longer captures are truncated
+pub const REFERENCE_MAX_SAMPLES: usize = 1 << 20;
+// Most points of one reference trace passed to the chart, the chart decimates them further
+pub const REFERENCE_MAX_POINTS: usize = 4000;
+
+// How the reference is shifted against the displayed samples
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReferenceAlignment {
+    #[default]
+    Sample,     // Samples of the reference keep their X axis values, shifted by the offset
+    Trigger,    // Trigger of the reference is placed on the displayed trigger, shifted by the offset
+}
+
+impl ReferenceAlignment {
+    pub const ALL: [ReferenceAlignment; 2] = [ReferenceAlignment::Sample, ReferenceAlignment::Trigger];
+}
+
+impl fmt::Display for ReferenceAlignment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReferenceAlignment::Sample => write!(f, "Align by sample"),
+            ReferenceAlignment::Trigger => write!(f, "Align by trigger"),
+        }
+    }
+}
+
+// Alignment and the difference channel of the reference selected by user
+#[derive(Debug, Clone)]
+pub struct ReferenceSettings {
+    pub alignment: ReferenceAlignment,
+    pub offset: f64,                        // Parsed manual shift of the reference in samples
+    pub offset_string: String,              // String format of the shift inserted by user
+    pub difference_channel: Option<String>, // Variable ID of the channel compared with the reference
+}
+
+impl Default for ReferenceSettings {
+    fn default() -> Self {
+        Self {
+            alignment: ReferenceAlignment::default(),
+            offset: 0.0,
+            offset_string: "0".to_string(),
+            difference_channel: None,
+        }
+    }
+}
+
+// Pinned snapshot of the displayed samples or a loaded capture, drawn faded behind the live traces
+#[derive(Debug, Clone, Default)]
+pub struct ReferenceTrace {
+    pub name: String,                       // Time of the snapshot or name of the loaded file
+    pub first_sample: f64,                  // X axis value of the first sample
+    pub trigger_sample: Option<f64>,        // X axis value of the trigger of the pinned capture
+    pub channels: Vec<(String, Vec<f64>)>,  // Variable ID and values scaled by the device profile
+    pub markers: Vec<Marker>,               // Markers saved with the loaded capture
+}
+
+impl ReferenceTrace {
+    // Snapshot of the samples in the range, the values are scaled as they are displayed
+    pub fn pin(
+        signal_store: &SignalStore,
+        device_profile: &DeviceProfile,
+        start: usize,
+        end: usize,
+        trigger_sample: Option<f64>,
+        name: String,
+    ) -> Option<Self> {
+        let end = end.min(signal_store.len()).min(start + REFERENCE_MAX_SAMPLES);
+        if start >= end {
+            return None;
+        }
+        let channels = signal_store
+            .channels
+            .iter()
+            .map(|channel| {
+                let values = (start..end)
+                    .map(|sample| device_profile.scale(&channel.id, channel.get(sample)))
+                    .collect();
+                (channel.id.clone(), values)
+            })
+            .collect();
+        Some(Self {
+            name,
+            first_sample: start as f64,
+            trigger_sample,
+            channels,
+            markers: Vec::new(),
+        })
+    }
+
+    // Capture saved to the output file, the first line is the header with the variable IDs, files
+    // without the header take the IDs in the order in which the frames are written, the event
+    // column with the notes of the markers is skipped
+    // rows with another number of fields than the header are rejected, so the values cannot be
+    // assigned to wrong channels
+    pub fn load(path: &str, channel_ids: &[String]) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read the capture {}: {}", path, e))?;
+        let mut lines = content.lines().filter(|line| !line.trim().is_empty()).peekable();
+
+        let mut ids: Vec<String> = {
+            let mut sorted = channel_ids.to_vec();
+            sorted.sort();
+            sorted
+        };
+        let mut has_event_column = false;
+        if let Some(header) = lines.peek() {
+            let mut fields = header.split(',');
+            if fields.next().map(|field| field.trim().parse::<f64>().is_err()) == Some(true) {
+                ids = fields.map(|field| field.trim().to_string()).collect();
+                has_event_column = ids.last().is_some_and(|id| id == "event");
+                if has_event_column {
+                    ids.pop();
+                }
+                lines.next();
+            }
+        }
+        if ids.is_empty() {
+            return Err("The capture has no header and no channels are received to name its columns".to_string());
+        }
+
+        let mut first_sample = None;
+        let mut columns: Vec<Vec<f64>> = vec![Vec::new(); ids.len()];
+        // X axis value, the values of the channels and the event which may contain quoted commas
+        let field_count = ids.len() + 1 + usize::from(has_event_column);
+        for (row, line) in lines.take(REFERENCE_MAX_SAMPLES).enumerate() {
+            let fields: Vec<&str> = line.splitn(field_count, ',').collect();
+            let matches_header = match has_event_column {
+                true => {
+                    fields.len() == field_count
+                        && (!fields[field_count - 1].contains(',')
+                            || fields[field_count - 1].trim_start().starts_with('"'))
+                }
+                false => fields.len() == field_count && !fields[field_count - 1].contains(','),
+            };
+            if !matches_header {
+                return Err(format!(
+                    "Row {} of the capture {} does not have the {} fields of the header",
+                    row + 1,
+                    path,
+                    field_count
+                ));
+            }
+            let mut fields = fields
+                .iter()
+                .map(|field| field.trim().parse::<f64>().unwrap_or(f64::NAN));
+            let x = fields.next().unwrap_or(f64::NAN);
+            if first_sample.is_none() && x.is_finite() {
+                first_sample = Some(x);
+            }
+            for column in columns.iter_mut() {
+                column.push(fields.next().unwrap_or(f64::NAN));
+            }
+        }
+        let first_sample = first_sample.ok_or(format!("The capture {} contains no samples", path))?;
+
+        // Capture without the markers file is loaded without the markers
+        let markers = match MarkerList::load(path) {
+            Ok(markers) => markers,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => {
+                println!("Error loading the markers of the reference {:#?}", e);
+                Vec::new()
+            }
+        };
+
+        let name = path.rsplit(['/', '\\']).next().unwrap_or(path).to_string();
+        Ok(Self {
+            name,
+            first_sample,
+            trigger_sample: None,
+            channels: ids.into_iter().zip(columns).collect(),
+            markers,
+        })
+    }
+
+    pub fn get_sample_count(&self) -> usize {
+        self.channels.first().map_or(0, |(_, values)| values.len())
+    }
+
+    pub fn get_channel(&self, id: &str) -> Option<&[f64]> {
+        self.channels
+            .iter()
+            .find(|(channel_id, _)| channel_id == id)
+            .map(|(_, values)| values.as_slice())
+    }
+
+    pub fn channel_ids(&self) -> Vec<String> {
+        self.channels.iter().map(|(id, _)| id.clone()).collect()
+    }
+
+    // Trigger of the reference, the pinned trigger capture keeps its trigger, otherwise the first
+    // crossing of the trigger level in the reference is searched with the current trigger settings
+    pub fn get_trigger_sample(&self, trigger: &Trigger) -> Option<f64> {
+        if self.trigger_sample.is_some() {
+            return self.trigger_sample;
+        }
+        let values = self.get_channel(trigger.channel.as_deref()?)?;
+        trigger
+            .find_crossing(values)
+            .map(|index| self.first_sample + index as f64)
+    }
+
+    // Value of the reference at the displayed X axis value, the nearest sample is taken
+    pub fn get_value(&self, id: &str, x: f64, shift: f64) -> Option<f64> {
+        let values = self.get_channel(id)?;
+        let index = (x - shift - self.first_sample).round();
+        if index < 0.0 || index >= values.len() as f64 {
+            return None;
+        }
+        Some(values[index as usize]).filter(|value| !value.is_nan())
+    }
+
+    // Points of the reference shifted to the displayed X axis and limited to the displayed range
+    pub fn get_points(&self, id: &str, shift: f64, x_range: (f64, f64)) -> Vec<(f64, f64)> {
+        let Some(values) = self.get_channel(id) else {
+            return Vec::new();
+        };
+        let first_x = self.first_sample + shift;
+        let start = (x_range.0 - first_x).floor().clamp(0.0, values.len() as f64) as usize;
+        let end = ((x_range.1 - first_x).ceil() + 1.0).clamp(0.0, values.len() as f64) as usize;
+        let stride = ((end.saturating_sub(start)) / REFERENCE_MAX_POINTS).max(1);
+        (start..end)
+            .step_by(stride)
+            .filter(|&index| !values[index].is_nan())
+            .map(|index| (first_x + index as f64, values[index]))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Loading the capture written to a temporary file
+    fn load(name: &str, content: &str, channel_ids: &[&str]) -> Result<ReferenceTrace, String> {
+        let path = std::env::temp_dir().join(format!("rtm-{}-{}.csv", name, std::process::id()));
+        fs::write(&path, content).unwrap();
+        let channel_ids: Vec<String> = channel_ids.iter().map(|id| id.to_string()).collect();
+        let reference = ReferenceTrace::load(&path.to_string_lossy(), &channel_ids);
+        fs::remove_file(&path).unwrap();
+        reference
+    }
+
+    #[test]
+    fn header_with_event_column() {
+        let content = "x,@001,@000,event\n\
+                       10,1,2,\n\
+                       11,3,4,\"load step, applied\"\n\
+                       12,5,,note\n";
+        let reference = load("header", content, &[]).unwrap();
+        assert_eq!(reference.first_sample, 10.0);
+        assert_eq!(reference.channel_ids(), ["@001", "@000"]);
+        assert_eq!(reference.get_channel("@001"), Some(&[1.0, 3.0, 5.0][..]));
+        let values = reference.get_channel("@000").unwrap();
+        assert_eq!(values[..2], [2.0, 4.0]);
+        assert!(values[2].is_nan());
+        assert_eq!(reference.get_sample_count(), 3);
+    }
+
+    #[test]
+    fn header_without_event_column() {
+        let reference = load("no-event", "x,@000\n0,1.5\n1,2.5\n", &[]).unwrap();
+        assert_eq!(reference.get_channel("@000"), Some(&[1.5, 2.5][..]));
+    }
+
+    #[test]
+    fn without_header_takes_sorted_channel_ids() {
+        let reference = load("no-header", "5,1,2\n6,3,4\n", &["@001", "@000"]).unwrap();
+        assert_eq!(reference.first_sample, 5.0);
+        assert_eq!(reference.get_channel("@000"), Some(&[1.0, 3.0][..]));
+        assert_eq!(reference.get_channel("@001"), Some(&[2.0, 4.0][..]));
+
+        assert!(load("no-header-no-ids", "5,1,2\n", &[]).is_err());
+    }
+
+    #[test]
+    fn mismatched_rows_are_rejected() {
+        // Missing field, extra field and comma in an unquoted event
+        assert!(load("short", "x,@000,@001\n0,1\n", &[]).is_err());
+        assert!(load("long", "x,@000\n0,1,2\n", &[]).is_err());
+        assert!(load("event", "x,@000,event\n0,1,a,b\n", &[]).is_err());
+        assert!(load("empty", "x,@000\n", &[]).is_err());
+    }
+}
@@ -0,0 +1,670 @@
+/*
+
+MIT License
+
+Copyright (c) 2024 Petr Zakopal, Deparment of Electric Drives and Traction, CTU FEE
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+/*----------------------------------------------------------------------------*/
+
+use std::collections::{HashMap, VecDeque};
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use chrono::Local;
+use memmap2::MmapMut;
+use serde_json::{Map, Value};
+
+use crate::statistics::RunningStatistics;
+
+// Size of one stored sample in bytes, the samples are stored as f64
+const SAMPLE_SIZE_BYTES: usize = 8;
+// Size of one stored fixed point word in bytes, the words are stored as u32
+const WORD_SIZE_BYTES: usize = 4;
+// Number of samples by which the memory mapped file of a channel is grown when it is full
+// 1M samples = 8 MB per channel, the file is remapped only once per chunk
+const SAMPLES_PER_FILE_CHUNK: usize = 1 << 20;
+// Number of raw samples summarised by one block of the lowest level of the min/max pyramid
+const PYRAMID_BASE_BLOCK: usize = 64;
+// Number of blocks of a level which are merged to one block of the level above
+const PYRAMID_LEVEL_FACTOR: usize = 8;
+
+// Empty min/max value used for accumulating, after the first sample it is replaced
+const EMPTY_MIN_MAX: (f64, f64) = (f64::INFINITY, f64::NEG_INFINITY);
+
+// Merging a value to the accumulated min/max, NaN values (channel missing in a frame) are skipped
+// by the f64::min and f64::max functions
+fn merge_min_max(accumulated: (f64, f64), value: (f64, f64)) -> (f64, f64) {
+    (accumulated.0.min(value.0), accumulated.1.max(value.1))
+}
+
+// One variable recorded to the disk
+// the samples are written to a memory mapped file, so the OS decides which part of the history
+// stays in the RAM and which part is only on the disk
+pub struct StoredChannel {
+    pub id: String,                     // Variable ID as received from the FPGA e.g. "@000"
+    file: File,                         // Backing file of the memory map
+    map: Option<MmapMut>,               // Memory map of the backing file, None until first sample
+    word_file: Option<File>,            // Backing file of the fixed point words as received from
+                                        // the FPGA, None for the channels computed by the application
+    word_map: Option<MmapMut>,          // Memory map of the word file, None until first sample
+    capacity: usize,                    // Number of samples which fit to the current map
+    length: usize,                      // Number of samples written
+    pyramid: Vec<Vec<(f64, f64)>>,      // Min/max pyramid, level 0 block = PYRAMID_BASE_BLOCK
+                                        // samples, every level above merges PYRAMID_LEVEL_FACTOR
+                                        // blocks of the level below
+    pending: Vec<(f64, f64)>,           // Min/max of the unfinished block on every level
+    statistics: RunningStatistics,      // Statistics of the whole channel, updated incrementally
+    block_statistics: Vec<RunningStatistics>, // Statistics of every PYRAMID_BASE_BLOCK samples
+    pending_statistics: RunningStatistics,    // Statistics of the unfinished block
+}
+
+impl StoredChannel {
+    fn new(id: String, file: File, word_file: Option<File>) -> Self {
+        Self {
+            id,
+            file,
+            map: None,
+            word_file,
+            word_map: None,
+            capacity: 0,
+            length: 0,
+            pyramid: Vec::new(),
+            pending: Vec::new(),
+            statistics: RunningStatistics::default(),
+            block_statistics: Vec::new(),
+            pending_statistics: RunningStatistics::default(),
+        }
+    }
+
+    // Growing the backing file and remapping it when the map is full
+    fn reserve_sample(&mut self) -> std::io::Result<()> {
+        if self.length == self.capacity {
+            self.capacity += SAMPLES_PER_FILE_CHUNK;
+            self.file
+                .set_len((self.capacity * SAMPLE_SIZE_BYTES) as u64)?;
+            // The file is created by this application only and it is not truncated by anyone
+            // else while mapped
+            self.map = Some(unsafe { MmapMut::map_mut(&self.file)? });
+            if let Some(word_file) = &self.word_file {
+                word_file.set_len((self.capacity * WORD_SIZE_BYTES) as u64)?;
+                self.word_map = Some(unsafe { MmapMut::map_mut(word_file)? });
+            }
+        }
+        Ok(())
+    }
+
+    // Appending the decoded sample and the fixed point word it was decoded from, the word is
+    // None for the samples where the variable was not received
+    fn push(&mut self, value: f64, word: Option<u32>) -> std::io::Result<()> {
+        self.reserve_sample()?;
+        if let Some(map) = self.map.as_mut() {
+            let position = self.length * SAMPLE_SIZE_BYTES;
+            map[position..position + SAMPLE_SIZE_BYTES].copy_from_slice(&value.to_ne_bytes());
+        }
+        if let Some(word_map) = self.word_map.as_mut() {
+            let position = self.length * WORD_SIZE_BYTES;
+            word_map[position..position + WORD_SIZE_BYTES]
+                .copy_from_slice(&word.unwrap_or(0).to_ne_bytes());
+        }
+        self.length += 1;
+        self.push_to_pyramid(value);
+        self.push_to_statistics(value);
+        Ok(())
+    }
+
+    fn push_to_statistics(&mut self, value: f64) {
+        self.statistics.push(value);
+        self.pending_statistics.push(value);
+        if self.length.is_multiple_of(PYRAMID_BASE_BLOCK) {
+            self.block_statistics
+                .push(std::mem::take(&mut self.pending_statistics));
+        }
+    }
+
+    // Statistics of the samples in the range start..end, the whole channel is taken directly and
+    // other ranges are merged from the block statistics with the unaligned edges read raw
+    pub fn statistics(&self, start: usize, end: usize) -> RunningStatistics {
+        let end = end.min(self.length);
+        let start = start.min(end);
+        if start == 0 && end == self.length {
+            return self.statistics;
+        }
+
+        let mut statistics = RunningStatistics::default();
+        let first_block = start.div_ceil(PYRAMID_BASE_BLOCK);
+        let last_block = end / PYRAMID_BASE_BLOCK;
+        if first_block >= last_block {
+            for index in start..end {
+                statistics.push(self.get(index));
+            }
+            return statistics;
+        }
+
+        for index in start..first_block * PYRAMID_BASE_BLOCK {
+            statistics.push(self.get(index));
+        }
+        for block in &self.block_statistics[first_block..last_block] {
+            statistics.merge(block);
+        }
+        for index in last_block * PYRAMID_BASE_BLOCK..end {
+            statistics.push(self.get(index));
+        }
+        statistics
+    }
+
+    // Incrementally updating the min/max pyramid, when a block on one level is finished it is
+    // merged to the pending block of the level above
+    fn push_to_pyramid(&mut self, value: f64) {
+        if self.pending.is_empty() {
+            self.pending.push(EMPTY_MIN_MAX);
+            self.pyramid.push(Vec::new());
+        }
+        self.pending[0] = merge_min_max(self.pending[0], (value, value));
+
+        if !self.length.is_multiple_of(PYRAMID_BASE_BLOCK) {
+            return;
+        }
+
+        let mut level = 0;
+        loop {
+            let finished_block = self.pending[level];
+            self.pending[level] = EMPTY_MIN_MAX;
+            self.pyramid[level].push(finished_block);
+
+            if self.pending.len() == level + 1 {
+                self.pending.push(EMPTY_MIN_MAX);
+                self.pyramid.push(Vec::new());
+            }
+            self.pending[level + 1] = merge_min_max(self.pending[level + 1], finished_block);
+
+            if !self.pyramid[level].len().is_multiple_of(PYRAMID_LEVEL_FACTOR) {
+                break;
+            }
+            level += 1;
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.length
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    // Raw sample at the index, NaN when the index is out of the recorded range
+    pub fn get(&self, index: usize) -> f64 {
+        match self.map.as_ref() {
+            Some(map) if index < self.length => {
+                let position = index * SAMPLE_SIZE_BYTES;
+                let mut bytes = [0u8; SAMPLE_SIZE_BYTES];
+                bytes.copy_from_slice(&map[position..position + SAMPLE_SIZE_BYTES]);
+                f64::from_ne_bytes(bytes)
+            }
+            _ => f64::NAN,
+        }
+    }
+
+    // Fixed point word of the sample at the index as it was sent by the FPGA, None when the
+    // channel is computed by the application or the variable was not received in the frame
+    pub fn get_word(&self, index: usize) -> Option<u32> {
+        let word_map = self.word_map.as_ref()?;
+        if self.get(index).is_nan() {
+            return None;
+        }
+        let position = index * WORD_SIZE_BYTES;
+        let mut bytes = [0u8; WORD_SIZE_BYTES];
+        bytes.copy_from_slice(&word_map[position..position + WORD_SIZE_BYTES]);
+        Some(u32::from_ne_bytes(bytes))
+    }
+
+    // True for the received channels which keep the fixed point words
+    pub fn has_words(&self) -> bool {
+        self.word_file.is_some()
+    }
+
+    // Min and max of the samples in the range start..end
+    // the unaligned edges are read from the raw samples and the rest is taken from the pyramid
+    // levels, so the cost does not depend on the length of the range
+    pub fn min_max(&self, start: usize, end: usize) -> Option<(f64, f64)> {
+        let end = end.min(self.length);
+        let mut start = start.min(end);
+        let mut end_aligned = end;
+        let mut accumulated = EMPTY_MIN_MAX;
+
+        while start < end_aligned && !start.is_multiple_of(PYRAMID_BASE_BLOCK) {
+            let value = self.get(start);
+            accumulated = merge_min_max(accumulated, (value, value));
+            start += 1;
+        }
+        while end_aligned > start && !end_aligned.is_multiple_of(PYRAMID_BASE_BLOCK) {
+            end_aligned -= 1;
+            let value = self.get(end_aligned);
+            accumulated = merge_min_max(accumulated, (value, value));
+        }
+
+        let mut block_start = start / PYRAMID_BASE_BLOCK;
+        let mut block_end = end_aligned / PYRAMID_BASE_BLOCK;
+        let mut level = 0;
+        while block_start < block_end && level < self.pyramid.len() {
+            if level + 1 < self.pyramid.len() {
+                while block_start < block_end && !block_start.is_multiple_of(PYRAMID_LEVEL_FACTOR) {
+                    accumulated = merge_min_max(accumulated, self.pyramid[level][block_start]);
+                    block_start += 1;
+                }
+                while block_end > block_start && !block_end.is_multiple_of(PYRAMID_LEVEL_FACTOR) {
+                    block_end -= 1;
+                    accumulated = merge_min_max(accumulated, self.pyramid[level][block_end]);
+                }
+                block_start /= PYRAMID_LEVEL_FACTOR;
+                block_end /= PYRAMID_LEVEL_FACTOR;
+                level += 1;
+            } else {
+                for block in &self.pyramid[level][block_start..block_end] {
+                    accumulated = merge_min_max(accumulated, *block);
+                }
+                break;
+            }
+        }
+
+        if accumulated.0 <= accumulated.1 {
+            Some(accumulated)
+        } else {
+            None
+        }
+    }
+}
+
+// Store of all received samples of all variables
+// replaces keeping the whole history in the VecDeque objects, so the history is limited only by
+// the disk space and the chart can still be zoomed out to the whole run thanks to the pyramid
+pub struct SignalStore {
+    pub directory: PathBuf,                     // Directory with one backing file per channel
+    pub channels: Vec<StoredChannel>,           // Channels in order of the first appearance
+    pub channel_indexes: HashMap<String, usize>, // Variable ID to the position in channels
+    length: usize,                              // Number of recorded frames (X axis samples)
+    started: Instant,                           // Start of the recording
+    block_times: Vec<f64>,                      // Receive time in seconds from the start of every
+                                                // PYRAMID_BASE_BLOCK-th frame
+}
+
+impl Default for SignalStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SignalStore {
+    pub fn new() -> Self {
+        let local_time = Local::now().format("%Y-%m-%d-%H:%M:%S").to_string();
+        Self {
+            directory: PathBuf::from("./../".to_owned() + &local_time + "-rtm-history"),
+            channels: Vec::new(),
+            channel_indexes: HashMap::new(),
+            length: 0,
+            started: Instant::now(),
+            block_times: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.length
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    // Variable IDs of the stored channels, the position + 1 is the index used by the charts
+    pub fn channel_ids(&self) -> Vec<String> {
+        self.channels.iter().map(|channel| channel.id.clone()).collect()
+    }
+
+    // Backing file of the samples of the channel at the position
+    pub fn get_channel_path(&self, position: usize) -> PathBuf {
+        self.directory.join(format!("{}.f64", position + 1))
+    }
+
+    // Creating the channel with its backing files, the word file only for the received channels
+    fn create_channel(&mut self, id: &str, received: bool) -> std::io::Result<usize> {
+        std::fs::create_dir_all(&self.directory)?;
+        let position = self.channels.len();
+        let open = |extension: &str| {
+            OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(true)
+                .open(self.directory.join(format!("{}.{}", position + 1, extension)))
+        };
+        let file = open("f64")?;
+        let word_file = if received { Some(open("u32")?) } else { None };
+        let mut channel = StoredChannel::new(id.to_string(), file, word_file);
+        // Channel which appeared later than the others is aligned to the X axis with NaN values
+        for _ in 0..self.length {
+            channel.push(f64::NAN, None)?;
+        }
+        self.channels.push(channel);
+        self.channel_indexes.insert(id.to_string(), position);
+        Ok(position)
+    }
+
+    // Storing one received frame with var_id:var_data pairs and the fixed point words of the
+    // received variables, returns the X axis value of the frame
+    pub fn push_frame(
+        &mut self,
+        frame: &Map<String, Value>,
+        words: &HashMap<String, u32>,
+    ) -> std::io::Result<u128> {
+        if self.length.is_multiple_of(PYRAMID_BASE_BLOCK) {
+            self.block_times.push(self.started.elapsed().as_secs_f64());
+        }
+        for (key, value) in frame {
+            let word = words.get(key).copied();
+            let position = match self.channel_indexes.get(key) {
+                Some(&position) => position,
+                None => self.create_channel(key, word.is_some())?,
+            };
+            self.channels[position].push(value.as_f64().unwrap_or(f64::NAN), word)?;
+        }
+        // Channels missing in this frame are kept aligned
+        for channel in self.channels.iter_mut() {
+            if channel.len() == self.length {
+                channel.push(f64::NAN, None)?;
+            }
+        }
+        self.length += 1;
+        Ok((self.length - 1) as u128)
+    }
+
+    // Sample rate estimated from the receive times of the frames in the range start..end
+    // the frames are received in batches, so the estimate is precise only for longer ranges
+    pub fn get_sample_rate(&self, start: usize, end: usize) -> Option<f64> {
+        let first_block = start.div_ceil(PYRAMID_BASE_BLOCK);
+        let last_block = (end.min(self.length).saturating_sub(1) / PYRAMID_BASE_BLOCK)
+            .min(self.block_times.len().saturating_sub(1));
+        if last_block <= first_block {
+            return None;
+        }
+        let elapsed = self.block_times[last_block] - self.block_times[first_block];
+        if elapsed <= 0.0 {
+            return None;
+        }
+        Some(((last_block - first_block) * PYRAMID_BASE_BLOCK) as f64 / elapsed)
+    }
+
+    // Min and max of all channels in the range start..end, taken from the pyramids
+    pub fn min_max(&self, start: usize, end: usize) -> Option<(f64, f64)> {
+        self.channels
+            .iter()
+            .filter_map(|channel| channel.min_max(start, end))
+            .reduce(merge_min_max)
+    }
+
+    // Min and max of the channels at the positions in the range start..end, used for the Y axis
+    // of a chart pane which displays only some of the channels, scale maps the raw min/max of the
+    // channel (variable ID, min/max) to the displayed values
+    pub fn channels_min_max(
+        &self,
+        positions: &[usize],
+        start: usize,
+        end: usize,
+        scale: impl Fn(&str, (f64, f64)) -> (f64, f64),
+    ) -> Option<(f64, f64)> {
+        positions
+            .iter()
+            .filter_map(|&position| self.channels.get(position))
+            .filter_map(|channel| {
+                channel
+                    .min_max(start, end)
+                    .map(|min_max| scale(&channel.id, min_max))
+            })
+            .reduce(merge_min_max)
+    }
+
+    // Preparing the data of the channels at the positions for the chart in the range start..end in
+    // the same form as they were kept in the memory before (x-samples, HashMap with data from
+    // variables indexed from 1)
+    // when the range is longer than 2 * max_buckets, every bucket is represented by its min and
+    // max value, so no spikes are hidden when zooming out
+    pub fn window(
+        &self,
+        positions: &[usize],
+        start: usize,
+        end: usize,
+        max_buckets: usize,
+    ) -> (VecDeque<u128>, HashMap<u128, VecDeque<f64>>) {
+        let end = end.min(self.length);
+        let start = start.min(end);
+        let mut x_values: VecDeque<u128> = VecDeque::new();
+        let mut y_values: HashMap<u128, VecDeque<f64>> = HashMap::new();
+        let max_buckets = max_buckets.max(1);
+
+        if end - start <= 2 * max_buckets {
+            x_values.extend((start..end).map(|x| x as u128));
+            for &position in positions.iter() {
+                let channel = &self.channels[position];
+                y_values.insert(
+                    position as u128 + 1,
+                    (start..end).map(|x| channel.get(x)).collect(),
+                );
+            }
+        } else {
+            let bucket_size = (end - start).div_ceil(max_buckets);
+            for bucket_start in (start..end).step_by(bucket_size) {
+                x_values.push_back(bucket_start as u128);
+                x_values.push_back((bucket_start + bucket_size / 2) as u128);
+            }
+            for &position in positions.iter() {
+                let channel = &self.channels[position];
+                let mut values = VecDeque::with_capacity(x_values.len());
+                for bucket_start in (start..end).step_by(bucket_size) {
+                    let bucket_end = (bucket_start + bucket_size).min(end);
+                    let (min, max) = channel
+                        .min_max(bucket_start, bucket_end)
+                        .unwrap_or((f64::NAN, f64::NAN));
+                    values.push_back(min);
+                    values.push_back(max);
+                }
+                y_values.insert(position as u128 + 1, values);
+            }
+        }
+
+        (x_values, y_values)
+    }
+
+    // Channels and the range start..end which can be saved by a worker thread
+    pub fn get_snapshot(&self, start: usize, end: usize) -> StoreSnapshot {
+        let end = end.min(self.length);
+        StoreSnapshot {
+            channels: (0..self.channels.len())
+                .map(|position| (self.channels[position].id.clone(), self.get_channel_path(position)))
+                .collect(),
+            start: start.min(end),
+            end,
+        }
+    }
+
+    // Removing all recorded data, the backing files are deleted
+    pub fn clear(&mut self) {
+        self.channels.clear();
+        self.channel_indexes.clear();
+        self.length = 0;
+        self.started = Instant::now();
+        self.block_times.clear();
+        if self.directory.exists() {
+            if let Err(e) = std::fs::remove_dir_all(&self.directory) {
+                println!("Error removing the history directory {:#?}", e);
+            }
+        }
+    }
+}
+
+impl Drop for SignalStore {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+// Range of the recorded samples which is read from the backing files of the channels, so it can
+// be saved off the update loop while the store is receiving
+#[derive(Debug, Clone)]
+pub struct StoreSnapshot {
+    pub channels: Vec<(String, PathBuf)>, // Variable ID and backing file of every channel
+    pub start: usize,                     // First sample of the range
+    pub end: usize,                       // End of the range, not included
+}
+
+impl StoreSnapshot {
+    // Saving the samples to a CSV file in the format of the output file, the header has the
+    // variable IDs, the values are scaled by the closure and the last column has the events of
+    // the samples, the samples are read by SAMPLES_PER_FILE_CHUNK rows
+    pub fn write_csv(
+        &self,
+        path: &str,
+        scale: impl Fn(&str, f64) -> f64,
+        event: impl Fn(u128) -> String,
+    ) -> std::io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        let ids: Vec<&str> = self.channels.iter().map(|(id, _)| id.as_str()).collect();
+        writeln!(writer, "x,{},event", ids.join(","))?;
+        for chunk_start in (self.start..self.end).step_by(SAMPLES_PER_FILE_CHUNK) {
+            let chunk_end = (chunk_start + SAMPLES_PER_FILE_CHUNK).min(self.end);
+            let columns = self
+                .channels
+                .iter()
+                .map(|(_, channel_path)| read_samples(channel_path, chunk_start, chunk_end))
+                .collect::<std::io::Result<Vec<Vec<f64>>>>()?;
+            for sample in chunk_start..chunk_end {
+                write!(writer, "{}", sample)?;
+                for ((id, _), column) in self.channels.iter().zip(columns.iter()) {
+                    write!(writer, ",{}", scale(id, column[sample - chunk_start]))?;
+                }
+                writeln!(writer, ",{}", event(sample as u128))?;
+            }
+        }
+        writer.flush()
+    }
+}
+
+// Reading the samples start..end of a channel from its backing file, so the samples can be read
+// by a worker thread while the store is receiving, end must not exceed the recorded samples
+pub fn read_samples(path: &Path, start: usize, end: usize) -> std::io::Result<Vec<f64>> {
+    let mut file = File::open(path)?;
+    file.seek(SeekFrom::Start((start * SAMPLE_SIZE_BYTES) as u64))?;
+    let mut bytes = vec![0u8; end.saturating_sub(start) * SAMPLE_SIZE_BYTES];
+    file.read_exact(&mut bytes)?;
+    Ok(bytes
+        .chunks_exact(SAMPLE_SIZE_BYTES)
+        .map(|sample| f64::from_ne_bytes(sample.try_into().unwrap_or_default()))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Store in its own temporary directory, removed when the store is dropped
+    fn get_store(name: &str) -> SignalStore {
+        let mut store = SignalStore::new();
+        store.directory = std::env::temp_dir().join(format!("rtm-{}-{}", name, std::process::id()));
+        store
+    }
+
+    // Pseudo-random values, so the extremes are spread over all pyramid levels
+    fn get_values(length: usize) -> Vec<f64> {
+        let mut state: u64 = 12345;
+        (0..length)
+            .map(|_| {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                (state >> 33) as f64 / (1u64 << 31) as f64 - 0.5
+            })
+            .collect()
+    }
+
+    fn scan_min_max(values: &[f64]) -> Option<(f64, f64)> {
+        let values: Vec<f64> = values.iter().copied().filter(|value| !value.is_nan()).collect();
+        if values.is_empty() {
+            return None;
+        }
+        Some(values.iter().fold(EMPTY_MIN_MAX, |accumulated, value| {
+            merge_min_max(accumulated, (*value, *value))
+        }))
+    }
+
+    #[test]
+    fn pyramid_min_max_matches_scan() {
+        let mut store = get_store("pyramid");
+        // Over three pyramid levels and not aligned to the blocks
+        let length = PYRAMID_BASE_BLOCK * PYRAMID_LEVEL_FACTOR.pow(3) + 37;
+        let mut values = get_values(length);
+        // Channel missing in some frames
+        for value in values.iter_mut().skip(100).step_by(7) {
+            *value = f64::NAN;
+        }
+        for value in values.iter() {
+            let mut frame = Map::new();
+            if !value.is_nan() {
+                frame.insert("@000".to_string(), Value::from(*value));
+            }
+            frame.insert("@001".to_string(), Value::from(1.0));
+            store.push_frame(&frame, &HashMap::new()).unwrap();
+        }
+        assert_eq!(store.len(), length);
+        let channel = &store.channels[store.channel_indexes["@000"]];
+
+        let mut ranges = vec![(0, length), (0, 0), (5, 6), (100, 101), (64, 128), (63, 4097)];
+        let mut state = 0;
+        for _ in 0..500 {
+            state = (state * 7919 + 104729) % length;
+            let start = state;
+            state = (state * 7919 + 104729) % length;
+            ranges.push((start.min(state), start.max(state)));
+        }
+        for (start, end) in ranges {
+            assert_eq!(
+                channel.min_max(start, end),
+                scan_min_max(&values[start..end]),
+                "range {}..{}",
+                start,
+                end
+            );
+        }
+        // Range over the end is limited to the recorded samples
+        assert_eq!(channel.min_max(length - 10, length + 100), scan_min_max(&values[length - 10..]));
+        assert_eq!(store.min_max(0, length), scan_min_max(&values).map(|(min, max)| (min, max.max(1.0))));
+    }
+
+    #[test]
+    fn clear_removes_directory() {
+        let mut store = get_store("clear");
+        let mut frame = Map::new();
+        frame.insert("@000".to_string(), Value::from(1.0));
+        store.push_frame(&frame, &HashMap::new()).unwrap();
+        assert!(store.directory.exists());
+        store.clear();
+        assert!(!store.directory.exists());
+        assert!(store.is_empty());
+    }
+}
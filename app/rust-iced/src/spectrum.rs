@@ -0,0 +1,481 @@
+/*
+
+MIT License
+
+Copyright (c) 2024 Petr Zakopal, Deparment of Electric Drives and Traction, CTU FEE
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+/*----------------------------------------------------------------------------*/
+
+use std::f64::consts::PI;
+use std::fmt;
+
+// Largest number of samples taken from the buffer window for one FFT
+pub const MAX_FFT_LENGTH: usize = 1 << 16;
+// Number of the highest peaks marked in the spectrum
+pub const SPECTRUM_PEAK_COUNT: usize = 5;
+// Highest harmonic included in the THD
+const THD_MAX_HARMONIC: usize = 50;
+// Number of bins around the expected frequency searched for the harmonic, covers the leakage of
+// the fundamental which is not exactly at a bin
+const HARMONIC_SEARCH_BINS: usize = 2;
+// Segment lengths of the short-time FFT selectable in the spectrogram
+pub const SPECTROGRAM_SEGMENT_LENGTHS: [usize; 6] = [64, 128, 256, 512, 1024, 2048];
+// Largest number of samples taken from the buffer window for one spectrogram
+pub const SPECTROGRAM_MAX_SAMPLES: usize = 1 << 20;
+// Limits of the spectrogram cells, every cell is one rectangle in the chart, so the hop between
+// the segments grows and the bins are merged for longer windows
+pub const SPECTROGRAM_MAX_COLUMNS: usize = 256;
+pub const SPECTROGRAM_MAX_ROWS: usize = 128;
+
+// Window applied to the samples before the FFT
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WindowFunction {
+    #[default]
+    Hann,
+    Hamming,
+    Blackman,
+    FlatTop,
+}
+
+impl WindowFunction {
+    pub const ALL: [WindowFunction; 4] = [
+        WindowFunction::Hann,
+        WindowFunction::Hamming,
+        WindowFunction::Blackman,
+        WindowFunction::FlatTop,
+    ];
+
+    // Coefficient of the window for the sample n of length samples
+    pub fn coefficient(&self, n: usize, length: usize) -> f64 {
+        if length < 2 {
+            return 1.0;
+        }
+        let x = 2.0 * PI * n as f64 / (length - 1) as f64;
+        match self {
+            WindowFunction::Hann => 0.5 - 0.5 * x.cos(),
+            WindowFunction::Hamming => 0.54 - 0.46 * x.cos(),
+            WindowFunction::Blackman => 0.42 - 0.5 * x.cos() + 0.08 * (2.0 * x).cos(),
+            WindowFunction::FlatTop => {
+                0.21557895 - 0.41663158 * x.cos() + 0.277263158 * (2.0 * x).cos()
+                    - 0.083578947 * (3.0 * x).cos()
+                    + 0.006947368 * (4.0 * x).cos()
+            }
+        }
+    }
+}
+
+// Displayed in the PickList
+impl fmt::Display for WindowFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WindowFunction::Hann => write!(f, "Hann"),
+            WindowFunction::Hamming => write!(f, "Hamming"),
+            WindowFunction::Blackman => write!(f, "Blackman"),
+            WindowFunction::FlatTop => write!(f, "Flat-top"),
+        }
+    }
+}
+
+// Scale of the magnitude axis of the spectrum
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MagnitudeScale {
+    Linear,
+    #[default]
+    Decibel,
+}
+
+impl MagnitudeScale {
+    pub const ALL: [MagnitudeScale; 2] = [MagnitudeScale::Linear, MagnitudeScale::Decibel];
+
+    pub fn apply(&self, magnitude: f64) -> f64 {
+        match self {
+            MagnitudeScale::Linear => magnitude,
+            // Floor avoids -inf for the empty bins
+            MagnitudeScale::Decibel => 20.0 * magnitude.max(1e-12).log10(),
+        }
+    }
+}
+
+// Displayed in the PickList
+impl fmt::Display for MagnitudeScale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MagnitudeScale::Linear => write!(f, "Linear"),
+            MagnitudeScale::Decibel => write!(f, "dB"),
+        }
+    }
+}
+
+// In-place iterative radix-2 FFT, the length must be a power of two
+pub fn fft(real: &mut [f64], imaginary: &mut [f64]) {
+    let length = real.len();
+    if length < 2 {
+        return;
+    }
+
+    // Bit reversal permutation
+    let mut j = 0;
+    for i in 1..length {
+        let mut bit = length >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            real.swap(i, j);
+            imaginary.swap(i, j);
+        }
+    }
+
+    // Butterflies
+    let mut size = 2;
+    while size <= length {
+        let angle = -2.0 * PI / size as f64;
+        let (step_sin, step_cos) = angle.sin_cos();
+        for start in (0..length).step_by(size) {
+            let (mut twiddle_real, mut twiddle_imaginary) = (1.0, 0.0);
+            for k in 0..size / 2 {
+                let even = start + k;
+                let odd = even + size / 2;
+                let odd_real = real[odd] * twiddle_real - imaginary[odd] * twiddle_imaginary;
+                let odd_imaginary = real[odd] * twiddle_imaginary + imaginary[odd] * twiddle_real;
+                real[odd] = real[even] - odd_real;
+                imaginary[odd] = imaginary[even] - odd_imaginary;
+                real[even] += odd_real;
+                imaginary[even] += odd_imaginary;
+                let next_twiddle_real = twiddle_real * step_cos - twiddle_imaginary * step_sin;
+                twiddle_imaginary = twiddle_real * step_sin + twiddle_imaginary * step_cos;
+                twiddle_real = next_twiddle_real;
+            }
+        }
+        size <<= 1;
+    }
+}
+
+// Single sided amplitude spectrum of one channel
+#[derive(Debug, Clone, Default)]
+pub struct Spectrum {
+    pub sample_rate: f64,       // Sample rate used for the frequency axis
+    pub frequencies: Vec<f64>,  // Frequency of every bin from 0 to the Nyquist frequency
+    pub magnitudes: Vec<f64>,   // Amplitude of every bin, corrected by the window gain
+}
+
+impl Spectrum {
+    // Windowed FFT of the samples, NaN samples (channel missing in a frame) are taken as zero and
+    // the samples are zero padded to the next power of two
+    pub fn compute(samples: &[f64], window: WindowFunction, sample_rate: f64) -> Self {
+        let length = samples.len();
+        if length < 2 {
+            return Self::default();
+        }
+        let fft_length = length.next_power_of_two();
+        let mut real = vec![0.0; fft_length];
+        let mut imaginary = vec![0.0; fft_length];
+        let mut window_sum = 0.0;
+        for (n, sample) in samples.iter().enumerate() {
+            let coefficient = window.coefficient(n, length);
+            window_sum += coefficient;
+            real[n] = if sample.is_nan() { 0.0 } else { sample * coefficient };
+        }
+        fft(&mut real, &mut imaginary);
+
+        let bins = fft_length / 2 + 1;
+        let frequencies = (0..bins)
+            .map(|bin| bin as f64 * sample_rate / fft_length as f64)
+            .collect();
+        let magnitudes = (0..bins)
+            .map(|bin| {
+                let magnitude = real[bin].hypot(imaginary[bin]) / window_sum;
+                // Energy of the negative frequencies is added to the positive ones
+                if bin == 0 || bin == fft_length / 2 {
+                    magnitude
+                } else {
+                    2.0 * magnitude
+                }
+            })
+            .collect();
+
+        Self {
+            sample_rate,
+            frequencies,
+            magnitudes,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.magnitudes.is_empty()
+    }
+
+    // Frequency resolution of the bins
+    pub fn get_bin_width(&self) -> f64 {
+        match self.frequencies.get(1) {
+            Some(frequency) => *frequency,
+            None => 0.0,
+        }
+    }
+
+    // Highest local maxima of the spectrum without the DC bin, as (frequency, magnitude)
+    pub fn find_peaks(&self, count: usize) -> Vec<(f64, f64)> {
+        let mut peaks: Vec<(f64, f64)> = (1..self.magnitudes.len().saturating_sub(1))
+            .filter(|&bin| {
+                self.magnitudes[bin] > self.magnitudes[bin - 1]
+                    && self.magnitudes[bin] >= self.magnitudes[bin + 1]
+            })
+            .map(|bin| (self.frequencies[bin], self.magnitudes[bin]))
+            .collect();
+        peaks.sort_by(|a, b| b.1.total_cmp(&a.1));
+        peaks.truncate(count);
+        peaks
+    }
+
+    // Highest bin around the frequency, as (frequency, magnitude)
+    fn get_peak_near(&self, frequency: f64) -> Option<(f64, f64)> {
+        let bin_width = self.get_bin_width();
+        if bin_width <= 0.0 {
+            return None;
+        }
+        let center = (frequency / bin_width).round() as usize;
+        if center >= self.magnitudes.len() {
+            return None;
+        }
+        let first = center.saturating_sub(HARMONIC_SEARCH_BINS).max(1);
+        let last = (center + HARMONIC_SEARCH_BINS).min(self.magnitudes.len() - 1);
+        (first..=last)
+            .map(|bin| (self.frequencies[bin], self.magnitudes[bin]))
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+    }
+
+    // Total harmonic distortion in percent relative to the fundamental, the fundamental is the
+    // highest peak when not selected, returns (fundamental frequency, THD)
+    pub fn get_thd(&self, fundamental: Option<f64>) -> Option<(f64, f64)> {
+        let (fundamental_frequency, fundamental_magnitude) = match fundamental {
+            Some(frequency) => self.get_peak_near(frequency)?,
+            None => *self.find_peaks(1).first()?,
+        };
+        if fundamental_magnitude <= 0.0 {
+            return None;
+        }
+        let nyquist = self.sample_rate / 2.0;
+        let harmonics_power: f64 = (2..=THD_MAX_HARMONIC)
+            .map(|harmonic| harmonic as f64 * fundamental_frequency)
+            .take_while(|frequency| *frequency < nyquist)
+            .filter_map(|frequency| self.get_peak_near(frequency))
+            .map(|(_, magnitude)| magnitude * magnitude)
+            .sum();
+        Some((
+            fundamental_frequency,
+            100.0 * harmonics_power.sqrt() / fundamental_magnitude,
+        ))
+    }
+}
+
+// Column of the spectrogram with the magnitudes in dB, the bins are merged into at most
+// SPECTROGRAM_MAX_ROWS rows
+fn get_column(spectrum: &Spectrum) -> Vec<f64> {
+    let bins = spectrum.magnitudes.len();
+    let rows = bins.min(SPECTROGRAM_MAX_ROWS);
+    (0..rows)
+        .map(|row| {
+            let magnitude = spectrum.magnitudes[row * bins / rows..(row + 1) * bins / rows]
+                .iter()
+                .fold(0.0_f64, |max, magnitude| max.max(*magnitude));
+            MagnitudeScale::Decibel.apply(magnitude)
+        })
+        .collect()
+}
+
+// Short-time FFT of one channel, every column is the dB spectrum of one segment
+#[derive(Debug, Clone, Default)]
+pub struct Spectrogram {
+    pub channel: String,        // Variable ID of the analysed channel
+    pub window: WindowFunction, // Window applied to every segment
+    pub sample_rate: f64,       // Sample rate used for the frequency axis
+    pub segment_length: usize,  // Number of samples of one segment
+    pub first_sample: usize,    // Index of the first sample of the first segment in the store
+    pub hop: usize,             // Number of samples between the starts of the segments
+    pub columns: Vec<Vec<f64>>, // Magnitude in dB of every row of every segment, rows from 0 Hz
+                                // to the Nyquist frequency
+}
+
+impl Spectrogram {
+    // Spectrogram of the samples start..end of the channel, the samples are read by read(first, end)
+    // the columns of this spectrogram are kept when the channel and the settings are the same and
+    // only the segments after its last column are computed, so the spectrogram scrolls with the
+    // received samples and the columns before start are dropped, otherwise the segments are
+    // aligned to the newest sample, the bins merged into one row keep their highest magnitude,
+    // so no harmonic is hidden
+    pub fn update(
+        &self,
+        channel: &str,
+        window: WindowFunction,
+        segment_length: usize,
+        sample_rate: f64,
+        (start, end): (usize, usize),
+        read: impl Fn(usize, usize) -> Vec<f64>,
+    ) -> Self {
+        if segment_length < 2 || end.saturating_sub(start) < segment_length {
+            return Self::default();
+        }
+        let span = end - start - segment_length;
+        let hop = (segment_length / 2)
+            .max(span.div_ceil(SPECTROGRAM_MAX_COLUMNS - 1))
+            .max(1);
+
+        let next_segment = self.first_sample + self.columns.len() * self.hop;
+        let continued = !self.is_empty()
+            && self.channel == channel
+            && self.window == window
+            && self.segment_length == segment_length
+            && self.sample_rate == sample_rate
+            && self.hop == hop
+            && self.first_sample < start + hop
+            && (start..=end).contains(&next_segment);
+        let mut spectrogram = if continued {
+            self.clone()
+        } else {
+            Self {
+                channel: channel.to_string(),
+                window,
+                sample_rate,
+                segment_length,
+                first_sample: start + span % hop,
+                hop,
+                columns: Vec::with_capacity(span / hop + 1),
+            }
+        };
+
+        // Segments of the samples after the last column
+        let first_segment = spectrogram.first_sample + spectrogram.columns.len() * hop;
+        if first_segment + segment_length <= end {
+            let samples = read(first_segment, end);
+            for segment_start in (0..=samples.len() - segment_length).step_by(hop) {
+                let spectrum = Spectrum::compute(
+                    &samples[segment_start..segment_start + segment_length],
+                    window,
+                    sample_rate,
+                );
+                spectrogram.columns.push(get_column(&spectrum));
+            }
+        }
+
+        // Columns of the segments which left the range
+        let expired = start
+            .saturating_sub(spectrogram.first_sample)
+            .div_ceil(hop)
+            .max(spectrogram.columns.len().saturating_sub(SPECTROGRAM_MAX_COLUMNS))
+            .min(spectrogram.columns.len());
+        spectrogram.columns.drain(..expired);
+        spectrogram.first_sample += expired * hop;
+        spectrogram
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.columns.is_empty()
+    }
+
+    pub fn get_max_frequency(&self) -> f64 {
+        self.sample_rate / 2.0
+    }
+
+    // Range of the store sample indexes covered by the columns
+    pub fn get_sample_range(&self) -> (f64, f64) {
+        let start = self.first_sample as f64;
+        (
+            start,
+            start + (self.columns.len().saturating_sub(1) * self.hop + self.segment_length) as f64,
+        )
+    }
+
+    // Min and max magnitude in dB of all cells
+    pub fn get_min_max(&self) -> Option<(f64, f64)> {
+        self.columns
+            .iter()
+            .flatten()
+            .fold(None, |min_max, magnitude| match min_max {
+                None => Some((*magnitude, *magnitude)),
+                Some((min, max)) => Some((min.min(*magnitude), max.max(*magnitude))),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: f64 = 1024.0;
+
+    // Sum of the sines (frequency, amplitude) sampled with SAMPLE_RATE
+    fn get_samples(length: usize, sines: &[(f64, f64)]) -> Vec<f64> {
+        (0..length)
+            .map(|n| {
+                let time = n as f64 / SAMPLE_RATE;
+                sines
+                    .iter()
+                    .map(|(frequency, amplitude)| amplitude * (2.0 * PI * frequency * time).sin())
+                    .sum()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn sine_in_one_bin() {
+        let samples = get_samples(1024, &[(64.0, 2.0)]);
+        let spectrum = Spectrum::compute(&samples, WindowFunction::Hann, SAMPLE_RATE);
+        assert_eq!(spectrum.magnitudes.len(), 513);
+        assert_eq!(spectrum.get_bin_width(), 1.0);
+        assert_eq!(spectrum.find_peaks(1), [(64.0, spectrum.magnitudes[64])]);
+        assert!((spectrum.magnitudes[64] - 2.0).abs() < 1e-4);
+        assert!(spectrum.magnitudes[0].abs() < 1e-3);
+    }
+
+    #[test]
+    fn flat_top_amplitude_between_bins() {
+        // Frequency in the middle of two bins, the flat top window keeps the amplitude
+        let samples = get_samples(1024, &[(64.5, 1.0)]);
+        let spectrum = Spectrum::compute(&samples, WindowFunction::FlatTop, SAMPLE_RATE);
+        let (_, magnitude) = spectrum.find_peaks(1)[0];
+        assert!((magnitude - 1.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn thd_of_known_harmonics() {
+        let samples = get_samples(1024, &[(64.0, 1.0), (192.0, 0.03), (320.0, 0.04)]);
+        let spectrum = Spectrum::compute(&samples, WindowFunction::Hann, SAMPLE_RATE);
+        let (fundamental, thd) = spectrum.get_thd(None).unwrap();
+        assert_eq!(fundamental, 64.0);
+        assert!((thd - 5.0).abs() < 1e-3);
+        // Selected fundamental is searched around the frequency
+        let (fundamental, _) = spectrum.get_thd(Some(65.5)).unwrap();
+        assert_eq!(fundamental, 64.0);
+
+        let samples = get_samples(1024, &[(64.0, 1.0)]);
+        let spectrum = Spectrum::compute(&samples, WindowFunction::Hann, SAMPLE_RATE);
+        assert!(spectrum.get_thd(None).unwrap().1 < 1e-3);
+    }
+
+    #[test]
+    fn short_or_empty_samples() {
+        assert!(Spectrum::compute(&[1.0], WindowFunction::Hann, SAMPLE_RATE).is_empty());
+        assert!(Spectrum::compute(&[], WindowFunction::Hann, SAMPLE_RATE).get_thd(None).is_none());
+    }
+}
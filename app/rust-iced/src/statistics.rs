@@ -0,0 +1,127 @@
+/*
+
+MIT License
+
+Copyright (c) 2024 Petr Zakopal, Deparment of Electric Drives and Traction, CTU FEE
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+/*----------------------------------------------------------------------------*/
+
+// Statistics of a channel updated incrementally sample by sample, NaN samples (channel missing in
+// a frame) are skipped
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RunningStatistics {
+    pub count: usize,   // Number of samples
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,      // Running mean (Welford), stable also for long runs
+    m2: f64,            // Sum of the squared differences from the mean
+}
+
+impl Default for RunningStatistics {
+    fn default() -> Self {
+        Self {
+            count: 0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            mean: 0.0,
+            m2: 0.0,
+        }
+    }
+}
+
+impl RunningStatistics {
+    pub fn push(&mut self, value: f64) {
+        if value.is_nan() {
+            return;
+        }
+        self.count += 1;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (value - self.mean);
+    }
+
+    // Merging statistics of another part of the samples (Chan et al.), used for summing the
+    // blocks of the signal store
+    pub fn merge(&mut self, other: &Self) {
+        if other.is_empty() {
+            return;
+        }
+        if self.is_empty() {
+            *self = *other;
+            return;
+        }
+        let count = self.count + other.count;
+        let delta = other.mean - self.mean;
+        self.mean += delta * other.count as f64 / count as f64;
+        self.m2 += other.m2 + delta * delta * (self.count * other.count) as f64 / count as f64;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        self.count = count;
+    }
+
+    // Population variance of the samples
+    pub fn variance(&self) -> f64 {
+        if self.is_empty() {
+            f64::NAN
+        } else {
+            self.m2 / self.count as f64
+        }
+    }
+
+    pub fn standard_deviation(&self) -> f64 {
+        self.variance().sqrt()
+    }
+
+    // RMS from the mean and the variance, mean of squares = mean^2 + variance
+    pub fn rms(&self) -> f64 {
+        (self.mean * self.mean + self.variance()).sqrt()
+    }
+
+    pub fn peak_to_peak(&self) -> f64 {
+        if self.is_empty() {
+            f64::NAN
+        } else {
+            self.max - self.min
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    // Statistics of the values scaled by value * gain + offset, negative gain swaps min and max
+    pub fn scaled(&self, gain: f64, offset: f64) -> Self {
+        if self.is_empty() {
+            return *self;
+        }
+        let (a, b) = (self.min * gain + offset, self.max * gain + offset);
+        Self {
+            count: self.count,
+            min: a.min(b),
+            max: a.max(b),
+            mean: self.mean * gain + offset,
+            m2: self.m2 * gain * gain,
+        }
+    }
+}
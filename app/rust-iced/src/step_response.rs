@@ -0,0 +1,245 @@
+/*
+
+MIT License
+
+Copyright (c) 2024 Petr Zakopal, Deparment of Electric Drives and Traction, CTU FEE
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+/*----------------------------------------------------------------------------*/
+
+// Part of the region at its beginning from which the initial value is averaged
+const INITIAL_PART: f64 = 0.05;
+// Part of the region at its end from which the final value is averaged
+const FINAL_PART: f64 = 0.1;
+// Rise time is measured between these parts of the step
+const RISE_LOW: f64 = 0.1;
+const RISE_HIGH: f64 = 0.9;
+// Settling band in percent of the step by default
+pub const STEP_DEFAULT_SETTLING_BAND: f64 = 2.0;
+
+// Step response of a channel in the region selected by user, the sample indexes are the indexes
+// of the signal store, times are in samples
+#[derive(Debug, Clone)]
+pub struct StepResponse {
+    pub channel: String,                // Variable ID of the response
+    pub initial_value: f64,             // Mean of the beginning of the region
+    pub final_value: f64,               // Mean of the end of the region
+    pub step_sample: f64,               // Step of the reference, or the start of the response
+    pub delay: Option<f64>,             // From the step of the reference to 10 % of the response
+    pub rise_start: f64,                // Response crossing 10 % of the step
+    pub rise_end: f64,                  // Response crossing 90 % of the step
+    pub peak_sample: f64,
+    pub peak_value: f64,
+    pub overshoot: f64,                 // Peak beyond the final value in percent of the step
+    pub settling_sample: f64,           // Last sample outside the settling band
+    pub settling_band: f64,             // Settling band in percent of the step
+    pub steady_state_error: Option<f64>, // Final reference minus the final response
+}
+
+impl StepResponse {
+    // Analysing the scaled response samples starting at the first sample, the reference samples
+    // are of the same range, None when there is no step in the region
+    pub fn analyze(
+        channel: &str,
+        first_sample: usize,
+        response: &[f64],
+        reference: Option<&[f64]>,
+        settling_band: f64,
+    ) -> Option<Self> {
+        let initial_value = get_part_mean(response, 0.0, INITIAL_PART)?;
+        let final_value = get_part_mean(response, 1.0 - FINAL_PART, 1.0)?;
+        let step = final_value - initial_value;
+        if step == 0.0 {
+            return None;
+        }
+        // Position along the step, 0 is the initial value and 1 the final value
+        let progress = |value: f64| (value - initial_value) / step;
+        let first = first_sample as f64;
+
+        // Step of the reference at the half of its change, otherwise the response leaving the
+        // settling band of its initial value
+        let reference_values = match reference {
+            Some(reference) => Some((
+                get_part_mean(reference, 0.0, INITIAL_PART)?,
+                get_part_mean(reference, 1.0 - FINAL_PART, 1.0)?,
+                reference,
+            )),
+            None => None,
+        };
+        let reference_step_index = match reference_values {
+            Some((reference_initial, reference_final, reference)) if reference_final != reference_initial => {
+                Some(reference.iter().position(|value| {
+                    (value - reference_initial) / (reference_final - reference_initial) >= 0.5
+                })?)
+            }
+            _ => None,
+        };
+        let step_index = match reference_step_index {
+            Some(index) => index,
+            None => response
+                .iter()
+                .position(|value| progress(*value).abs() > settling_band / 100.0)?,
+        };
+
+        let rise_start_index = step_index
+            + response[step_index..]
+                .iter()
+                .position(|value| progress(*value) >= RISE_LOW)?;
+        let rise_end_index = rise_start_index
+            + response[rise_start_index..]
+                .iter()
+                .position(|value| progress(*value) >= RISE_HIGH)?;
+
+        let (peak_index, peak_progress) = response[step_index..]
+            .iter()
+            .enumerate()
+            .filter(|(_, value)| !value.is_nan())
+            .map(|(index, value)| (step_index + index, progress(*value)))
+            .max_by(|a, b| a.1.total_cmp(&b.1))?;
+
+        let settling_index = response
+            .iter()
+            .rposition(|value| (progress(*value) - 1.0).abs() > settling_band / 100.0)
+            .map_or(step_index, |index| index + 1);
+
+        Some(Self {
+            channel: channel.to_string(),
+            initial_value,
+            final_value,
+            step_sample: first + step_index as f64,
+            // Delay only from the step of the reference, a reference without a step has none
+            delay: reference_step_index.map(|_| (rise_start_index - step_index) as f64),
+            rise_start: first + rise_start_index as f64,
+            rise_end: first + rise_end_index as f64,
+            peak_sample: first + peak_index as f64,
+            peak_value: response[peak_index],
+            overshoot: ((peak_progress - 1.0) * 100.0).max(0.0),
+            settling_sample: first + settling_index as f64,
+            settling_band,
+            steady_state_error: reference_values
+                .map(|(_, reference_final, _)| reference_final - final_value),
+        })
+    }
+
+    pub fn get_rise_time(&self) -> f64 {
+        self.rise_end - self.rise_start
+    }
+
+    pub fn get_settling_time(&self) -> f64 {
+        (self.settling_sample - self.step_sample).max(0.0)
+    }
+
+    // Vertical markers (sample, label) annotated in the chart
+    pub fn get_markers(&self) -> Vec<(f64, String)> {
+        let mut markers = vec![
+            (self.step_sample, "step".to_string()),
+            (self.rise_start, format!("{:.0} %", RISE_LOW * 100.0)),
+            (self.rise_end, format!("{:.0} %", RISE_HIGH * 100.0)),
+            (self.settling_sample, format!("settled ±{} %", self.settling_band)),
+        ];
+        if self.overshoot > 0.0 {
+            markers.push((self.peak_sample, format!("peak +{:.1} %", self.overshoot)));
+        }
+        markers
+    }
+}
+
+// Mean of the part of the samples between the fractions of the length, NaN samples are skipped
+fn get_part_mean(samples: &[f64], from: f64, to: f64) -> Option<f64> {
+    let length = samples.len();
+    let start = ((length as f64 * from) as usize).min(length.saturating_sub(1));
+    let end = ((length as f64 * to).ceil() as usize).clamp(start + 1, length.max(start + 1));
+    let values: Vec<f64> = samples
+        .get(start..end.min(length))?
+        .iter()
+        .copied()
+        .filter(|value| !value.is_nan())
+        .collect();
+    if values.is_empty() {
+        return None;
+    }
+    Some(values.iter().sum::<f64>() / values.len() as f64)
+}
+
+// Settings of the step response analysis selected by user
+#[derive(Debug, Clone)]
+pub struct StepResponseSettings {
+    pub channel: Option<String>,        // Variable ID of the response
+    pub reference: Option<String>,      // Variable ID of the reference, needed for the delay and
+                                        // the steady-state error
+    pub settling_band: f64,             // Settling band in percent of the step
+    pub settling_band_string: String,   // String format of the settling band inserted by user
+}
+
+impl Default for StepResponseSettings {
+    fn default() -> Self {
+        Self {
+            channel: None,
+            reference: None,
+            settling_band: STEP_DEFAULT_SETTLING_BAND,
+            settling_band_string: STEP_DEFAULT_SETTLING_BAND.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // First order response to the step at the sample 100 of 1000 samples, starting after the delay
+    fn get_response(delay: usize) -> Vec<f64> {
+        (0..1000usize)
+            .map(|n| match n.checked_sub(100 + delay) {
+                Some(time) => 2.0 * (1.0 - (-(time as f64) / 20.0).exp()),
+                None => 0.0,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn delay_from_reference_step() {
+        let reference: Vec<f64> = (0..1000).map(|n| if n < 100 { 0.0 } else { 2.0 }).collect();
+        let step = StepResponse::analyze("@000", 0, &get_response(5), Some(&reference), 2.0).unwrap();
+        assert_eq!(step.step_sample, 100.0);
+        // 10 % of the first order response is reached after -20 ln(0.9) = 2.1 samples
+        assert_eq!(step.delay, Some(8.0));
+        assert!(step.steady_state_error.unwrap().abs() < 1e-6);
+        assert_eq!(step.overshoot, 0.0);
+    }
+
+    #[test]
+    fn no_delay_without_reference_step() {
+        let reference = vec![2.0; 1000];
+        let step = StepResponse::analyze("@000", 0, &get_response(5), Some(&reference), 2.0).unwrap();
+        assert_eq!(step.delay, None);
+        assert_eq!(step.step_sample, 106.0);
+        assert!(step.steady_state_error.unwrap().abs() < 1e-6);
+
+        let step = StepResponse::analyze("@000", 0, &get_response(5), None, 2.0).unwrap();
+        assert_eq!(step.delay, None);
+        assert_eq!(step.steady_state_error, None);
+    }
+
+    #[test]
+    fn no_step() {
+        assert!(StepResponse::analyze("@000", 0, &[1.0; 100], None, 2.0).is_none());
+    }
+}
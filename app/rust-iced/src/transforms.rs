@@ -0,0 +1,244 @@
+/*
+
+MIT License
+
+Copyright (c) 2024 Petr Zakopal, Deparment of Electric Drives and Traction, CTU FEE
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+/*----------------------------------------------------------------------------*/
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::channels::DeviceProfile;
+
+// 1 / sqrt(3) used by the Clarke transform
+const FRAC_1_SQRT_3: f64 = 0.577_350_269_189_625_8;
+
+// Electric drive transform computing two derived channels from the received channels
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum TransformKind {
+    #[default]
+    Clarke,     // a, b, c -> alpha, beta (amplitude invariant)
+    Park,       // alpha, beta, theta -> d, q
+    Polar,      // alpha, beta -> magnitude, angle of the space vector
+}
+
+impl TransformKind {
+    pub const ALL: [TransformKind; 3] = [
+        TransformKind::Clarke,
+        TransformKind::Park,
+        TransformKind::Polar,
+    ];
+
+    // Names of the inputs displayed as placeholders, empty name means the input is not used
+    pub fn get_input_names(&self) -> [&'static str; 3] {
+        match self {
+            TransformKind::Clarke => ["a", "b", "c (optional)"],
+            TransformKind::Park => ["alpha", "beta", "theta [rad]"],
+            TransformKind::Polar => ["alpha", "beta", ""],
+        }
+    }
+
+    // Default variable IDs of the derived channels
+    pub fn get_default_outputs(&self) -> [String; 2] {
+        let names = match self {
+            TransformKind::Clarke => ["alpha", "beta"],
+            TransformKind::Park => ["d", "q"],
+            TransformKind::Polar => ["magnitude", "angle"],
+        };
+        names.map(|name| name.to_string())
+    }
+}
+
+// Displayed in the PickList
+impl fmt::Display for TransformKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransformKind::Clarke => write!(f, "Clarke abc→αβ"),
+            TransformKind::Park => write!(f, "Park αβ→dq"),
+            TransformKind::Polar => write!(f, "Magnitude/angle"),
+        }
+    }
+}
+
+// One transform of the device profile, inputs are the variable IDs of the received or derived
+// channels, the outputs are the variable IDs of the derived channels
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DriveTransform {
+    pub kind: TransformKind,
+    pub inputs: [String; 3],
+    pub outputs: [String; 2],
+}
+
+impl DriveTransform {
+    pub fn new(kind: TransformKind) -> Self {
+        Self {
+            kind,
+            inputs: Default::default(),
+            outputs: kind.get_default_outputs(),
+        }
+    }
+
+    // Changing the kind keeps the inputs, the outputs are renamed only when they were not changed
+    // by user
+    pub fn set_kind(&mut self, kind: TransformKind) {
+        if self.outputs == self.kind.get_default_outputs() {
+            self.outputs = kind.get_default_outputs();
+        }
+        self.kind = kind;
+    }
+
+    // Computing the derived channels from the values of the frame scaled by the device profile,
+    // frames without all the inputs get null derived channels, so every frame has the same channels
+    pub fn apply(&self, frame: &mut Map<String, Value>, device_profile: &DeviceProfile) {
+        let input = |index: usize| -> f64 {
+            let id = &self.inputs[index];
+            match frame.get(id).and_then(|value| value.as_f64()) {
+                Some(value) => device_profile.scale(id, value),
+                None => f64::NAN,
+            }
+        };
+
+        let outputs = match self.kind {
+            TransformKind::Clarke => {
+                let (a, b) = (input(0), input(1));
+                // Without the third phase the currents are expected to sum to zero
+                let c = if self.inputs[2].is_empty() {
+                    -a - b
+                } else {
+                    input(2)
+                };
+                ((2.0 * a - b - c) / 3.0, (b - c) * FRAC_1_SQRT_3)
+            }
+            TransformKind::Park => {
+                let (alpha, beta, theta) = (input(0), input(1), input(2));
+                let (sin, cos) = theta.sin_cos();
+                (alpha * cos + beta * sin, -alpha * sin + beta * cos)
+            }
+            TransformKind::Polar => {
+                let (alpha, beta) = (input(0), input(1));
+                (alpha.hypot(beta), beta.atan2(alpha))
+            }
+        };
+
+        for (id, value) in self.outputs.iter().zip([outputs.0, outputs.1]) {
+            if id.is_empty() {
+                continue;
+            }
+            let value = serde_json::Number::from_f64(value).map_or(Value::Null, Value::Number);
+            frame.insert(id.clone(), value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    fn get_transform(kind: TransformKind, inputs: [&str; 3]) -> DriveTransform {
+        let mut transform = DriveTransform::new(kind);
+        transform.inputs = inputs.map(|input| input.to_string());
+        transform
+    }
+
+    // Derived channels of the transform applied to the frame with the values
+    fn apply(transform: &DriveTransform, values: &[(&str, f64)]) -> (Value, Value) {
+        let mut frame = Map::new();
+        for (id, value) in values {
+            frame.insert(id.to_string(), Value::from(*value));
+        }
+        transform.apply(&mut frame, &DeviceProfile::new("test"));
+        (frame[&transform.outputs[0]].clone(), frame[&transform.outputs[1]].clone())
+    }
+
+    fn as_f64(value: Value) -> f64 {
+        value.as_f64().unwrap()
+    }
+
+    #[test]
+    fn clarke_of_balanced_phases() {
+        let amplitude = 10.0;
+        for angle in [0.0f64, 0.3, 1.2, 2.5, 4.0] {
+            let phases = [0.0, -2.0 * PI / 3.0, 2.0 * PI / 3.0]
+                .map(|shift: f64| amplitude * (angle + shift).cos());
+            let values = [("a", phases[0]), ("b", phases[1]), ("c", phases[2])];
+            let three_phases = get_transform(TransformKind::Clarke, ["a", "b", "c"]);
+            let (alpha, beta) = apply(&three_phases, &values);
+            let (alpha, beta) = (as_f64(alpha), as_f64(beta));
+            // Amplitude invariant, the space vector has the amplitude of the phases and their angle
+            assert!((alpha.hypot(beta) - amplitude).abs() < 1e-9);
+            assert!((alpha - amplitude * angle.cos()).abs() < 1e-9);
+            assert!((beta - amplitude * angle.sin()).abs() < 1e-9);
+
+            // Without the third phase it is computed from the sum of the currents
+            let two_phases = get_transform(TransformKind::Clarke, ["a", "b", ""]);
+            let (alpha_2, beta_2) = apply(&two_phases, &values[..2]);
+            assert!((as_f64(alpha_2) - alpha).abs() < 1e-9);
+            assert!((as_f64(beta_2) - beta).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn park_with_vector_angle() {
+        let transform = get_transform(TransformKind::Park, ["alpha", "beta", "theta"]);
+        let (magnitude, angle): (f64, f64) = (5.0, 0.7);
+        let (d, q) = apply(
+            &transform,
+            &[
+                ("alpha", magnitude * angle.cos()),
+                ("beta", magnitude * angle.sin()),
+                ("theta", angle),
+            ],
+        );
+        assert!((as_f64(d) - magnitude).abs() < 1e-9);
+        assert!(as_f64(q).abs() < 1e-9);
+
+        // Vector 90° ahead of the rotor angle is on the q axis
+        let (d, q) = apply(&transform, &[("alpha", 0.0), ("beta", 2.0), ("theta", 0.0)]);
+        assert!(as_f64(d).abs() < 1e-9);
+        assert!((as_f64(q) - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn polar_magnitude_and_angle() {
+        let transform = get_transform(TransformKind::Polar, ["alpha", "beta", ""]);
+        let (magnitude, angle) = apply(&transform, &[("alpha", -3.0), ("beta", 4.0)]);
+        assert_eq!(as_f64(magnitude), 5.0);
+        assert!((as_f64(angle) - 4.0f64.atan2(-3.0)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn missing_input_gives_null() {
+        // Third phase selected but not received in the frame
+        let transform = get_transform(TransformKind::Clarke, ["a", "b", "c"]);
+        let (alpha, beta) = apply(&transform, &[("a", 1.0), ("b", 2.0)]);
+        assert_eq!(alpha, Value::Null);
+        assert_eq!(beta, Value::Null);
+
+        let transform = get_transform(TransformKind::Park, ["alpha", "beta", "theta"]);
+        let (d, q) = apply(&transform, &[("alpha", 1.0), ("beta", 2.0)]);
+        assert_eq!((d, q), (Value::Null, Value::Null));
+    }
+}
@@ -0,0 +1,373 @@
+/*
+
+MIT License
+
+Copyright (c) 2024 Petr Zakopal, Deparment of Electric Drives and Traction, CTU FEE
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+/*----------------------------------------------------------------------------*/
+
+use std::fmt;
+
+use crate::channels::DeviceProfile;
+use crate::signal_store::SignalStore;
+
+// Edge of the trigger channel crossing the trigger level
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TriggerEdge {
+    #[default]
+    Rising,
+    Falling,
+    Either,
+}
+
+impl TriggerEdge {
+    pub const ALL: [TriggerEdge; 3] = [TriggerEdge::Rising, TriggerEdge::Falling, TriggerEdge::Either];
+}
+
+impl fmt::Display for TriggerEdge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TriggerEdge::Rising => write!(f, "Rising"),
+            TriggerEdge::Falling => write!(f, "Falling"),
+            TriggerEdge::Either => write!(f, "Either"),
+        }
+    }
+}
+
+// How the trigger is armed, as on an oscilloscope
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TriggerArming {
+    #[default]
+    Auto,       // Captures on trigger, without trigger the newest window is displayed
+    Normal,     // Captures only on trigger, the last capture stays displayed
+    Single,     // Captures once and stops until armed again
+}
+
+impl TriggerArming {
+    pub const ALL: [TriggerArming; 3] = [TriggerArming::Auto, TriggerArming::Normal, TriggerArming::Single];
+}
+
+impl fmt::Display for TriggerArming {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TriggerArming::Auto => write!(f, "Auto"),
+            TriggerArming::Normal => write!(f, "Normal"),
+            TriggerArming::Single => write!(f, "Single"),
+        }
+    }
+}
+
+// Window of the signal store captured around a trigger event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TriggerCapture {
+    pub start: usize,           // First sample of the window
+    pub trigger_sample: usize,  // Sample at which the trigger level was crossed
+    pub end: usize,             // Sample after the last sample of the window
+    pub triggered: bool,        // False when the window was captured by the auto mode
+}
+
+// Software oscilloscope trigger working on the samples saved to the signal store
+#[derive(Debug, Clone)]
+pub struct Trigger {
+    pub channel: Option<String>,    // Variable ID of the trigger channel
+    pub edge: TriggerEdge,
+    pub level: f64,                 // Parsed trigger level
+    pub level_string: String,       // String format of the trigger level inserted by user
+    pub pre_samples: usize,         // Number of samples displayed before the trigger
+    pub pre_samples_string: String,
+    pub post_samples: usize,        // Number of samples displayed after the trigger
+    pub post_samples_string: String,
+    pub arming: TriggerArming,
+    pub armed: bool,                // Waiting for a trigger, cleared after the single shot
+    pub capture: Option<TriggerCapture>, // Last captured window
+    pending_trigger: Option<usize>, // Trigger sample waiting for the post trigger samples
+    checked_samples: usize,         // Samples of the store already checked for the trigger
+}
+
+impl Default for Trigger {
+    fn default() -> Self {
+        Self {
+            channel: None,
+            edge: TriggerEdge::default(),
+            level: 0.0,
+            level_string: "0".to_string(),
+            pre_samples: 100,
+            pre_samples_string: "100".to_string(),
+            post_samples: 400,
+            post_samples_string: "400".to_string(),
+            arming: TriggerArming::default(),
+            armed: true,
+            capture: None,
+            pending_trigger: None,
+            checked_samples: 0,
+        }
+    }
+}
+
+impl Trigger {
+    // Starting the trigger again from the newest sample of the store
+    pub fn arm(&mut self, signal_store: &SignalStore) {
+        self.armed = true;
+        self.pending_trigger = None;
+        self.checked_samples = signal_store.len();
+    }
+
+    // Crossing of the trigger level with the selected edge between two consecutive values
+    fn is_crossing(&self, previous: f64, current: f64) -> bool {
+        let rising = previous < self.level && current >= self.level;
+        let falling = previous > self.level && current <= self.level;
+        match self.edge {
+            TriggerEdge::Rising => rising,
+            TriggerEdge::Falling => falling,
+            TriggerEdge::Either => rising || falling,
+        }
+    }
+
+    // Index of the first crossing of the trigger level in already scaled values, used for the
+    // captures which were not triggered live
+    pub fn find_crossing(&self, values: &[f64]) -> Option<usize> {
+        (1..values.len()).find(|&index| self.is_crossing(values[index - 1], values[index]))
+    }
+
+    // Checking the samples received since the last call for the trigger event
+    // called from the data path after the received frames are saved to the signal store, the level
+    // is compared with the values scaled by the device profile as they are displayed
+    pub fn process(&mut self, signal_store: &SignalStore, device_profile: &DeviceProfile) {
+        let length = signal_store.len();
+        // The store was cleared
+        if self.checked_samples > length {
+            self.checked_samples = 0;
+            self.pending_trigger = None;
+            self.capture = None;
+        }
+
+        let channel = self
+            .channel
+            .as_ref()
+            .and_then(|id| signal_store.channel_indexes.get(id))
+            .map(|&position| &signal_store.channels[position]);
+
+        if let Some(channel) = channel {
+            for sample in self.checked_samples.max(1)..length {
+                if !self.armed || self.pending_trigger.is_some() {
+                    break;
+                }
+                let previous = device_profile.scale(&channel.id, channel.get(sample - 1));
+                let current = device_profile.scale(&channel.id, channel.get(sample));
+                if self.is_crossing(previous, current) {
+                    self.pending_trigger = Some(sample);
+                }
+            }
+        }
+        self.checked_samples = length;
+
+        // Window is captured when all post trigger samples were received
+        if let Some(trigger_sample) = self.pending_trigger {
+            if length >= trigger_sample + self.post_samples {
+                self.capture = Some(TriggerCapture {
+                    start: trigger_sample.saturating_sub(self.pre_samples),
+                    trigger_sample,
+                    end: trigger_sample + self.post_samples,
+                    triggered: true,
+                });
+                self.pending_trigger = None;
+                // Samples of the captured window are not checked again
+                self.checked_samples = trigger_sample + self.post_samples;
+                if self.arming == TriggerArming::Single {
+                    self.armed = false;
+                }
+            }
+            return;
+        }
+
+        // Without a trigger event for one whole window the auto mode displays the newest samples
+        if self.arming == TriggerArming::Auto {
+            let window_length = self.pre_samples + self.post_samples;
+            let last_capture_end = self.capture.map(|capture| capture.end).unwrap_or(0);
+            if length >= last_capture_end + window_length {
+                self.capture = Some(TriggerCapture {
+                    start: length - window_length,
+                    trigger_sample: length - self.post_samples,
+                    end: length,
+                    triggered: false,
+                });
+            }
+        }
+    }
+
+    // Range of the samples displayed in the trigger mode
+    pub fn get_range(&self, length: usize) -> (usize, usize) {
+        match self.capture {
+            Some(capture) => (capture.start.min(length), capture.end.min(length)),
+            None => (length.saturating_sub(self.pre_samples + self.post_samples), length),
+        }
+    }
+
+    // Position of the trigger sample and the trigger level drawn in the chart
+    pub fn get_marker(&self) -> Option<(f64, f64)> {
+        self.capture
+            .filter(|capture| capture.triggered)
+            .map(|capture| (capture.trigger_sample as f64, self.level))
+    }
+
+    // Text describing the state of the trigger
+    pub fn get_status(&self) -> String {
+        match (self.armed, self.pending_trigger, self.capture) {
+            (_, Some(sample), _) => format!("Triggered at sample {}, capturing", sample),
+            (false, _, _) => "Stopped, arm the trigger for the next capture".to_string(),
+            (true, None, Some(capture)) if capture.triggered => {
+                format!("Armed, last trigger at sample {}", capture.trigger_sample)
+            }
+            (true, None, _) => "Armed, waiting for trigger".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{Map, Value};
+    use std::collections::HashMap;
+
+    // Rising edge at 0.5 with 2 samples before and 3 samples after the trigger
+    fn get_trigger(arming: TriggerArming) -> Trigger {
+        Trigger {
+            channel: Some("@000".to_string()),
+            level: 0.5,
+            pre_samples: 2,
+            post_samples: 3,
+            arming,
+            ..Default::default()
+        }
+    }
+
+    fn get_store(name: &str) -> SignalStore {
+        let mut store = SignalStore::new();
+        store.directory =
+            std::env::temp_dir().join(format!("rtm-trigger-{}-{}", name, std::process::id()));
+        store
+    }
+
+    // Saving the values one by one and processing the trigger after every frame as the data path
+    fn push(store: &mut SignalStore, trigger: &mut Trigger, values: &[f64]) {
+        let device_profile = DeviceProfile::new("test");
+        for value in values {
+            let mut frame = Map::new();
+            frame.insert("@000".to_string(), Value::from(*value));
+            store.push_frame(&frame, &HashMap::new()).unwrap();
+            trigger.process(store, &device_profile);
+        }
+    }
+
+    fn get_capture(start: usize, trigger_sample: usize, end: usize) -> Option<TriggerCapture> {
+        Some(TriggerCapture {
+            start,
+            trigger_sample,
+            end,
+            triggered: true,
+        })
+    }
+
+    #[test]
+    fn normal_waits_for_post_trigger_samples() {
+        let mut store = get_store("normal");
+        let mut trigger = get_trigger(TriggerArming::Normal);
+        push(&mut store, &mut trigger, &[0.0, 0.0, 0.0, 1.0, 0.0]);
+        assert_eq!(trigger.pending_trigger, Some(3));
+        assert_eq!(trigger.capture, None);
+        assert!(trigger.get_status().starts_with("Triggered at sample 3"));
+
+        // Crossing inside the post trigger samples does not trigger again
+        push(&mut store, &mut trigger, &[1.0]);
+        assert_eq!(trigger.capture, get_capture(1, 3, 6));
+        assert_eq!(trigger.pending_trigger, None);
+        assert!(trigger.armed);
+
+        // Samples after the captured window are checked for the next crossing
+        push(&mut store, &mut trigger, &[0.0, 0.0, 0.0]);
+        assert_eq!(trigger.capture, get_capture(1, 3, 6));
+        push(&mut store, &mut trigger, &[1.0, 0.0, 0.0]);
+        assert_eq!(trigger.capture, get_capture(7, 9, 12));
+        assert_eq!(trigger.get_marker(), Some((9.0, 0.5)));
+    }
+
+    #[test]
+    fn single_stops_after_capture() {
+        let mut store = get_store("single");
+        let mut trigger = get_trigger(TriggerArming::Single);
+        push(&mut store, &mut trigger, &[0.0, 1.0, 1.0, 1.0, 0.0, 1.0, 1.0, 1.0, 1.0]);
+        assert_eq!(trigger.capture, get_capture(0, 1, 4));
+        assert!(!trigger.armed);
+        assert!(trigger.get_status().starts_with("Stopped"));
+
+        // Arming starts from the newest sample, the earlier crossing at 5 is not captured
+        trigger.arm(&store);
+        assert!(trigger.armed);
+        push(&mut store, &mut trigger, &[0.0, 1.0, 1.0, 1.0]);
+        assert_eq!(trigger.capture, get_capture(8, 10, 13));
+        assert!(!trigger.armed);
+    }
+
+    #[test]
+    fn auto_displays_newest_window_without_trigger() {
+        let mut store = get_store("auto");
+        let mut trigger = get_trigger(TriggerArming::Auto);
+        push(&mut store, &mut trigger, &[0.0; 4]);
+        assert_eq!(trigger.capture, None);
+        assert_eq!(trigger.get_range(store.len()), (0, 4));
+        push(&mut store, &mut trigger, &[0.0]);
+        let untriggered = TriggerCapture {
+            start: 0,
+            trigger_sample: 2,
+            end: 5,
+            triggered: false,
+        };
+        assert_eq!(trigger.capture, Some(untriggered));
+        assert_eq!(trigger.get_marker(), None);
+        // Window stays until one whole window is received after it
+        push(&mut store, &mut trigger, &[0.0; 4]);
+        assert_eq!(trigger.capture, Some(untriggered));
+        push(&mut store, &mut trigger, &[0.0]);
+        assert_eq!(trigger.capture.map(|capture| capture.start), Some(5));
+
+        // Trigger event takes over the auto window
+        push(&mut store, &mut trigger, &[1.0, 1.0, 1.0]);
+        assert_eq!(trigger.capture, get_capture(8, 10, 13));
+    }
+
+    #[test]
+    fn cleared_store_resets_trigger() {
+        let mut store = get_store("cleared");
+        let mut trigger = get_trigger(TriggerArming::Normal);
+        push(&mut store, &mut trigger, &[0.0, 1.0, 1.0, 1.0, 0.0, 1.0]);
+        assert_eq!(trigger.capture, get_capture(0, 1, 4));
+        assert_eq!(trigger.pending_trigger, Some(5));
+
+        store.clear();
+        trigger.process(&store, &DeviceProfile::new("test"));
+        assert_eq!(trigger.capture, None);
+        assert_eq!(trigger.pending_trigger, None);
+        assert_eq!(trigger.checked_samples, 0);
+        push(&mut store, &mut trigger, &[0.0, 0.0, 1.0, 1.0, 1.0]);
+        assert_eq!(trigger.capture, get_capture(0, 2, 5));
+    }
+}
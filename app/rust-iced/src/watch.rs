@@ -0,0 +1,100 @@
+/*
+
+MIT License
+
+Copyright (c) 2024 Petr Zakopal, Deparment of Electric Drives and Traction, CTU FEE
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+/*----------------------------------------------------------------------------*/
+
+use std::collections::{BTreeMap, HashMap};
+use std::time::{Duration, Instant};
+
+use serde_json::{Map, Value};
+
+use crate::statistics::RunningStatistics;
+
+// Latest value and statistics of one channel in the watch table
+#[derive(Debug, Clone)]
+pub struct WatchEntry {
+    pub latest: f64,                    // Latest decoded value, not scaled by the device profile
+    pub latest_word: Option<u32>,       // Fixed point word of the latest value as received from
+                                        // the FPGA, None for the channels computed by the application
+    pub statistics: RunningStatistics,  // Min, max and mean since the last clear
+    pub update_rate: f64,               // Measured number of received values per second
+    updates: u32,                       // Number of values received since measured_since
+    measured_since: Instant,            // Start of the current measurement of the update rate
+}
+
+impl WatchEntry {
+    fn new(now: Instant) -> Self {
+        Self {
+            latest: f64::NAN,
+            latest_word: None,
+            statistics: RunningStatistics::default(),
+            update_rate: 0.0,
+            updates: 0,
+            measured_since: now,
+        }
+    }
+}
+
+// Watch window of the latest values of all channels, for quasi-static signals such as DC-link
+// voltage or temperature
+#[derive(Debug, Clone, Default)]
+pub struct WatchTable {
+    pub entries: BTreeMap<String, WatchEntry>, // Variable ID to the watched values
+}
+
+impl WatchTable {
+    // Updating the table with one frame from get_structured_message_data and the fixed point
+    // words of the received variables
+    pub fn push_frame(&mut self, frame: &Map<String, Value>, words: &HashMap<String, u32>, now: Instant) {
+        for (key, value) in frame {
+            let value = value.as_f64().unwrap_or(f64::NAN);
+            let entry = self
+                .entries
+                .entry(key.clone())
+                .or_insert_with(|| WatchEntry::new(now));
+            entry.latest = value;
+            entry.latest_word = words.get(key).copied();
+            entry.statistics.push(value);
+            entry.updates += 1;
+        }
+    }
+
+    // Updating the measured update rates, called on every render tick, the rates are refreshed
+    // once per second
+    pub fn measure(&mut self, now: Instant) {
+        for entry in self.entries.values_mut() {
+            let elapsed = now.saturating_duration_since(entry.measured_since);
+            if elapsed >= Duration::from_secs(1) {
+                entry.update_rate = entry.updates as f64 / elapsed.as_secs_f64();
+                entry.updates = 0;
+                entry.measured_since = now;
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}